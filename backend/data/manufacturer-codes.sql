@@ -0,0 +1,35 @@
+-- Manufacturer specific DTC descriptions, in code-descriptions.sqlite.
+--
+-- P1xxx, B1xxx, C1xxx and U1xxx codes mean something different for each make,
+-- sometimes for each range of model years. A row applies to vehicles of `make`
+-- (as decoded from the VIN, e.g 'FORD') built from `year_from` to `year_to`.
+-- Either year can be NULL to leave that end of the range open.
+--
+-- Codes without a row here fall back to the generic description in `codes`.
+-- Databases without this table use the rows below, which are only inserted
+-- when the table is first created.
+
+CREATE TABLE IF NOT EXISTS manufacturer_codes (
+    id TEXT NOT NULL,
+    make TEXT NOT NULL,
+    year_from INTEGER,
+    year_to INTEGER,
+    desc TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS manufacturer_codes_id ON manufacturer_codes (id);
+
+INSERT INTO manufacturer_codes (id, make, year_from, year_to, desc) VALUES
+    ('C1702', 'HYUNDAI', NULL, NULL, 'Variant Coding Error (ABS/ESC module not coded for this vehicle)'),
+    ('C1702', 'KIA', NULL, NULL, 'Variant Coding Error (ABS/ESC module not coded for this vehicle)'),
+    ('P1000', 'FORD', NULL, NULL, 'OBD-II Monitor Testing Not Complete'),
+    ('P1131', 'FORD', NULL, NULL, 'Lack of HO2S11 Switch - Sensor Indicates Lean'),
+    ('P1450', 'FORD', NULL, NULL, 'Unable to Bleed Up Fuel Tank Vacuum'),
+    ('P1133', 'CHEVROLET', NULL, NULL, 'HO2S Insufficient Switching Bank 1 Sensor 1'),
+    ('P1133', 'GMC', NULL, NULL, 'HO2S Insufficient Switching Bank 1 Sensor 1'),
+    ('P1349', 'TOYOTA', NULL, NULL, 'VVT System Malfunction (Bank 1)'),
+    ('P1135', 'TOYOTA', NULL, NULL, 'Air/Fuel Sensor Heater Circuit Response Malfunction (Bank 1 Sensor 1)'),
+    ('P1456', 'HONDA', NULL, NULL, 'EVAP Emission Control System Leak Detected (Fuel Tank System)'),
+    ('P1148', 'NISSAN', NULL, NULL, 'Closed Loop Control Function (Bank 1)'),
+    ('P1391', 'DODGE', NULL, NULL, 'Intermittent Loss of CMP or CKP'),
+    ('P1391', 'JEEP', NULL, NULL, 'Intermittent Loss of CMP or CKP');
@@ -0,0 +1,21 @@
+// Convert a recording from the old requests.json format to JSON Lines.
+//
+// cargo run --example convert-recording -- ./data/requests.json ./data/requests.jsonl
+// cargo run --example convert-recording -- ./data/requests.json ./data/requests.jsonl.xz
+
+use std::path::PathBuf;
+
+use obdium::replay::convert_legacy_recording;
+
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let (Some(from), Some(to)) = (args.next(), args.next()) else {
+        println!("usage: convert-recording <requests.json> <requests.jsonl[.xz]>");
+        return;
+    };
+
+    match convert_legacy_recording(&PathBuf::from(&from), &PathBuf::from(&to)) {
+        Ok(converted) => println!("converted {converted} requests from {from} to {to}"),
+        Err(err) => println!("when converting {from}: {err}"),
+    }
+}
@@ -0,0 +1,61 @@
+// Search the DTC catalog from the command line.
+//
+// cargo run --example dtc-search -- evap small leak
+// cargo run --example dtc-search -- --category P --range P0400 P04FF oxygen heater
+// cargo run --example dtc-search -- --related P0420
+// cargo run --example dtc-search -- --db ./data/code-descriptions.sqlite misfire
+
+use std::path::PathBuf;
+
+use obdium::catalog::{set_catalog_path, DtcCatalog, DtcQuery};
+use obdium::diagnostics::TroubleCodeCategory;
+
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let mut query = DtcQuery::default();
+    let mut related = None;
+    let mut words = Vec::new();
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--db" => set_catalog_path(PathBuf::from(args.next().unwrap_or_default())),
+            "--related" => related = args.next(),
+            "--limit" => query.limit = args.next().and_then(|n| n.parse().ok()).unwrap_or(0),
+            "--range" => {
+                let from = args.next().unwrap_or_default();
+                let to = args.next().unwrap_or_default();
+                query.range = Some((from, to));
+            }
+            "--category" => {
+                query.category = match args.next().unwrap_or_default().to_uppercase().as_str() {
+                    "P" => Some(TroubleCodeCategory::Powertrain),
+                    "C" => Some(TroubleCodeCategory::Chassis),
+                    "B" => Some(TroubleCodeCategory::Body),
+                    "U" => Some(TroubleCodeCategory::Network),
+                    other => {
+                        println!("unknown category '{other}'. expected one of P, C, B or U.");
+                        return;
+                    }
+                }
+            }
+            _ => words.push(arg),
+        }
+    }
+    query.text = words.join(" ");
+
+    let catalog = DtcCatalog::global();
+    let results = match &related {
+        Some(code) => catalog.related(code, if query.limit == 0 { 20 } else { query.limit }),
+        None => catalog.search(&query),
+    };
+
+    match results {
+        Ok(results) if results.is_empty() => println!("no matching codes."),
+        Ok(results) => {
+            for result in results {
+                println!("{}\t{}", result.code, result.description);
+            }
+        }
+        Err(err) => println!("error: {err}"),
+    }
+}
@@ -5,17 +5,25 @@
 /// to perform actions
 ///
 use super::{
-    ConnectPaylod, ConnectionStatus, Dtc, Setting, VehicleInfo, VehicleInfoExtended, ACTIVE_OBD,
+    Aftertreatment, ConnectPaylod, ConnectionStatus, Dtc, DtcClearResult, FrontendNotification,
+    Setting, VehicleInfo, VehicleInfoExtended, VehicleMatch, ACTIVE_OBD,
 };
 use crate::bridge::{
-    unlisten_events, CustomPid, CUSTOM_PIDS_TRACKED, READINESS_TESTS_LISTENER,
-    USER_COMMAND_LISTENER,
+    unlisten_events, CustomPid, CUSTOM_PIDS_TRACKED, DRIVE_CYCLE, DTC_JOURNAL,
+    READINESS_TESTS_LISTENER, USER_COMMAND_LISTENER,
 };
 use crate::{connect_obd, track_data, OBD};
-use obdium::dicts::PID_INFOS;
-use obdium::scalar::UnitPreferences;
+use obdium::catalog::{DtcCatalog, DtcQuery, VehicleContext};
+use obdium::diagnostics::TroubleCode;
+use obdium::dicts::PidInfo;
+use obdium::drive_cycle::DriveCycleTracker;
+use obdium::fingerprint::FingerprintDb;
+use obdium::history::DtcJournal;
+use obdium::inspection::InspectionRules;
+use obdium::registry::{self, PidError};
+use obdium::scalar::{Unit, UnitPreferences};
 use obdium::vin::VIN;
-use obdium::{Command, PAUSE_OBD_COUNT};
+use obdium::{BankNumber, Command, Mode22Pid, PAUSE_OBD_COUNT};
 use std::sync::atomic::Ordering;
 use std::sync::{Arc, Mutex};
 use std::time::Duration;
@@ -33,25 +41,34 @@ pub fn listen_send_pids(window: &Arc<WebviewWindow>, obd: &Arc<Mutex<OBD>>) {
     std::thread::sleep(Duration::from_millis(50));
 
     let window_arc = Arc::new(window.clone());
-    let pids = {
+    let obd_arc = Arc::clone(obd);
+    let capabilities = {
         let mut obd = obd.lock().unwrap();
-        obd.get_service_supported_pids("01")
+        match obd.load_vehicle_profile() {
+            Ok(profile) => {
+                if let Some((vin, pids)) = obd.untested_mode_22_pids() {
+                    do_test_mode_22_pids(obd_arc, vin, pids);
+                }
+                profile.capabilities
+            }
+            Err(err) => {
+                println!("Could not use a vehicle profile: {err}");
+                obd.discover_capabilities().clone()
+            }
+        }
     };
 
-    println!("Supported pids list: \n{:?}", pids);
+    println!("Supported pids list: \n{:?}", capabilities);
 
-    let supported_pids: Vec<&String> = { pids.values().flatten().collect() };
-
-    let mut supported_pids_info = PID_INFOS.to_owned();
-    supported_pids_info
-        .iter_mut()
-        .for_each(|pid| pid.supported = supported_pids.contains(&&pid.pid.to_string()));
+    let mut supported_pids_info: Vec<PidInfo> = registry::PIDS
+        .iter()
+        .map(|def| PidInfo {
+            supported: capabilities.is_supported(def.service, def.pid),
+            ..PidInfo::from(def)
+        })
+        .collect();
     supported_pids_info.sort_by(|a, b| b.supported.cmp(&a.supported));
 
-    if supported_pids_info.is_empty() {
-        supported_pids_info = PID_INFOS.to_vec();
-    }
-
     PAUSE_OBD_COUNT.fetch_sub(1, Ordering::Relaxed);
     println!("Supported pids: {supported_pids_info:?}");
 
@@ -101,10 +118,12 @@ pub fn listen_send_readiness_test(window: &Arc<WebviewWindow>, obd: &Arc<Mutex<O
             obd.get_advanced_tests_status().to_vec(),
         ]
         .concat();
+        let report = obd.inspection_readiness(&InspectionRules::load());
 
         PAUSE_OBD_COUNT.fetch_sub(1, Ordering::Relaxed);
 
         let _ = window_arc.emit("update-readiness-tests", tests);
+        let _ = window_arc.emit("update-inspection-report", report);
     });
 
     {
@@ -203,6 +222,39 @@ pub fn listen_decode_vin(window: &WebviewWindow) {
     });
 }
 
+pub fn listen_search_dtcs(window: &Arc<WebviewWindow>) {
+    let window_arc = Arc::clone(window);
+    window.listen("search-dtcs", move |event| {
+        let query: DtcQuery = match serde_json::from_str(event.payload()) {
+            Ok(query) => query,
+            Err(err) => {
+                println!("Failed to parse dtc search payload: {err}");
+                return;
+            }
+        };
+
+        match DtcCatalog::global().search(&query) {
+            Ok(results) => {
+                let _ = window_arc.emit("dtc-search-results", results);
+            }
+            Err(err) => println!("when searching dtcs: {err}"),
+        }
+    });
+
+    let window_arc = Arc::clone(window);
+    window.listen("get-related-dtcs", move |event| {
+        // payload is a json string, e.g "\"P0420\""
+        let code: String = serde_json::from_str(event.payload()).unwrap_or_default();
+
+        match DtcCatalog::global().related(&code, 20) {
+            Ok(results) => {
+                let _ = window_arc.emit("related-dtcs", results);
+            }
+            Err(err) => println!("when getting dtcs related to {code}: {err}"),
+        }
+    });
+}
+
 pub fn listen_connect_elm(window: &Arc<WebviewWindow>) {
     let window_arc = Arc::new(window.clone());
     let window_clone = Arc::clone(&window_arc);
@@ -232,6 +284,16 @@ pub fn listen_connect_elm(window: &Arc<WebviewWindow>) {
                 *active = Some(Arc::clone(&obd));
             }
 
+            // might be a different vehicle than last time
+            {
+                let mut journal = DTC_JOURNAL.lock().unwrap();
+                *journal = None;
+            }
+            {
+                let mut drive_cycle = DRIVE_CYCLE.lock().unwrap();
+                *drive_cycle = DriveCycleTracker::new();
+            }
+
             // Usually called once
             do_send_vehicle_details(&window_arc, &obd);
 
@@ -265,9 +327,15 @@ pub fn listen_connect_elm(window: &Arc<WebviewWindow>) {
             listen_send_connection_status(&window_arc, &obd);
             listen_change_obd_settings(&window_arc, &obd);
 
+            listen_set_vehicle_vin(&window_arc, &obd);
             listen_send_pids(&window_arc, &obd);
             listen_send_readiness_test(&window_arc, &obd);
             listen_send_dtcs(&window_arc, &obd);
+            listen_send_freeze_frames(&window_arc, &obd);
+            listen_send_dtc_history(&window_arc);
+            listen_send_drive_cycle(&window_arc);
+            listen_run_evap_test(&window_arc, &obd);
+            listen_send_aftertreatment(&window_arc, &obd);
             listen_clear_dtcs(&window_arc, &obd);
 
             listen_run_user_command(&window_arc);
@@ -275,6 +343,41 @@ pub fn listen_connect_elm(window: &Arc<WebviewWindow>) {
     });
 }
 
+/// VIN entered by the user for a vehicle that doesn't report one
+pub fn listen_set_vehicle_vin(window: &Arc<WebviewWindow>, obd: &Arc<Mutex<OBD>>) {
+    let window_arc = Arc::clone(window);
+    let obd = Arc::clone(obd);
+    window.listen("set-vehicle-vin", move |event| {
+        let vin: String = serde_json::from_str(event.payload()).unwrap_or_default();
+        let vin = match VIN::new(vin.trim()) {
+            Ok(vin) => vin,
+            Err(err) => {
+                println!("when setting vehicle vin: {err}");
+                let _ = window_arc.emit(
+                    "display-notification",
+                    FrontendNotification {
+                        title: "VEHICLE VIN",
+                        description: "The VIN entered is not valid.",
+                    },
+                );
+                return;
+            }
+        };
+
+        let mut obd = obd.lock().unwrap();
+        obd.set_manual_vin(Some(&vin));
+
+        // history was kept in memory without a vin
+        {
+            let mut journal = DTC_JOURNAL.lock().unwrap();
+            *journal = None;
+        }
+
+        do_send_vehicle_info(&window_arc, &mut obd, &vin);
+        do_record_fingerprint(&mut obd, &vin);
+    });
+}
+
 pub fn listen_disconnect_elm(window: &Arc<WebviewWindow>) {
     let window_arc_for_listen = Arc::clone(&window);
     window_arc_for_listen.listen("disconnect-elm", {
@@ -326,7 +429,7 @@ pub fn listen_change_obd_settings(window: &Arc<WebviewWindow>, obd: &Arc<Mutex<O
                     println!("record response has a path: {path}");
                     obd.record_requests(setting.checked, path);
                 } else {
-                    obd.record_requests(setting.checked, "./data/requests.json".into());
+                    obd.record_requests(setting.checked, "./data/requests.jsonl".into());
                 }
             }
             "replay-responses" => obd.replay_requests(setting.checked),
@@ -343,30 +446,136 @@ pub fn listen_send_dtcs(window: &Arc<WebviewWindow>, obd: &Arc<Mutex<OBD>>) {
         let mut obd = obd_arc.lock().unwrap();
         std::thread::sleep(Duration::from_secs(1));
 
-        let codes = [obd.get_trouble_codes(), obd.get_permanant_trouble_codes()].concat();
-        println!("codes: {:?}", codes);
+        let read = obd.read_all_trouble_codes();
+        println!("codes: {:?}", read);
+        do_record_dtc_history(&mut obd, read.as_deref());
+
+        let codes = read.unwrap_or_default();
+
+        let serialized: Vec<Dtc> = codes.into_iter().map(Dtc::from).collect();
+
+        let _ = window_arc.emit("update-dtcs", serialized);
+    });
+}
+
+pub fn listen_send_dtc_history(window: &Arc<WebviewWindow>) {
+    let window_arc = Arc::clone(window);
+    window.listen("get-dtc-history", move |_| {
+        let journal = DTC_JOURNAL.lock().unwrap();
+        if let Some(journal) = journal.as_ref() {
+            let _ = window_arc.emit("dtc-history", journal.clone());
+        }
+    });
+}
+
+pub fn listen_run_evap_test(window: &Arc<WebviewWindow>, obd: &Arc<Mutex<OBD>>) {
+    let obd_arc = Arc::clone(obd);
+    let window_arc = Arc::clone(window);
+    window.listen("run-evap-test", move |_| {
+        let test = {
+            let mut obd = obd_arc.lock().unwrap();
+            obd.start_evap_leak_test()
+        };
+
+        let test = match test {
+            Ok(test) => test,
+            Err(err) => {
+                println!("when starting evap leak test: {err}");
+                let _ = window_arc.emit("evap-test-failed", err.to_string());
+                return;
+            }
+        };
+
+        let _ = window_arc.emit("evap-test-started", test.ecus.clone());
+
+        // the ecu runs the test on its own, it can take several minutes
+        let obd = Arc::clone(&obd_arc);
+        let window = Arc::clone(&window_arc);
+        spawn(async move {
+            while test.elapsed() < 600.0 {
+                sleep(Duration::from_secs(10)).await;
+
+                let mut obd = obd.lock().unwrap();
+                if !obd.is_connected() {
+                    return;
+                }
+
+                if let Some(results) = test.poll(&mut obd) {
+                    let _ = window.emit("evap-test-results", results);
+                    return;
+                }
+            }
+
+            let _ = window.emit(
+                "evap-test-failed",
+                "No EVAP leak test results after 10 minutes.",
+            );
+        });
+    });
+}
 
-        // create serializable DTC struct from TroubleCode struct
-        let serialized: Vec<Dtc> = codes
+pub fn listen_send_aftertreatment(window: &Arc<WebviewWindow>, obd: &Arc<Mutex<OBD>>) {
+    let obd_arc = Arc::clone(obd);
+    let window_arc = Arc::clone(window);
+    window.listen("get-aftertreatment", move |_| {
+        let mut obd = obd_arc.lock().unwrap();
+        let mut banks = vec![(1, BankNumber::Bank1)];
+        if obd.has_second_aftertreatment_bank() {
+            banks.push((2, BankNumber::Bank2));
+        }
+
+        let banks: Vec<Aftertreatment> = banks
             .into_iter()
-            .map(|dtc| Dtc {
-                name: dtc.dtc,
-                category: dtc.category.system_letter().to_string(),
-                description: dtc.description,
-                permanant: dtc.permanant,
-                location: dtc.category.as_str().to_string(),
+            .map(|(number, bank)| {
+                let data = obd.get_aftertreatment_data(bank);
+                Aftertreatment {
+                    bank: number,
+                    health: data.assess(),
+                    data,
+                }
             })
             .collect();
+        drop(obd);
 
-        let _ = window_arc.emit("update-dtcs", serialized);
+        let _ = window_arc.emit("aftertreatment", banks);
+    });
+}
+
+pub fn listen_send_drive_cycle(window: &Arc<WebviewWindow>) {
+    let window_arc = Arc::clone(window);
+    window.listen("get-drive-cycle", move |_| {
+        let drive_cycle = DRIVE_CYCLE.lock().unwrap();
+        let _ = window_arc.emit("drive-cycle", drive_cycle.clone());
+    });
+}
+
+pub fn listen_send_freeze_frames(window: &Arc<WebviewWindow>, obd: &Arc<Mutex<OBD>>) {
+    let obd_arc = Arc::clone(obd);
+    let window_arc = Arc::clone(window);
+    window.listen("get-freeze-frames", move |_| {
+        let mut obd = obd_arc.lock().unwrap();
+        let frames = obd.get_freeze_frames();
+        let _ = window_arc.emit("update-freeze-frames", frames);
     });
 }
 
 pub fn listen_clear_dtcs(window: &Arc<WebviewWindow>, obd: &Arc<Mutex<OBD>>) {
     let obd_arc = Arc::clone(obd);
+    let window_arc = Arc::clone(window);
     window.listen("clear-dtcs", move |_| {
         let mut obd = obd_arc.lock().unwrap();
-        let _ = obd.clear_trouble_codes();
+        match obd.clear_trouble_codes_safely() {
+            Ok(report) => {
+                if let Some(returned) = &report.returned {
+                    do_record_dtc_history(&mut obd, Ok(returned));
+                }
+                let _ = window_arc.emit("dtcs-cleared", DtcClearResult::from(report));
+            }
+            Err(err) => {
+                println!("when clearing trouble codes: {err}");
+                let _ = window_arc.emit("dtc-clear-failed", err.to_string());
+            }
+        }
     });
 }
 
@@ -410,6 +619,16 @@ pub fn listen_run_user_command(window: &Arc<WebviewWindow>) {
                                 .replace("\n", " ")
                         ),
                     );
+
+                    // Known pids are also decoded, from the reply above
+                    if let Some(def) = registry::find_command(command) {
+                        for (card, value) in def.decode_response(&mut obd, response) {
+                            do_send_command_output(
+                                &window_arc,
+                                format!("{card}: {} {}", value.value, value.unit.as_str()),
+                            );
+                        }
+                    }
                 }
                 Err(err) => {
                     do_send_command_output(&window_arc, format!("Response: {}", err));
@@ -466,40 +685,12 @@ pub fn do_send_vehicle_details(window: &Arc<WebviewWindow>, obd: &Arc<Mutex<OBD>
         // send the vin and vehicle details to the frontend
         match obd.get_vin() {
             Some(vin) => {
-                let make = match vin.get_vehicle_make() {
-                    Ok(make) => make,
-                    Err(err) => {
-                        println!(
-                            "failed to resolve vehicle make from vin: '{}'",
-                            vin.get_vin()
-                        );
-                        println!("error: {err}");
-                        "??".to_string()
-                    }
-                };
-
-                let model = match vin.get_vehicle_model() {
-                    Ok(model) => model,
-                    Err(err) => {
-                        println!(
-                            "failed to resolve vehicle model from vin: '{}'",
-                            vin.get_vin()
-                        );
-                        println!("error: {err}");
-                        "??".to_string()
-                    }
-                };
-
-                let v_info = VehicleInfo {
-                    vin: vin.get_vin().to_string(),
-                    make,
-                    model,
-                };
-
-                window.emit("vehicle-details", v_info).unwrap();
+                do_send_vehicle_info(&window, &mut obd, &vin);
+                do_record_fingerprint(&mut obd, &vin);
             }
             None => {
                 println!("error: getting vin. vin is none.");
+                do_send_vehicle_matches(&window, &mut obd);
             }
         };
 
@@ -507,6 +698,131 @@ pub fn do_send_vehicle_details(window: &Arc<WebviewWindow>, obd: &Arc<Mutex<OBD>
     });
 }
 
+/// Send the make and model decoded from `vin`.
+pub fn do_send_vehicle_info(window: &WebviewWindow, obd: &mut OBD, vin: &VIN) {
+    let make = match vin.get_vehicle_make() {
+        Ok(make) => make,
+        Err(err) => {
+            println!(
+                "failed to resolve vehicle make from vin: '{}'",
+                vin.get_vin()
+            );
+            println!("error: {err}");
+            "??".to_string()
+        }
+    };
+
+    let model = match vin.get_vehicle_model() {
+        Ok(model) => model,
+        Err(err) => {
+            println!(
+                "failed to resolve vehicle model from vin: '{}'",
+                vin.get_vin()
+            );
+            println!("error: {err}");
+            "??".to_string()
+        }
+    };
+
+    // used to pick manufacturer specific dtc descriptions
+    if make != "??" {
+        obd.set_vehicle_context(Some(VehicleContext::new(
+            make.clone(),
+            vin.get_model_year().ok(),
+        )));
+    }
+
+    let v_info = VehicleInfo {
+        vin: vin.get_vin().to_string(),
+        make,
+        model,
+    };
+
+    window.emit("vehicle-details", v_info).unwrap();
+}
+
+/// Remember what the vehicle reports under its VIN,
+/// so vehicles like it can be recognized when they don't report one.
+pub fn do_record_fingerprint(obd: &mut OBD, vin: &VIN) {
+    // the demo recording isn't a vehicle to match against
+    if obd.is_replaying() {
+        return;
+    }
+
+    let mut db = match FingerprintDb::open() {
+        Ok(db) => db,
+        Err(err) => {
+            println!("when opening fingerprint database: {err}");
+            return;
+        }
+    };
+
+    obd.record_fingerprint(&mut db, vin);
+    if let Err(err) = db.save() {
+        println!("when saving fingerprint database: {err}");
+    }
+}
+
+/// Suggest known vehicles that look like one without a VIN.
+pub fn do_send_vehicle_matches(window: &WebviewWindow, obd: &mut OBD) {
+    let db = match FingerprintDb::open() {
+        Ok(db) => db,
+        Err(err) => {
+            println!("when opening fingerprint database: {err}");
+            return;
+        }
+    };
+
+    let fingerprint = obd.get_fingerprint();
+    let matches: Vec<VehicleMatch> = db
+        .matches(&fingerprint, 5)
+        .into_iter()
+        .map(|found| VehicleMatch {
+            make: found.vehicle.make,
+            model: found.vehicle.model,
+            model_year: found.vehicle.model_year,
+            engine: found.vehicle.engine,
+            score: found.score,
+        })
+        .collect();
+
+    println!("vehicle fingerprint: {fingerprint:?}");
+    let _ = window.emit("vehicle-matches", matches);
+}
+
+/// Try Mode 22 pids for the model in the background and save the
+/// ones that work in the vehicle profile. The OBD is locked one pid
+/// at a time, polling goes first.
+pub fn do_test_mode_22_pids(obd: Arc<Mutex<OBD>>, vin: String, pids: Vec<Mode22Pid>) {
+    spawn(async move {
+        let mut working = Vec::new();
+        for pid in pids {
+            while PAUSE_OBD_COUNT.load(Ordering::Relaxed) > 0 {
+                sleep(Duration::from_millis(500)).await;
+            }
+
+            {
+                let mut obd = obd.lock().unwrap();
+                if !obd.is_connected() {
+                    return;
+                }
+
+                if obd.read_mode_22_pid(&pid).unit != Unit::NoData {
+                    working.push(pid.pid);
+                }
+            }
+
+            sleep(Duration::from_millis(500)).await;
+        }
+
+        println!("working mode 22 pids for {vin}: {working:?}");
+        let mut obd = obd.lock().unwrap();
+        if let Err(err) = obd.save_mode_22_pids(&vin, working) {
+            println!("when saving mode 22 pids: {err}");
+        }
+    });
+}
+
 pub fn do_send_connection_status(
     window: &WebviewWindow,
     obd: &OBD,
@@ -526,3 +842,30 @@ pub fn do_send_connection_status(
     println!("connection status: {conn_status:?}");
     let _ = window.emit("connection-status", conn_status);
 }
+
+/// Record the codes read in the connected vehicle's DTC history.
+/// Returns the codes that just appeared.
+pub fn do_record_dtc_history(
+    obd: &mut OBD,
+    read: Result<&[TroubleCode], &PidError>,
+) -> Vec<TroubleCode> {
+    let mut journal = DTC_JOURNAL.lock().unwrap();
+    let journal = journal.get_or_insert_with(|| {
+        let Some(vin) = obd.get_vin() else {
+            println!("vin unavailable, dtc history will not be saved.");
+            return DtcJournal::in_memory();
+        };
+
+        DtcJournal::open(vin.get_vin()).unwrap_or_else(|err| {
+            println!("when opening dtc history: {err}");
+            DtcJournal::in_memory()
+        })
+    });
+
+    let appeared = obd.record_dtc_history(journal, read);
+    if let Err(err) = journal.save() {
+        println!("when saving dtc history: {err}");
+    }
+
+    appeared
+}
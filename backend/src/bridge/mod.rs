@@ -1,6 +1,10 @@
 pub mod events;
 
-use obdium::OBD;
+use obdium::{
+    aftertreatment::{AftertreatmentData, AftertreatmentHealth},
+    clear::ClearReport, diagnostics::TroubleCode, drive_cycle::DriveCycleTracker,
+    history::DtcJournal, OBD,
+};
 use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
 use std::{
@@ -13,6 +17,13 @@ pub static ACTIVE_OBD: Lazy<Mutex<Option<Arc<Mutex<OBD>>>>> = Lazy::new(|| Mutex
 pub static USER_COMMAND_LISTENER: Lazy<Mutex<Option<EventId>>> = Lazy::new(|| Mutex::new(None));
 pub static READINESS_TESTS_LISTENER: Lazy<Mutex<Option<EventId>>> = Lazy::new(|| Mutex::new(None));
 
+/// DTC history of the connected vehicle. Opened the first time codes are read.
+pub(crate) static DTC_JOURNAL: Lazy<Mutex<Option<DtcJournal>>> = Lazy::new(|| Mutex::new(None));
+
+/// Monitors completed during the current drive cycle. Restarted on connect.
+pub(crate) static DRIVE_CYCLE: Lazy<Mutex<DriveCycleTracker>> =
+    Lazy::new(|| Mutex::new(DriveCycleTracker::new()));
+
 pub(crate) static CUSTOM_PIDS_TRACKED: Lazy<Mutex<HashMap<String, CustomPid>>> =
     Lazy::new(|| Mutex::new(HashMap::new()));
 
@@ -44,6 +55,17 @@ struct VehicleInfo {
     model: String,
 }
 
+/// Known vehicle that looks like the connected one,
+/// suggested when the vehicle doesn't report its VIN.
+#[derive(Serialize, Deserialize, Clone)]
+struct VehicleMatch {
+    make: String,
+    model: String,
+    model_year: Option<i32>,
+    engine: Option<String>,
+    score: f32,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub(crate) struct CustomPid {
     pub name: String,
@@ -146,10 +168,73 @@ struct Setting {
 
 #[derive(Deserialize, Serialize, Clone)]
 #[serde(rename_all = "camelCase")]
-struct Dtc {
+pub(crate) struct Dtc {
     category: String,
     description: String,
+    description_source: String,
     name: String,
+    confirmed: bool,
+    pending: bool,
     permanant: bool,
     location: String,
+    ecu: String,
+    failure_type: Option<String>,
+    severity: Option<String>,
+}
+
+// create serializable DTC struct from TroubleCode struct
+impl From<TroubleCode> for Dtc {
+    fn from(dtc: TroubleCode) -> Self {
+        Dtc {
+            name: dtc.code(),
+            category: dtc.category.system_letter().to_string(),
+            description: dtc.description,
+            description_source: dtc.description_source.as_str().to_string(),
+            confirmed: dtc.status.confirmed,
+            pending: dtc.status.pending,
+            permanant: dtc.status.permanant,
+            location: dtc.category.as_str().to_string(),
+            ecu: dtc.ecu.unwrap_or_default(),
+            failure_type: dtc.failure_type.map(|ft| ft.as_str().to_string()),
+            severity: dtc.severity.map(|severity| severity.as_str().to_string()),
+        }
+    }
+}
+
+#[derive(Serialize, Clone)]
+pub(crate) struct Aftertreatment {
+    pub bank: u8,
+    pub data: AftertreatmentData,
+    pub health: AftertreatmentHealth,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct DtcClearResult {
+    cleared_ecus: Vec<String>,
+    rejected_ecus: Vec<(String, String)>,
+    returned: Vec<Dtc>,
+
+    /// False if the codes couldn't be read again after clearing
+    verified: bool,
+    snapshot_path: Option<String>,
+}
+
+impl From<ClearReport> for DtcClearResult {
+    fn from(report: ClearReport) -> Self {
+        DtcClearResult {
+            cleared_ecus: report.responses.cleared,
+            rejected_ecus: report.responses.rejected,
+            verified: report.returned.is_some(),
+            returned: report
+                .returned
+                .unwrap_or_default()
+                .into_iter()
+                .map(Dtc::from)
+                .collect(),
+            snapshot_path: report
+                .snapshot_path
+                .map(|path| path.to_string_lossy().to_string()),
+        }
+    }
 }
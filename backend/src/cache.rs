@@ -0,0 +1,97 @@
+// Short lived cache of responses.
+//
+// Several getters decode the same pid (e.g 0101 holds the check engine light,
+// the number of codes, the readiness monitors and the engine type), so a
+// single refresh would send the same request many times. Pids whose value
+// can't change while connected are kept for the session, slow changing ones
+// for a moment, the rest are never cached.
+//
+// Responses are kept by the header (ATSH) they were requested with, so a
+// request sent to a single ECU isn't answered with a broadcast's response.
+
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+use crate::{Response, OBD};
+
+/// How long a response to a request stays valid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CacheTtl {
+    Never,
+    For(Duration),
+
+    /// Until the vehicle is reconnected or its codes are cleared
+    Session,
+}
+
+impl CacheTtl {
+    /// e.g `CacheTtl::for_request("011C")`
+    pub fn for_request(request: &str) -> Self {
+        match request {
+            // supported pid bitmaps
+            "0100" | "0120" | "0140" | "0160" | "0180" | "01A0" | "01C0" | "01E0" => Self::Session,
+
+            // OBD standard, O2 sensor layout, fuel type, maximum values and reference torque
+            "011C" | "0113" | "011D" | "0151" | "014F" | "0150" | "0163" => Self::Session,
+
+            // VIN, calibration ids, calibration verification numbers and ECU name
+            "0902" | "0904" | "0906" | "090A" => Self::Session,
+
+            // monitor status, MIL and number of codes
+            "0101" | "0141" => Self::For(Duration::from_secs(2)),
+
+            _ => Self::Never,
+        }
+    }
+}
+
+// Header the request was sent with (`None` for the default) and the request
+type CacheKey = (Option<String>, String);
+
+#[derive(Debug, Default)]
+pub struct ResponseCache {
+    entries: HashMap<CacheKey, (Instant, CacheTtl, Response)>,
+}
+
+impl ResponseCache {
+    pub(crate) fn get(&self, header: Option<&str>, request: &str) -> Option<Response> {
+        let key = (header.map(str::to_string), request.to_string());
+        let (cached_at, ttl, response) = self.entries.get(&key)?;
+        match ttl {
+            CacheTtl::Session => Some(response.clone()),
+            CacheTtl::For(ttl) if cached_at.elapsed() < *ttl => Some(response.clone()),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn insert(&mut self, header: Option<&str>, request: &str, response: &Response) {
+        let ttl = CacheTtl::for_request(request);
+        if ttl == CacheTtl::Never {
+            return;
+        }
+
+        self.entries.insert(
+            (header.map(str::to_string), request.to_string()),
+            (Instant::now(), ttl, response.clone()),
+        );
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+impl OBD {
+    /// Reuse recent responses to the same request, see `CacheTtl::for_request`.
+    pub fn set_response_cache(&mut self, enabled: bool) {
+        self.cache_responses = enabled;
+        self.response_cache.clear();
+    }
+
+    /// Forget every cached response, they'll be requested again.
+    pub fn clear_response_cache(&mut self) {
+        self.response_cache.clear();
+    }
+}
@@ -0,0 +1,184 @@
+// What each ECU supports.
+//
+// Services 01, 02, 06, 08 and 09 report the ids they support 32 at a time,
+// requested with <service>00, <service>20, <service>40... The last bit of each
+// bitmap says whether the next range is worth requesting. Once read, `query`
+// skips requests for ids no ECU supports instead of waiting for NO DATA.
+
+use std::collections::{BTreeSet, HashMap};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Command, OBD};
+
+/// Services with support bitmaps
+pub const SERVICES: [u8; 5] = [0x01, 0x02, 0x06, 0x08, 0x09];
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Capabilities {
+    /// Supported ids by service, then by ECU.
+    /// Includes the bitmap ids (20, 40...) that chain to the next range.
+    services: HashMap<u8, HashMap<String, Vec<u8>>>,
+}
+
+impl Capabilities {
+    /// Whether any ECU supports `id` of `service`.
+    /// The first bitmap (id 00) is always supported.
+    pub fn is_supported(&self, service: u8, id: u8) -> bool {
+        id == 0x00
+            || self
+                .services
+                .get(&service)
+                .is_some_and(|ecus| ecus.values().any(|ids| ids.contains(&id)))
+    }
+
+    /// Whether `ecu` supports `id` of `service`.
+    pub fn is_supported_by(&self, ecu: &str, service: u8, id: u8) -> bool {
+        self.supported_by(ecu, service).contains(&id)
+    }
+
+    /// Whether the support of `service` has been read and an ECU answered.
+    pub fn is_known(&self, service: u8) -> bool {
+        self.services
+            .get(&service)
+            .is_some_and(|ecus| !ecus.is_empty())
+    }
+
+    /// Ids of `service` supported by any ECU, sorted.
+    pub fn supported(&self, service: u8) -> Vec<u8> {
+        let ids: BTreeSet<u8> = self
+            .services
+            .get(&service)
+            .into_iter()
+            .flat_map(|ecus| ecus.values().flatten().copied())
+            .collect();
+
+        ids.into_iter().collect()
+    }
+
+    pub fn supported_by(&self, ecu: &str, service: u8) -> &[u8] {
+        self.services
+            .get(&service)
+            .and_then(|ecus| ecus.get(ecu))
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// Every ECU that reported support for a service.
+    pub fn ecus(&self) -> Vec<String> {
+        let ecus: BTreeSet<&String> = self
+            .services
+            .values()
+            .flat_map(|ecus| ecus.keys())
+            .collect();
+        ecus.into_iter().cloned().collect()
+    }
+
+    pub(crate) fn insert(&mut self, service: u8, ecus: HashMap<String, Vec<u8>>) {
+        self.services.insert(service, ecus);
+    }
+
+    // Whether `request` is for an id that's known to be unsupported.
+    // Bitmap requests are never skipped, they're how support is found out.
+    pub(crate) fn rejects(&self, request: &str) -> bool {
+        let request = request.replace(' ', "");
+        let (Some(service), Some(id)) = (
+            request
+                .get(..2)
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok()),
+            request
+                .get(2..4)
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok()),
+        ) else {
+            return false;
+        };
+
+        // only frame 0 support is read
+        if service == 0x02 && request.get(4..6) != Some("00") {
+            return false;
+        }
+
+        SERVICES.contains(&service)
+            && id % 0x20 != 0
+            && self.is_known(service)
+            && !self.is_supported(service, id)
+    }
+}
+
+impl OBD {
+    /// Read the support bitmaps of every service and keep them.
+    pub fn discover_capabilities(&mut self) -> &Capabilities {
+        for service in SERVICES {
+            let frame = (service == 0x02).then_some(0);
+            let supported = self.get_supported_ids(service, frame);
+            self.capabilities.insert(service, supported);
+        }
+
+        &self.capabilities
+    }
+
+    /// Support read so far, see `discover_capabilities`.
+    pub fn capabilities(&self) -> &Capabilities {
+        &self.capabilities
+    }
+
+    pub fn is_supported(&self, service: u8, id: u8) -> bool {
+        self.capabilities.is_supported(service, id)
+    }
+
+    /// Walk the support bitmaps of `service`, keyed by ECU.
+    /// `frame` is the freeze frame for service 02.
+    pub fn get_supported_ids(
+        &mut self,
+        service: u8,
+        frame: Option<u8>,
+    ) -> HashMap<String, Vec<u8>> {
+        let mut supported: HashMap<String, Vec<u8>> = HashMap::new();
+        let frame = frame
+            .map(|frame| format!("{frame:02X}"))
+            .unwrap_or_default();
+
+        // <service + 40> <base> [frame] A B C D
+        let header = if frame.is_empty() { 2 } else { 3 };
+        for base in (0x00..=0xE0).step_by(0x20) {
+            let mut next_range = false;
+
+            let request = Command::new_arb(&format!("{service:02X}{base:02X}{frame}"));
+            for message in self.query_messages(request) {
+                if message.data.len() < header + 4
+                    || message.data[0] != service + 0x40
+                    || message.data[1] != base
+                {
+                    continue;
+                }
+
+                let bitmap = u32::from_be_bytes([
+                    message.data[header],
+                    message.data[header + 1],
+                    message.data[header + 2],
+                    message.data[header + 3],
+                ]);
+
+                let ids = supported.entry(message.ecu).or_default();
+                for bit in 0..32u8 {
+                    if bitmap & (1 << (31 - bit)) == 0 {
+                        continue;
+                    }
+
+                    // the last bit of E0 would chain to an id past FF
+                    if let Some(id) = base.checked_add(bit + 1) {
+                        ids.push(id);
+                    }
+                }
+
+                next_range |= bitmap & 1 != 0;
+            }
+
+            if !next_range {
+                break;
+            }
+        }
+
+        supported
+    }
+}
@@ -0,0 +1,52 @@
+use serde::{Deserialize, Serialize};
+
+use crate::vin::VIN;
+
+/// The vehicle codes were read from.
+/// Used to pick manufacturer specific descriptions for P1xxx, B1xxx, C1xxx and U1xxx codes.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VehicleContext {
+    /// Make as decoded from the VIN (e.g "FORD")
+    pub make: String,
+    pub model_year: Option<i32>,
+}
+
+impl VehicleContext {
+    pub fn new(make: impl Into<String>, model_year: Option<i32>) -> Self {
+        Self {
+            make: make.into(),
+            model_year,
+        }
+    }
+
+    /// Decode the make and model year from `vin`.
+    /// Returns `None` if the make can't be resolved.
+    pub fn from_vin(vin: &VIN) -> Option<Self> {
+        let make = vin.get_vehicle_make().ok()?;
+        Some(Self::new(make, vin.get_model_year().ok()))
+    }
+}
+
+/// A row of the `manufacturer_codes` table.
+#[derive(Debug, Clone)]
+pub(crate) struct ManufacturerCode {
+    pub make: String,
+    pub year_from: Option<i32>,
+    pub year_to: Option<i32>,
+    pub description: String,
+}
+
+impl ManufacturerCode {
+    pub fn matches(&self, vehicle: &VehicleContext) -> bool {
+        if !self.make.eq_ignore_ascii_case(vehicle.make.trim()) {
+            return false;
+        }
+
+        // Without a model year, any year range is accepted
+        let Some(year) = vehicle.model_year else {
+            return true;
+        };
+
+        self.year_from.is_none_or(|from| year >= from) && self.year_to.is_none_or(|to| year <= to)
+    }
+}
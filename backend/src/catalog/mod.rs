@@ -0,0 +1,359 @@
+mod manufacturer;
+mod search;
+
+pub use manufacturer::VehicleContext;
+pub use search::{DtcQuery, DtcSearchResult};
+
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::{Once, OnceLock},
+};
+
+use serde::{Deserialize, Serialize};
+use sqlite::State;
+use thiserror::Error;
+
+use crate::{diagnostics::TroubleCodeCategory, vin::APP_DATA_DIR, CODE_DESC_DB_PATH};
+use manufacturer::ManufacturerCode;
+use search::SearchIndex;
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("Failed to open DTC database '{0}'.")]
+    OpenFailed(PathBuf),
+    #[error("When querying DTC database - Query: {0}.")]
+    QueryError(&'static str),
+    #[error("DTC search index is unavailable.")]
+    SearchUnavailable,
+}
+
+/// Schema and built-in rows of the `manufacturer_codes` table, see data/manufacturer-codes.sql
+pub const MANUFACTURER_CODES_SCHEMA: &str = include_str!("../../data/manufacturer-codes.sql");
+
+static CATALOG_PATH: OnceLock<PathBuf> = OnceLock::new();
+static CATALOG: OnceLock<DtcCatalog> = OnceLock::new();
+static NO_MANUFACTURER_CODES: Once = Once::new();
+
+/// Set where the DTC database is loaded from.
+///
+/// Has to be called before the catalog is first used, later calls are ignored.
+pub fn set_catalog_path(path: PathBuf) {
+    let _ = CATALOG_PATH.set(path);
+}
+
+/// Path of the DTC database used by `DtcCatalog::global`.
+///
+/// Uses the path set with `set_catalog_path`, then a copy in the app data dir,
+/// then `./data/code-descriptions.sqlite` relative to the working directory.
+pub fn catalog_path() -> PathBuf {
+    if let Some(path) = CATALOG_PATH.get() {
+        return path.clone();
+    }
+
+    if let Some(app_data_dir) = APP_DATA_DIR.get() {
+        let path = app_data_dir.join("code-descriptions.sqlite");
+        if path.exists() {
+            return path;
+        }
+    }
+
+    PathBuf::from(CODE_DESC_DB_PATH)
+}
+
+/// Add the `manufacturer_codes` table, with the built-in rows,
+/// to the DTC database at `path` if it doesn't have it yet.
+/// An existing table is left as is.
+pub fn create_manufacturer_codes_table(path: &Path) -> Result<(), Error> {
+    let con = sqlite::Connection::open(path).map_err(|_| Error::OpenFailed(path.to_path_buf()))?;
+    if has_manufacturer_codes_table(&con) {
+        return Ok(());
+    }
+
+    con.execute(MANUFACTURER_CODES_SCHEMA)
+        .map_err(|_| Error::QueryError(MANUFACTURER_CODES_SCHEMA))
+}
+
+fn has_manufacturer_codes_table(con: &sqlite::Connection) -> bool {
+    con.prepare("SELECT id FROM manufacturer_codes LIMIT 1")
+        .is_ok()
+}
+
+/// Where a DTC description came from.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DescriptionSource {
+    /// Manufacturer specific description for the vehicle's make
+    Manufacturer(String),
+
+    /// Generic SAE J2012 description
+    Generic,
+
+    /// Generic description of a manufacturer specific code (e.g P1xxx).
+    /// The vehicle's make may use the code for something else.
+    GenericFallback,
+
+    /// No description was found
+    #[default]
+    None,
+}
+
+impl DescriptionSource {
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Manufacturer(make) => make,
+            Self::Generic => "SAE",
+            Self::GenericFallback => "generic fallback",
+            Self::None => "none",
+        }
+    }
+}
+
+/// Everything the catalog knows about a DTC.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DtcInfo {
+    pub code: String,
+    pub description: Option<String>,
+    pub source: DescriptionSource,
+
+    /// The system from the first letter (P, C, B, U)
+    pub category: TroubleCodeCategory,
+
+    /// The subsystem from the third digit, if the standard defines one for `category`
+    pub subsystem: Option<&'static str>,
+
+    /// SAE defined code, rather than manufacturer specific, based on the second digit
+    pub generic: bool,
+}
+
+/// DTC descriptions, loaded once from the code database.
+///
+/// Lookups don't touch the database, so a catalog can be shared between threads.
+#[derive(Debug, Default)]
+pub struct DtcCatalog {
+    descriptions: HashMap<String, String>,
+
+    /// Manufacturer specific descriptions by code.
+    /// A code can have a different meaning for each make and year range.
+    manufacturer: HashMap<String, Vec<ManufacturerCode>>,
+
+    /// Built on the first search
+    search_index: OnceLock<Option<SearchIndex>>,
+}
+
+impl DtcCatalog {
+    /// Load every description from the database at `path`.
+    pub fn open(path: &Path) -> Result<Self, Error> {
+        let con =
+            sqlite::Connection::open(path).map_err(|_| Error::OpenFailed(path.to_path_buf()))?;
+
+        let query = "SELECT id, desc FROM codes";
+        let mut statement = con.prepare(query).map_err(|_| Error::QueryError(query))?;
+
+        let mut descriptions = HashMap::new();
+        while let Ok(State::Row) = statement.next() {
+            let code = statement.read::<String, _>("id").unwrap_or_default();
+            let description = statement.read::<String, _>("desc").unwrap_or_default();
+            if !code.is_empty() {
+                descriptions.insert(code.to_uppercase(), description);
+            }
+        }
+
+        Ok(Self {
+            descriptions,
+            manufacturer: Self::load_manufacturer_codes(&con),
+            search_index: OnceLock::new(),
+        })
+    }
+
+    // The manufacturer_codes table is optional.
+    // Older databases only have the generic codes table, the built-in rows
+    // are used for those. See `create_manufacturer_codes_table` to add it.
+    fn load_manufacturer_codes(con: &sqlite::Connection) -> HashMap<String, Vec<ManufacturerCode>> {
+        let mut manufacturer: HashMap<String, Vec<ManufacturerCode>> = HashMap::new();
+
+        let built_in;
+        let con = if has_manufacturer_codes_table(con) {
+            con
+        } else {
+            NO_MANUFACTURER_CODES.call_once(|| {
+                println!(
+                    "DTC database has no manufacturer_codes table, using built-in manufacturer codes"
+                );
+            });
+
+            let Ok(memory) = sqlite::Connection::open(":memory:") else {
+                return manufacturer;
+            };
+            if memory.execute(MANUFACTURER_CODES_SCHEMA).is_err() {
+                return manufacturer;
+            }
+
+            built_in = memory;
+            &built_in
+        };
+
+        let query = "SELECT id, make, year_from, year_to, desc FROM manufacturer_codes";
+        let Ok(mut statement) = con.prepare(query) else {
+            return manufacturer;
+        };
+
+        while let Ok(State::Row) = statement.next() {
+            let code = statement.read::<String, _>("id").unwrap_or_default();
+            let make = statement.read::<String, _>("make").unwrap_or_default();
+            if code.is_empty() || make.is_empty() {
+                continue;
+            }
+
+            let year = |column: &str| {
+                statement
+                    .read::<Option<i64>, _>(column)
+                    .ok()
+                    .flatten()
+                    .map(|year| year as i32)
+            };
+
+            let entry = ManufacturerCode {
+                make,
+                year_from: year("year_from"),
+                year_to: year("year_to"),
+                description: statement.read::<String, _>("desc").unwrap_or_default(),
+            };
+
+            manufacturer
+                .entry(code.to_uppercase())
+                .or_default()
+                .push(entry);
+        }
+
+        manufacturer
+    }
+
+    /// The catalog loaded from `catalog_path`, loaded on first use.
+    ///
+    /// If the database can't be opened, an empty catalog is used.
+    pub fn global() -> &'static DtcCatalog {
+        CATALOG.get_or_init(|| {
+            let path = catalog_path();
+            DtcCatalog::open(&path).unwrap_or_else(|err| {
+                println!("when loading dtc catalog: {err}");
+                DtcCatalog::default()
+            })
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.descriptions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.descriptions.is_empty()
+    }
+
+    /// Describe `code`, preferring the manufacturer's meaning for `vehicle`
+    /// and falling back to the generic description.
+    ///
+    /// A generic description of a manufacturer specific code is reported
+    /// as `DescriptionSource::GenericFallback`, it may not apply to the vehicle.
+    pub fn description(
+        &self,
+        code: &str,
+        vehicle: Option<&VehicleContext>,
+    ) -> (Option<&str>, DescriptionSource) {
+        let code = code.to_uppercase();
+
+        if let Some(vehicle) = vehicle {
+            let matched = self
+                .manufacturer
+                .get(&code)
+                .and_then(|entries| entries.iter().find(|entry| entry.matches(vehicle)));
+
+            if let Some(entry) = matched {
+                return (
+                    Some(entry.description.as_str()),
+                    DescriptionSource::Manufacturer(entry.make.clone()),
+                );
+            }
+        }
+
+        let Some(desc) = self.descriptions.get(&code) else {
+            return (None, DescriptionSource::None);
+        };
+
+        let (category, second, third) = code_digits(&code);
+        if is_generic(category, second, third) {
+            (Some(desc.as_str()), DescriptionSource::Generic)
+        } else {
+            (Some(desc.as_str()), DescriptionSource::GenericFallback)
+        }
+    }
+
+    pub fn lookup(&self, code: &str, vehicle: Option<&VehicleContext>) -> DtcInfo {
+        let code = code.to_uppercase();
+        let (category, second, third) = code_digits(&code);
+
+        let (description, source) = self.description(&code, vehicle);
+
+        DtcInfo {
+            description: description.map(|desc| desc.to_string()),
+            source,
+            category,
+            subsystem: subsystem(category, third),
+            generic: is_generic(category, second, third),
+            code,
+        }
+    }
+}
+
+// The category, second and third characters of an uppercase code
+fn code_digits(code: &str) -> (TroubleCodeCategory, char, char) {
+    let chars: Vec<char> = code.chars().collect();
+
+    let category = match chars.first() {
+        Some('P') => TroubleCodeCategory::Powertrain,
+        Some('C') => TroubleCodeCategory::Chassis,
+        Some('B') => TroubleCodeCategory::Body,
+        Some('U') => TroubleCodeCategory::Network,
+        _ => TroubleCodeCategory::Unknown,
+    };
+    let second = chars.get(1).copied().unwrap_or('0');
+    let third = chars.get(2).copied().unwrap_or('0');
+
+    (category, second, third)
+}
+
+// See SAE J2012. P0, P2 and P34-P39 are generic, P1 and P30-P33 are manufacturer specific.
+// B, C and U codes are generic with a 0 or 3 and manufacturer specific with a 1 or 2.
+fn is_generic(category: TroubleCodeCategory, second: char, third: char) -> bool {
+    match (category, second) {
+        (TroubleCodeCategory::Powertrain, '0' | '2') => true,
+        (TroubleCodeCategory::Powertrain, '3') => !matches!(third, '0'..='3'),
+        (TroubleCodeCategory::Powertrain, _) => false,
+        (_, '0' | '3') => true,
+        _ => false,
+    }
+}
+
+fn subsystem(category: TroubleCodeCategory, third: char) -> Option<&'static str> {
+    match category {
+        TroubleCodeCategory::Powertrain => match third {
+            '0' => Some("Fuel and air metering and auxiliary emission controls"),
+            '1' => Some("Fuel and air metering"),
+            '2' => Some("Fuel and air metering (injector circuit)"),
+            '3' => Some("Ignition system or misfire"),
+            '4' => Some("Auxiliary emission controls"),
+            '5' => Some("Vehicle speed controls and idle control system"),
+            '6' => Some("Computer output circuit"),
+            '7' | '8' | '9' => Some("Transmission"),
+            'A' | 'B' | 'C' => Some("Hybrid propulsion"),
+            _ => None,
+        },
+        TroubleCodeCategory::Network => match third {
+            '0' => Some("Network electrical"),
+            '1' | '2' => Some("Network communication"),
+            '3' => Some("Network software"),
+            '4' => Some("Network data"),
+            _ => None,
+        },
+        _ => None,
+    }
+}
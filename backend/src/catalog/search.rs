@@ -0,0 +1,242 @@
+use std::fmt;
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use sqlite::{ConnectionThreadSafe, State, Value};
+
+use super::{DtcCatalog, Error};
+use crate::diagnostics::TroubleCodeCategory;
+
+// A DTC without the failure type, e.g "P0420"
+static DTC_PATTERN: Lazy<Regex> = Lazy::new(|| Regex::new(r"^[PCBU][0-9A-F]{4}$").unwrap());
+
+/// Full-text index of the catalog's descriptions.
+/// Built in memory from the catalog the first time it's searched.
+pub(crate) struct SearchIndex {
+    con: ConnectionThreadSafe,
+}
+
+impl fmt::Debug for SearchIndex {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "SearchIndex")
+    }
+}
+
+/// What to search the DTC catalog for.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct DtcQuery {
+    /// Keywords matched against the code and its description (e.g "evap small leak").
+    /// Each keyword also matches longer words starting with it.
+    pub text: String,
+
+    /// Only return codes from this system
+    pub category: Option<TroubleCodeCategory>,
+
+    /// Only return codes between these two codes, inclusive (e.g "P0400", "P04FF")
+    pub range: Option<(String, String)>,
+
+    /// Maximum number of results. 0 uses the default of 50.
+    pub limit: usize,
+}
+
+impl DtcQuery {
+    pub fn new(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DtcSearchResult {
+    pub code: String,
+    pub description: String,
+
+    /// How well the code matched, lower is better.
+    /// 0 when the query had no keywords.
+    pub rank: f64,
+}
+
+impl DtcCatalog {
+    /// Search the catalog, best matches first.
+    ///
+    /// With no keywords, every code matching the filters is returned in order.
+    pub fn search(&self, query: &DtcQuery) -> Result<Vec<DtcSearchResult>, Error> {
+        let index = self.search_index()?;
+
+        let limit = if query.limit == 0 { 50 } else { query.limit };
+        let terms = fts_terms(&query.text, " ", 1);
+        let ranked = !terms.is_empty();
+
+        let mut conditions = Vec::new();
+        let mut params: Vec<Value> = Vec::new();
+        if ranked {
+            conditions.push("dtc_search MATCH ?");
+            params.push(Value::String(terms));
+        }
+        if let Some(category) = query.category {
+            conditions.push("code LIKE ?");
+            params.push(Value::String(format!("{}%", category.system_letter())));
+        }
+        if let Some((from, to)) = &query.range {
+            conditions.push("code BETWEEN ? AND ?");
+            params.push(Value::String(from.to_uppercase()));
+            params.push(Value::String(to.to_uppercase()));
+        }
+
+        let filter = if conditions.is_empty() {
+            String::new()
+        } else {
+            format!("WHERE {}", conditions.join(" AND "))
+        };
+
+        // bm25 can only be used in a full-text query
+        let (rank, order) = if ranked {
+            ("bm25(dtc_search)", "rank")
+        } else {
+            ("0.0", "code")
+        };
+
+        index.query(
+            &format!(
+                "SELECT code, description, {rank} AS rank FROM dtc_search {filter} ORDER BY {order} LIMIT {limit}"
+            ),
+            &params,
+        )
+    }
+
+    /// Codes related to `code`.
+    ///
+    /// Codes in the same group (e.g P0420 to P042F) come first,
+    /// followed by codes with similar descriptions in the same system.
+    /// Empty if `code` isn't a DTC.
+    pub fn related(&self, code: &str, limit: usize) -> Result<Vec<DtcSearchResult>, Error> {
+        let code = code.trim().to_uppercase();
+        if !DTC_PATTERN.is_match(&code) {
+            return Ok(Vec::new());
+        }
+
+        let index = self.search_index()?;
+
+        let mut related = index.query(
+            "SELECT code, description, 0.0 AS rank FROM dtc_search WHERE code LIKE ? AND code != ? ORDER BY code",
+            &[
+                Value::String(format!("{}%", &code[..4])),
+                Value::String(code.clone()),
+            ],
+        )?;
+        related.truncate(limit);
+
+        let Some(description) = self.descriptions.get(&code) else {
+            return Ok(related);
+        };
+
+        // Any shared word counts, the best matches share the most.
+        // Short words like "of" or "a" would match almost everything.
+        let terms = fts_terms(description, " OR ", 3);
+        if terms.is_empty() || related.len() >= limit {
+            return Ok(related);
+        }
+
+        let similar = index.query(
+            &format!(
+                "SELECT code, description, bm25(dtc_search) AS rank FROM dtc_search WHERE dtc_search MATCH ? AND code LIKE ? AND code != ? ORDER BY rank LIMIT {limit}"
+            ),
+            &[
+                Value::String(terms),
+                Value::String(format!("{}%", &code[..1])),
+                Value::String(code.clone()),
+            ],
+        )?;
+
+        for result in similar {
+            if related.len() >= limit {
+                break;
+            }
+            if !related.iter().any(|existing| existing.code == result.code) {
+                related.push(result);
+            }
+        }
+
+        Ok(related)
+    }
+
+    fn search_index(&self) -> Result<&SearchIndex, Error> {
+        self.search_index
+            .get_or_init(|| match SearchIndex::build(self) {
+                Ok(index) => Some(index),
+                Err(err) => {
+                    println!("when building dtc search index: {err}");
+                    None
+                }
+            })
+            .as_ref()
+            .ok_or(Error::SearchUnavailable)
+    }
+}
+
+impl SearchIndex {
+    fn build(catalog: &DtcCatalog) -> Result<Self, Error> {
+        let con = sqlite::Connection::open_thread_safe(":memory:")
+            .map_err(|_| Error::OpenFailed(":memory:".into()))?;
+
+        let query = "CREATE VIRTUAL TABLE dtc_search USING fts5(code, description, tokenize = 'porter unicode61')";
+        con.execute(query).map_err(|_| Error::QueryError(query))?;
+
+        let query = "INSERT INTO dtc_search (code, description) VALUES (?, ?)";
+        con.execute("BEGIN")
+            .map_err(|_| Error::QueryError("BEGIN"))?;
+        {
+            let mut statement = con.prepare(query).map_err(|_| Error::QueryError(query))?;
+            for (code, description) in catalog.descriptions.iter() {
+                statement
+                    .bind(&[(1, code.as_str()), (2, description.as_str())][..])
+                    .map_err(|_| Error::QueryError(query))?;
+                let _ = statement.next();
+                let _ = statement.reset();
+            }
+        }
+        con.execute("COMMIT")
+            .map_err(|_| Error::QueryError("COMMIT"))?;
+
+        Ok(Self { con })
+    }
+
+    fn query(&self, query: &str, params: &[Value]) -> Result<Vec<DtcSearchResult>, Error> {
+        let mut statement = self
+            .con
+            .prepare(query)
+            .map_err(|_| Error::QueryError("dtc search"))?;
+
+        for (i, param) in params.iter().enumerate() {
+            statement
+                .bind((i + 1, param))
+                .map_err(|_| Error::QueryError("dtc search"))?;
+        }
+
+        let mut results = Vec::new();
+        while let Ok(State::Row) = statement.next() {
+            results.push(DtcSearchResult {
+                code: statement.read::<String, _>("code").unwrap_or_default(),
+                description: statement
+                    .read::<String, _>("description")
+                    .unwrap_or_default(),
+                rank: statement.read::<f64, _>("rank").unwrap_or_default(),
+            });
+        }
+
+        Ok(results)
+    }
+}
+
+// Turn free text into an FTS5 query of quoted prefix terms (e.g "evap"* "leak"*),
+// so punctuation in the text can't be mistaken for FTS5 syntax.
+fn fts_terms(text: &str, separator: &str, min_len: usize) -> String {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| word.len() >= min_len)
+        .map(|word| format!("\"{}\"*", word.to_lowercase()))
+        .collect::<Vec<_>>()
+        .join(separator)
+}
@@ -0,0 +1,218 @@
+// Clearing trouble codes.
+//
+// Service 04 doesn't just clear codes. It also erases freeze frames,
+// resets the readiness monitors and throws away the service 06 test results,
+// which a vehicle needs to pass an inspection. Everything is saved first,
+// and the codes are read again afterwards to see which ones came straight back.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{
+    diagnostics::{Test, TroubleCode},
+    freeze_frame::FreezeFrame,
+    mid::MonitorTest,
+    registry::PidError,
+    scalar::Unit,
+    vin::APP_DATA_DIR,
+    Command, NegativeResponseCode, OBD,
+};
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("Engine must be off to clear trouble codes ({0} rpm).")]
+    EngineRunning(f32),
+    #[error("Could not read engine speed to confirm the engine is off.")]
+    EngineSpeedUnavailable,
+    #[error("Could not read trouble codes before clearing them: {0}")]
+    CodesUnavailable(PidError),
+    #[error("When saving snapshot: {0}.")]
+    Io(#[from] std::io::Error),
+    #[error("When saving snapshot: {0}.")]
+    Json(#[from] serde_json::Error),
+    /// ECUs that held codes but didn't confirm they were cleared
+    #[error("{}", not_cleared_message(.0))]
+    NotCleared(Vec<String>),
+}
+
+fn not_cleared_message(ecus: &[String]) -> String {
+    if ecus.is_empty() {
+        "No ECU confirmed the trouble codes were cleared.".to_string()
+    } else {
+        format!(
+            "Trouble codes were not confirmed cleared by ECU {}.",
+            ecus.join(", ")
+        )
+    }
+}
+
+/// Everything service 04 erases, taken right before clearing.
+#[derive(Debug, Clone, Serialize)]
+pub struct ClearSnapshot {
+    /// RFC 3339 timestamp
+    pub taken_at: String,
+    pub vin: Option<String>,
+
+    pub trouble_codes: Vec<TroubleCode>,
+    pub freeze_frames: Vec<FreezeFrame>,
+    pub readiness: Vec<Test>,
+    pub monitor_tests: Vec<MonitorTest>,
+}
+
+impl ClearSnapshot {
+    pub fn save(&self, path: &Path) -> Result<(), Error> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+/// What each ECU said to a service 04 request.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct ClearResponses {
+    /// ECUs that replied with a positive 0x44
+    pub cleared: Vec<String>,
+
+    /// ECUs that refused, and why
+    pub rejected: Vec<(String, String)>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ClearReport {
+    pub snapshot: ClearSnapshot,
+
+    /// Where the snapshot was saved, if it was
+    pub snapshot_path: Option<PathBuf>,
+    pub responses: ClearResponses,
+
+    /// Codes reported again right after clearing.
+    /// Usually a fault that is still present.
+    /// `None` if the codes couldn't be read again, so the clear isn't verified.
+    pub returned: Option<Vec<TroubleCode>>,
+}
+
+impl OBD {
+    /// Clear trouble codes, but only with the engine off.
+    ///
+    /// A snapshot of the codes, freeze frames, readiness and monitor test results
+    /// is taken first and saved in the app data dir when it's set.
+    /// Codes are read again after clearing and the ones that came back are reported.
+    pub fn clear_trouble_codes_safely(&mut self) -> Result<ClearReport, Error> {
+        // make sure live data is read, not a freeze frame
+        let frame = self.get_freeze_frame();
+        self.set_freeze_frame(None);
+        let report = self.run_clear_workflow();
+        self.set_freeze_frame(frame);
+
+        report
+    }
+
+    /// Everything service 04 will erase.
+    /// Fails if the trouble codes couldn't be read.
+    pub fn take_clear_snapshot(&mut self) -> Result<ClearSnapshot, Error> {
+        let trouble_codes = self
+            .read_all_trouble_codes()
+            .map_err(Error::CodesUnavailable)?;
+
+        let mut readiness = self.get_common_tests_status().to_vec();
+        readiness.extend(self.get_advanced_tests_status());
+
+        Ok(ClearSnapshot {
+            taken_at: Utc::now().to_rfc3339(),
+            vin: self.get_vin().map(|vin| vin.get_vin().to_string()),
+            trouble_codes,
+            freeze_frames: self.get_freeze_frames(),
+            readiness,
+            monitor_tests: self.get_all_monitor_tests(),
+        })
+    }
+
+    /// Send service 04 and collect the answer of each ECU.
+    pub fn request_clear_trouble_codes(&mut self) -> ClearResponses {
+        let mut responses = ClearResponses::default();
+
+        // monitors, codes and the like are reset
+        self.clear_response_cache();
+
+        for message in self.query_messages(Command::new_svc(b"04")) {
+            match message.data.as_slice() {
+                [0x44, ..] => responses.cleared.push(message.ecu),
+                [0x7F, 0x04, code, ..] => responses.rejected.push((
+                    message.ecu,
+                    NegativeResponseCode::from_u8(*code).to_string(),
+                )),
+                _ => {}
+            }
+        }
+
+        responses
+    }
+
+    fn run_clear_workflow(&mut self) -> Result<ClearReport, Error> {
+        let rpm = self.rpm();
+        if rpm.unit == Unit::NoData {
+            return Err(Error::EngineSpeedUnavailable);
+        }
+        if rpm.value > 0.0 {
+            return Err(Error::EngineRunning(rpm.value));
+        }
+
+        // without the codes there's no telling which ECUs have to confirm
+        let snapshot = self.take_clear_snapshot()?;
+        let snapshot_path = APP_DATA_DIR.get().map(|dir| {
+            dir.join("clear-snapshots").join(format!(
+                "{}-{}.json",
+                snapshot.vin.as_deref().unwrap_or("unknown"),
+                Utc::now().format("%Y%m%dT%H%M%S")
+            ))
+        });
+
+        // don't erase anything that couldn't be kept
+        if let Some(path) = &snapshot_path {
+            snapshot.save(path)?;
+        }
+
+        let responses = self.request_clear_trouble_codes();
+        for (ecu, reason) in responses.rejected.iter() {
+            println!("ecu {ecu} refused to clear trouble codes: {reason}");
+        }
+
+        // every ECU that held codes has to confirm, not just any one of them
+        let mut not_cleared: Vec<String> = snapshot
+            .trouble_codes
+            .iter()
+            .filter_map(|code| code.ecu.clone())
+            .filter(|ecu| !responses.cleared.contains(ecu))
+            .collect();
+        not_cleared.sort();
+        not_cleared.dedup();
+
+        if responses.cleared.is_empty() || !not_cleared.is_empty() {
+            return Err(Error::NotCleared(not_cleared));
+        }
+
+        let returned = match self.read_all_trouble_codes() {
+            Ok(codes) => Some(codes),
+            Err(err) => {
+                println!("could not read trouble codes again after clearing: {err}");
+                None
+            }
+        };
+
+        Ok(ClearReport {
+            snapshot,
+            snapshot_path,
+            responses,
+            returned,
+        })
+    }
+}
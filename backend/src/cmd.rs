@@ -1,7 +1,7 @@
 use serde::{Deserialize, Serialize};
 
 /// The type of command to send via OBD
-#[derive(Serialize, Deserialize, Debug, PartialEq, Default)]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
 pub(crate) enum CommandType {
     /// Regular PID command, e.g 010C
     PIDCommand,
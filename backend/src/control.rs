@@ -0,0 +1,171 @@
+// On-board system control (service 08).
+//
+// Lets a scan tool ask an ECU to run a test, usually the EVAP leak test (TID 01).
+// The ECU only acknowledges the request, the outcome is read afterwards
+// from the service 06 results of the monitor that ran.
+
+use std::{collections::HashMap, time::Instant};
+
+use serde::Serialize;
+use thiserror::Error;
+
+use crate::{
+    mid::{EvapLeakSize, MonitorTest},
+    scalar::Unit,
+    Command, NegativeResponseCode, OBD,
+};
+
+/// Test ids of service 08
+pub mod tid {
+    pub const EVAP_LEAK_TEST: u8 = 0x01;
+}
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("Test id {0:#04X} is not supported by any ECU.")]
+    NotSupported(u8),
+    #[error("Test id {tid:#04X} was rejected: {code}.")]
+    Rejected { tid: u8, code: NegativeResponseCode },
+    #[error("No response to test id {0:#04X}.")]
+    NoResponse(u8),
+    #[error("Conditions for the test aren't met: {}.", .0.join(", "))]
+    PrerequisitesNotMet(Vec<String>),
+}
+
+/// An EVAP leak test that was started.
+/// The ECU runs it on its own, `poll` reads the results once they're available.
+#[derive(Debug, Clone, Serialize)]
+pub struct EvapLeakTest {
+    /// ECUs that accepted the request
+    pub ecus: Vec<String>,
+
+    /// Leak test results before the test was requested
+    pub previous_results: Vec<MonitorTest>,
+
+    #[serde(skip)]
+    started: Instant,
+}
+
+impl EvapLeakTest {
+    /// Seconds since the test was requested
+    pub fn elapsed(&self) -> f32 {
+        self.started.elapsed().as_secs_f32()
+    }
+
+    /// Leak test results, once they differ from the ones before the test.
+    pub fn poll(&self, obd: &mut OBD) -> Option<Vec<MonitorTest>> {
+        let results = obd.get_evap_leak_results();
+        if results.is_empty() || results == self.previous_results {
+            return None;
+        }
+
+        Some(results)
+    }
+}
+
+impl OBD {
+    /// Test ids each ECU supports, keyed by ECU.
+    pub fn get_supported_control_tids(&mut self) -> HashMap<String, Vec<u8>> {
+        self.get_supported_ids(0x08, None)
+    }
+
+    /// Ask every ECU to run test `tid`.
+    ///
+    /// Returns the ECUs that accepted. Fails only if none did.
+    pub fn request_on_board_test(&mut self, tid: u8) -> Result<Vec<String>, Error> {
+        let mut accepted = Vec::new();
+        let mut rejection = None;
+
+        for message in self.query_messages(Command::new_arb(&format!("08{tid:02X}"))) {
+            match message.data.as_slice() {
+                [0x48, id, ..] if *id == tid => accepted.push(message.ecu),
+                [0x7F, 0x08, code, ..] => {
+                    let code = NegativeResponseCode::from_u8(*code);
+                    println!("ecu {} rejected test id {tid:#04X}: {code}", message.ecu);
+                    rejection = Some(code);
+                }
+                _ => {}
+            }
+        }
+
+        match (accepted.is_empty(), rejection) {
+            (false, _) => Ok(accepted),
+            (true, Some(code)) => Err(Error::Rejected { tid, code }),
+            (true, None) => Err(Error::NoResponse(tid)),
+        }
+    }
+
+    /// Conditions that would stop the EVAP leak test from running.
+    /// Empty when the test can be requested.
+    ///
+    /// Most ECUs want the vehicle stopped, the fuel tank neither
+    /// nearly empty nor full, and no EVAP fault already stored.
+    pub fn evap_test_prerequisites(&mut self) -> Vec<String> {
+        let mut unmet = Vec::new();
+
+        let speed = self.vehicle_speed();
+        if speed.unit != Unit::NoData && speed.value > 0.0 {
+            unmet.push("vehicle must be stopped".to_string());
+        }
+
+        // not every vehicle reports it, the ECU will reject the test if it matters
+        let fuel = self.fuel_tank_level();
+        if fuel.unit != Unit::NoData && !(15.0..=85.0).contains(&fuel.value) {
+            unmet.push(format!(
+                "fuel level must be between 15% and 85% ({:.0}%)",
+                fuel.value
+            ));
+        }
+
+        // P0440 to P045F are EVAP system faults
+        let evap_codes: Vec<String> = self
+            .get_all_trouble_codes()
+            .into_iter()
+            .filter(|code| code.dtc.starts_with("P044") || code.dtc.starts_with("P045"))
+            .map(|code| code.dtc)
+            .collect();
+
+        if !evap_codes.is_empty() {
+            unmet.push(format!(
+                "EVAP trouble codes must be repaired first ({})",
+                evap_codes.join(", ")
+            ));
+        }
+
+        unmet
+    }
+
+    /// Results of every EVAP leak monitor (service 06).
+    pub fn get_evap_leak_results(&mut self) -> Vec<MonitorTest> {
+        EvapLeakSize::ALL
+            .iter()
+            .flat_map(|size| self.test_evap_monitor(*size))
+            .collect()
+    }
+
+    /// Check the prerequisites and request the EVAP leak test (TID 01).
+    pub fn start_evap_leak_test(&mut self) -> Result<EvapLeakTest, Error> {
+        let supported = self
+            .get_supported_control_tids()
+            .values()
+            .any(|tids| tids.contains(&tid::EVAP_LEAK_TEST));
+
+        if !supported {
+            return Err(Error::NotSupported(tid::EVAP_LEAK_TEST));
+        }
+
+        let unmet = self.evap_test_prerequisites();
+        if !unmet.is_empty() {
+            return Err(Error::PrerequisitesNotMet(unmet));
+        }
+
+        let previous_results = self.get_evap_leak_results();
+        let ecus = self.request_on_board_test(tid::EVAP_LEAK_TEST)?;
+
+        Ok(EvapLeakTest {
+            ecus,
+            previous_results,
+            started: Instant::now(),
+        })
+    }
+}
@@ -0,0 +1,132 @@
+// Drive cycle tracking.
+//
+// After a repair the readiness monitors have to run again before the vehicle
+// can pass an inspection. Each monitor needs certain conditions (a cold start,
+// a steady cruise, idling...) so the conditions at the moment a monitor
+// completes are kept, to know which parts of the drive cycle are still needed.
+
+use std::time::Instant;
+
+use chrono::Utc;
+use serde::Serialize;
+
+use crate::{diagnostics::Test, scalar::Scalar, OBD};
+
+/// Operating conditions when a monitor completed.
+#[derive(Debug, Clone, Serialize)]
+pub struct DriveConditions {
+    pub vehicle_speed: Scalar,
+    pub coolant_temp: Scalar,
+    pub engine_runtime: Scalar,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MonitorCompletion {
+    pub monitor: &'static str,
+
+    /// RFC 3339 timestamp
+    pub at: String,
+
+    /// Seconds since tracking started
+    pub elapsed: f32,
+    pub conditions: DriveConditions,
+}
+
+/// Follows the current drive cycle monitors (0141) through a session.
+#[derive(Debug, Clone, Serialize)]
+pub struct DriveCycleTracker {
+    /// RFC 3339 timestamp
+    pub started_at: String,
+    pub monitors: Vec<Test>,
+    pub completions: Vec<MonitorCompletion>,
+
+    #[serde(skip)]
+    started: Instant,
+}
+
+impl Default for DriveCycleTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DriveCycleTracker {
+    pub fn new() -> Self {
+        Self {
+            started_at: Utc::now().to_rfc3339(),
+            monitors: Vec::new(),
+            completions: Vec::new(),
+            started: Instant::now(),
+        }
+    }
+
+    /// Compare `monitors` to the last status seen, and keep the ones that just completed.
+    ///
+    /// Monitors already complete the first time they're seen are not counted,
+    /// they didn't complete during this session.
+    pub fn update(
+        &mut self,
+        monitors: &[Test],
+        conditions: &DriveConditions,
+    ) -> Vec<MonitorCompletion> {
+        let mut completed = Vec::new();
+
+        for monitor in monitors.iter().filter(|test| test.available) {
+            let was_complete = self
+                .monitors
+                .iter()
+                .find(|previous| previous.name == monitor.name)
+                .map(|previous| previous.complete);
+
+            if monitor.complete && was_complete == Some(false) {
+                completed.push(MonitorCompletion {
+                    monitor: monitor.name,
+                    at: Utc::now().to_rfc3339(),
+                    elapsed: self.started.elapsed().as_secs_f32(),
+                    conditions: conditions.clone(),
+                });
+            }
+        }
+
+        // a response with no data shouldn't make every monitor look new next time
+        if monitors.iter().any(|test| test.available) {
+            self.monitors = monitors.to_vec();
+        }
+
+        self.completions.extend(completed.iter().cloned());
+        completed
+    }
+
+    /// Monitors enabled this drive cycle that haven't completed yet.
+    pub fn remaining(&self) -> Vec<&'static str> {
+        self.monitors
+            .iter()
+            .filter(|test| test.available && !test.complete)
+            .map(|test| test.name)
+            .collect()
+    }
+}
+
+impl OBD {
+    /// Status of every monitor this drive cycle (0141).
+    pub fn get_drive_cycle_tests_status(&mut self) -> Vec<Test> {
+        let mut tests = self.get_drive_cycle_common_tests_status().to_vec();
+        tests.extend(self.get_drive_cycle_advanced_tests_status());
+        tests
+    }
+
+    /// Read the current drive cycle monitors and record the ones that completed in `tracker`.
+    pub fn update_drive_cycle(
+        &mut self,
+        tracker: &mut DriveCycleTracker,
+    ) -> Vec<MonitorCompletion> {
+        let monitors = self.get_drive_cycle_tests_status();
+        let conditions = DriveConditions {
+            vehicle_speed: self.vehicle_speed(),
+            coolant_temp: self.coolant_temp(),
+            engine_runtime: self.engine_runtime(),
+        };
+
+        tracker.update(&monitors, &conditions)
+    }
+}
@@ -0,0 +1,251 @@
+// Recognizing a vehicle that doesn't report its VIN.
+//
+// Many vehicles from before 2005 don't answer 0902. What they do report, the
+// protocol, which ECUs answer, the pids each supports, the calibration ids and
+// the OBD standard (011C), is mostly the same between vehicles of the same
+// model and engine. Every time a vehicle with a known VIN is connected its
+// fingerprint is added to a local database, which is then searched for the
+// closest match when the VIN is missing.
+
+use std::{collections::HashMap, fs, path::PathBuf};
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{
+    capabilities::{Capabilities, SERVICES},
+    vin::{APP_DATA_DIR, VIN},
+    Command, OBD,
+};
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("App data directory is not set.")]
+    NoAppDataDir,
+    #[error("When reading or writing fingerprints: {0}.")]
+    Io(#[from] std::io::Error),
+    #[error("Fingerprint database is malformed: {0}.")]
+    Json(#[from] serde_json::Error),
+}
+
+/// Matches scoring below this are not suggested
+pub const MIN_SCORE: f32 = 0.5;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Fingerprint {
+    /// Protocol the adapter detected (e.g "ISO 9141-2")
+    pub protocol: String,
+    pub ecus: Vec<String>,
+    pub capabilities: Capabilities,
+
+    /// Calibration ids (0904) by ECU
+    pub calibration_ids: HashMap<String, Vec<String>>,
+
+    /// Raw value of 011C, `None` if not reported
+    pub obd_standard: Option<u8>,
+}
+
+impl Fingerprint {
+    /// How alike two fingerprints are, from 0 to 1.
+    ///
+    /// A shared calibration id weighs the most, it's specific to
+    /// the software flashed for a model and engine.
+    pub fn similarity(&self, other: &Fingerprint) -> f32 {
+        let ids = |fingerprint: &Fingerprint| -> Vec<(u8, u8)> {
+            SERVICES
+                .iter()
+                .flat_map(|&service| {
+                    fingerprint
+                        .capabilities
+                        .supported(service)
+                        .into_iter()
+                        .map(move |id| (service, id))
+                })
+                .collect()
+        };
+        let calibration_ids = |fingerprint: &Fingerprint| -> Vec<String> {
+            fingerprint
+                .calibration_ids
+                .values()
+                .flatten()
+                .cloned()
+                .collect()
+        };
+
+        let obd_standard = match (self.obd_standard, other.obd_standard) {
+            (None, None) => None,
+            (a, b) => Some(same(&a, &b)),
+        };
+
+        // what's missing from both says nothing about
+        // the vehicle, so it's left out of the score
+        let scores: Vec<(f32, f32)> = [
+            (1.0, Some(same(&self.protocol, &other.protocol))),
+            (1.0, obd_standard),
+            (2.0, jaccard(&self.ecus, &other.ecus)),
+            (3.0, jaccard(&ids(self), &ids(other))),
+            (
+                5.0,
+                jaccard(&calibration_ids(self), &calibration_ids(other)),
+            ),
+        ]
+        .into_iter()
+        .filter_map(|(weight, score)| Some((weight, score?)))
+        .collect();
+
+        // only the protocol to go by
+        if scores.len() == 1 {
+            return 0.0;
+        }
+
+        let total: f32 = scores.iter().map(|(weight, _)| weight).sum();
+        scores
+            .iter()
+            .map(|(weight, score)| weight * score)
+            .sum::<f32>()
+            / total
+    }
+}
+
+fn same<T: PartialEq>(a: &T, b: &T) -> f32 {
+    if a == b {
+        1.0
+    } else {
+        0.0
+    }
+}
+
+// Share of items in both lists, `None` if both are empty
+fn jaccard<T: PartialEq>(a: &[T], b: &[T]) -> Option<f32> {
+    let shared = a.iter().filter(|item| b.contains(item)).count();
+    let total = a.len() + b.iter().filter(|item| !a.contains(item)).count();
+    if total == 0 {
+        return None;
+    }
+
+    Some(shared as f32 / total as f32)
+}
+
+/// A vehicle whose VIN was known when it was fingerprinted
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KnownVehicle {
+    pub vin: String,
+    pub make: String,
+    pub model: String,
+    pub model_year: Option<i32>,
+
+    /// Engine model from the VIN (e.g "K24Z7")
+    pub engine: Option<String>,
+    pub fingerprint: Fingerprint,
+
+    /// RFC 3339 timestamp
+    pub seen_at: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FingerprintMatch {
+    pub vehicle: KnownVehicle,
+
+    /// From `MIN_SCORE` to 1
+    pub score: f32,
+}
+
+/// Fingerprints of every vehicle connected with a known VIN.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct FingerprintDb {
+    pub vehicles: Vec<KnownVehicle>,
+
+    /// Where the database is saved. `None` keeps it in memory only.
+    #[serde(skip)]
+    path: Option<PathBuf>,
+}
+
+impl FingerprintDb {
+    /// Load the database from the app data dir, or start an empty one.
+    pub fn open() -> Result<Self, Error> {
+        let dir = APP_DATA_DIR.get().ok_or(Error::NoAppDataDir)?;
+        Self::open_at(dir.join("fingerprints.json"))
+    }
+
+    pub fn open_at(path: PathBuf) -> Result<Self, Error> {
+        let mut db = if path.exists() {
+            serde_json::from_str(&fs::read_to_string(&path)?)?
+        } else {
+            Self::default()
+        };
+
+        db.path = Some(path);
+        Ok(db)
+    }
+
+    pub fn save(&self) -> Result<(), Error> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Add a vehicle, replacing what was known for the same VIN.
+    pub fn record(&mut self, vehicle: KnownVehicle) {
+        self.vehicles.retain(|known| known.vin != vehicle.vin);
+        self.vehicles.push(vehicle);
+    }
+
+    /// Known vehicles most like `fingerprint`, best match first.
+    pub fn matches(&self, fingerprint: &Fingerprint, limit: usize) -> Vec<FingerprintMatch> {
+        let mut matches: Vec<FingerprintMatch> = self
+            .vehicles
+            .iter()
+            .map(|vehicle| FingerprintMatch {
+                score: vehicle.fingerprint.similarity(fingerprint),
+                vehicle: vehicle.clone(),
+            })
+            .filter(|found| found.score >= MIN_SCORE)
+            .collect();
+
+        matches.sort_by(|a, b| b.score.total_cmp(&a.score));
+        matches.truncate(limit);
+        matches
+    }
+}
+
+impl OBD {
+    /// Fingerprint of the connected vehicle.
+    /// Discovers capabilities if they haven't been read yet.
+    pub fn get_fingerprint(&mut self) -> Fingerprint {
+        if !self.capabilities.is_known(0x01) {
+            self.discover_capabilities();
+        }
+
+        let standard = self.query(Command::new_pid(b"011C"));
+        Fingerprint {
+            protocol: self.get_detected_protocol(),
+            ecus: self.capabilities.ecus(),
+            capabilities: self.capabilities.clone(),
+            calibration_ids: self.get_calibration_ids(),
+            obd_standard: (*standard.get_payload_size() != 0).then(|| standard.a_value() as u8),
+        }
+    }
+
+    /// Fingerprint the connected vehicle and remember it as `vin`.
+    pub fn record_fingerprint(&mut self, db: &mut FingerprintDb, vin: &VIN) {
+        let vehicle = KnownVehicle {
+            vin: vin.get_vin().to_string(),
+            make: vin.get_vehicle_make().unwrap_or_default(),
+            model: vin.get_vehicle_model().unwrap_or_default(),
+            model_year: vin.get_model_year().ok(),
+            engine: vin.get_engine_model().ok(),
+            fingerprint: self.get_fingerprint(),
+            seen_at: Utc::now().to_rfc3339(),
+        };
+
+        db.record(vehicle);
+    }
+}
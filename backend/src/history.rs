@@ -0,0 +1,270 @@
+// Per vehicle DTC history.
+//
+// Every time codes are read they are recorded in a journal kept for the VIN,
+// so codes that come and go between drives aren't lost. The journal is
+// stored as json in the app data dir, one file per VIN.
+
+use std::{fs, path::PathBuf};
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{
+    diagnostics::{TroubleCode, TroubleCodeStatus},
+    freeze_frame::FreezeFrame,
+    registry::PidError,
+    uds::FailureType,
+    vin::APP_DATA_DIR,
+    OBD,
+};
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("App data directory is not set.")]
+    NoAppDataDir,
+    #[error("When reading or writing DTC history: {0}.")]
+    Io(#[from] std::io::Error),
+    #[error("DTC history is malformed: {0}.")]
+    Json(#[from] serde_json::Error),
+}
+
+/// The most serious state a code was reported in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum DtcState {
+    Pending,
+    Confirmed,
+    Permanant,
+
+    /// No longer reported by the vehicle
+    Cleared,
+}
+
+impl DtcState {
+    pub fn from_status(status: &TroubleCodeStatus) -> Self {
+        if status.permanant {
+            Self::Permanant
+        } else if status.confirmed {
+            Self::Confirmed
+        } else {
+            Self::Pending
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Pending => "pending",
+            Self::Confirmed => "confirmed",
+            Self::Permanant => "permanant",
+            Self::Cleared => "cleared",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StateTransition {
+    /// `None` when the code was first seen
+    pub from: Option<DtcState>,
+    pub to: DtcState,
+
+    /// RFC 3339 timestamp
+    pub at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DtcHistoryEntry {
+    /// Base code without the failure type (e.g "P0420")
+    pub code: String,
+    pub ecu: Option<String>,
+
+    /// Last failure type the ECU reported with the code.
+    /// Only known when the codes were read with UDS service 19.
+    #[serde(default)]
+    pub failure_type: Option<FailureType>,
+    pub description: String,
+
+    /// RFC 3339 timestamps
+    pub first_seen: String,
+    pub last_seen: String,
+
+    /// How many times the code appeared, counting reappearances after being cleared
+    pub occurrences: u32,
+    pub state: DtcState,
+    pub transitions: Vec<StateTransition>,
+
+    /// Freeze frame stored by the ECU when the code was first seen, if any
+    pub freeze_frame: Option<FreezeFrame>,
+}
+
+impl DtcHistoryEntry {
+    /// Whether `code` is the code this entry tracks, whatever its failure type.
+    pub fn is(&self, code: &TroubleCode) -> bool {
+        self.code == code.dtc && self.ecu == code.ecu
+    }
+}
+
+/// DTC history for one vehicle.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct DtcJournal {
+    pub vin: String,
+    pub entries: Vec<DtcHistoryEntry>,
+
+    /// Where the journal is saved. `None` keeps it in memory only.
+    #[serde(skip)]
+    path: Option<PathBuf>,
+}
+
+impl DtcJournal {
+    /// Load the journal for `vin` from the app data dir,
+    /// or start a new one if the vehicle hasn't been seen before.
+    pub fn open(vin: &str) -> Result<Self, Error> {
+        let dir = APP_DATA_DIR.get().ok_or(Error::NoAppDataDir)?;
+        Self::open_at(dir.join("dtc-history").join(format!("{vin}.json")), vin)
+    }
+
+    pub fn open_at(path: PathBuf, vin: &str) -> Result<Self, Error> {
+        let mut journal = if path.exists() {
+            serde_json::from_str(&fs::read_to_string(&path)?)?
+        } else {
+            Self {
+                vin: vin.to_string(),
+                ..Default::default()
+            }
+        };
+
+        journal.path = Some(path);
+        Ok(journal)
+    }
+
+    /// A journal that is never saved, used when the VIN is unknown.
+    pub fn in_memory() -> Self {
+        Self::default()
+    }
+
+    pub fn save(&self) -> Result<(), Error> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Record the codes currently reported by the vehicle.
+    ///
+    /// Codes that are no longer reported are marked as cleared.
+    /// A failed read changes nothing, it doesn't say which codes are gone.
+    /// Returns the codes that are new, or came back after being cleared.
+    ///
+    /// Codes are tracked by the base code and ECU, so a code read with
+    /// and without its failure type is still the same entry.
+    pub fn record(&mut self, read: Result<&[TroubleCode], &PidError>) -> Vec<TroubleCode> {
+        let Ok(codes) = read else {
+            return Vec::new();
+        };
+
+        let now = Utc::now().to_rfc3339();
+        let mut appeared = Vec::new();
+
+        for code in codes {
+            let state = DtcState::from_status(&code.status);
+
+            let Some(entry) = self.entries.iter_mut().find(|entry| entry.is(code)) else {
+                self.entries.push(DtcHistoryEntry {
+                    code: code.dtc.clone(),
+                    ecu: code.ecu.clone(),
+                    failure_type: code.failure_type,
+                    description: code.description.clone(),
+                    first_seen: now.clone(),
+                    last_seen: now.clone(),
+                    occurrences: 1,
+                    state,
+                    transitions: vec![StateTransition {
+                        from: None,
+                        to: state,
+                        at: now.clone(),
+                    }],
+                    freeze_frame: None,
+                });
+                appeared.push(code.clone());
+                continue;
+            };
+
+            entry.last_seen = now.clone();
+            if code.failure_type.is_some() {
+                entry.failure_type = code.failure_type;
+            }
+
+            if entry.state == DtcState::Cleared {
+                entry.occurrences += 1;
+                appeared.push(code.clone());
+            }
+
+            if entry.state != state {
+                entry.transitions.push(StateTransition {
+                    from: Some(entry.state),
+                    to: state,
+                    at: now.clone(),
+                });
+                entry.state = state;
+            }
+        }
+
+        for entry in self.entries.iter_mut() {
+            let reported = codes.iter().any(|code| entry.is(code));
+
+            if !reported && entry.state != DtcState::Cleared {
+                entry.transitions.push(StateTransition {
+                    from: Some(entry.state),
+                    to: DtcState::Cleared,
+                    at: now.clone(),
+                });
+                entry.state = DtcState::Cleared;
+            }
+        }
+
+        appeared
+    }
+
+    /// Keep `frame` with `code` if it doesn't already have a freeze frame.
+    pub fn attach_freeze_frame(&mut self, code: &TroubleCode, frame: FreezeFrame) {
+        if let Some(entry) = self.entries.iter_mut().find(|entry| entry.is(code)) {
+            entry.freeze_frame.get_or_insert(frame);
+        }
+    }
+}
+
+impl OBD {
+    /// Record the codes read in `journal` and return the ones that just appeared.
+    ///
+    /// The freeze frame of each new code is read and kept with it,
+    /// so the conditions it was set under are kept after the code is cleared.
+    pub fn record_dtc_history(
+        &mut self,
+        journal: &mut DtcJournal,
+        read: Result<&[TroubleCode], &PidError>,
+    ) -> Vec<TroubleCode> {
+        let appeared = journal.record(read);
+        if appeared.is_empty() {
+            return appeared;
+        }
+
+        let frames = self.get_freeze_frames();
+        for code in appeared.iter() {
+            let frame = frames.iter().find(|frame| {
+                frame.ecu.as_str() == code.ecu.as_deref().unwrap_or_default()
+                    && frame.dtc.as_ref().is_some_and(|dtc| dtc.dtc == code.dtc)
+            });
+
+            if let Some(frame) = frame {
+                journal.attach_freeze_frame(code, frame.clone());
+            }
+        }
+
+        appeared
+    }
+}
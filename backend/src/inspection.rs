@@ -0,0 +1,375 @@
+// Emissions inspection (I/M) readiness.
+//
+// Puts together what an inspection station looks at: the MIL, stored codes,
+// and whether the readiness monitors have completed since codes were last cleared.
+// How many incomplete monitors are tolerated depends on the jurisdiction,
+// so the rules are configurable and can be loaded from json.
+
+use std::{fs, path::Path};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{
+    catalog::VehicleContext,
+    diagnostics::{OBDStandard, Test},
+    engine::EngineType,
+    vin::APP_DATA_DIR,
+    Command, OBD,
+};
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("When reading inspection rules: {0}.")]
+    Io(#[from] std::io::Error),
+    #[error("Inspection rules are malformed: {0}.")]
+    Json(#[from] serde_json::Error),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Ignition {
+    Spark,
+    Compression,
+}
+
+/// How many monitors may be incomplete for a range of vehicles.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MonitorAllowance {
+    /// Model years the allowance applies to, inclusive.
+    /// `None` leaves the range open on that side.
+    pub from_model_year: Option<i32>,
+    pub to_model_year: Option<i32>,
+
+    /// Only applies to this kind of engine, or any engine when `None`
+    pub ignition: Option<Ignition>,
+    pub max_incomplete: usize,
+}
+
+impl MonitorAllowance {
+    fn applies_to(&self, model_year: Option<i32>, ignition: Option<Ignition>) -> bool {
+        if self.ignition.is_some() && self.ignition != ignition {
+            return false;
+        }
+
+        // without a model year only open ended allowances can apply
+        match model_year {
+            Some(year) => {
+                self.from_model_year.is_none_or(|from| year >= from)
+                    && self.to_model_year.is_none_or(|to| year <= to)
+            }
+            None => self.from_model_year.is_none() && self.to_model_year.is_none(),
+        }
+    }
+}
+
+/// What a jurisdiction requires to pass an OBD inspection.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InspectionRules {
+    pub name: String,
+
+    /// Vehicles older than this aren't OBD tested
+    pub first_model_year: Option<i32>,
+
+    pub fail_on_mil: bool,
+    pub fail_on_confirmed_codes: bool,
+    pub fail_on_permanant_codes: bool,
+
+    /// The first allowance that applies is used.
+    /// If none apply, every monitor must be complete.
+    pub allowances: Vec<MonitorAllowance>,
+
+    /// Monitors that aren't checked (e.g "Evaporative System")
+    pub ignored_monitors: Vec<String>,
+}
+
+impl Default for InspectionRules {
+    fn default() -> Self {
+        Self::epa()
+    }
+}
+
+impl InspectionRules {
+    /// US EPA guidance for OBD-II inspections.
+    ///
+    /// Two incomplete monitors are allowed for gasoline model years 1996 to 2000,
+    /// one for 2001 and newer. Diesels must have every monitor complete.
+    pub fn epa() -> Self {
+        Self {
+            name: "EPA".to_string(),
+            first_model_year: Some(1996),
+            fail_on_mil: true,
+            fail_on_confirmed_codes: false,
+            fail_on_permanant_codes: false,
+            allowances: vec![
+                MonitorAllowance {
+                    from_model_year: Some(1996),
+                    to_model_year: Some(2000),
+                    ignition: Some(Ignition::Spark),
+                    max_incomplete: 2,
+                },
+                MonitorAllowance {
+                    from_model_year: Some(2001),
+                    to_model_year: None,
+                    ignition: Some(Ignition::Spark),
+                    max_incomplete: 1,
+                },
+            ],
+            ignored_monitors: Vec::new(),
+        }
+    }
+
+    /// Every monitor complete, no codes of any kind.
+    pub fn strict() -> Self {
+        Self {
+            name: "Strict".to_string(),
+            first_model_year: None,
+            fail_on_mil: true,
+            fail_on_confirmed_codes: true,
+            fail_on_permanant_codes: true,
+            allowances: Vec::new(),
+            ignored_monitors: Vec::new(),
+        }
+    }
+
+    pub fn from_file(path: &Path) -> Result<Self, Error> {
+        Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    /// Rules saved as `inspection-rules.json` in the app data dir,
+    /// or the EPA rules if there are none.
+    pub fn load() -> Self {
+        let Some(path) = APP_DATA_DIR
+            .get()
+            .map(|dir| dir.join("inspection-rules.json"))
+        else {
+            return Self::default();
+        };
+
+        if !path.exists() {
+            return Self::default();
+        }
+
+        Self::from_file(&path).unwrap_or_else(|err| {
+            println!("{err} Using EPA rules.");
+            Self::default()
+        })
+    }
+
+    pub fn allowed_incomplete(&self, model_year: Option<i32>, ignition: Option<Ignition>) -> usize {
+        self.allowances
+            .iter()
+            .find(|allowance| allowance.applies_to(model_year, ignition))
+            .map(|allowance| allowance.max_incomplete)
+            .unwrap_or(0)
+    }
+
+    pub fn evaluate(&self, vehicle: &InspectionData) -> InspectionReport {
+        let mut verdict = InspectionVerdict::Pass;
+        let mut reasons = Vec::new();
+
+        if let (Some(first), Some(year)) = (self.first_model_year, vehicle.model_year) {
+            if year < first {
+                reasons.push(format!(
+                    "Model year {year} is older than {first}, {} rules don't require an OBD test.",
+                    self.name
+                ));
+
+                return self.report(vehicle, InspectionVerdict::Exempt, reasons, 0);
+            }
+        }
+
+        if !vehicle.obd_compliant {
+            reasons.push(format!(
+                "Vehicle reports '{}', not an OBD-II compliant standard.",
+                vehicle.obd_standard
+            ));
+            verdict = verdict.max(InspectionVerdict::Fail);
+        }
+
+        if self.fail_on_mil && vehicle.mil_on {
+            reasons.push("Check engine light (MIL) is on.".to_string());
+            verdict = verdict.max(InspectionVerdict::Fail);
+        }
+
+        if self.fail_on_confirmed_codes && vehicle.confirmed_codes > 0 {
+            reasons.push(format!(
+                "{} confirmed trouble code(s) stored.",
+                vehicle.confirmed_codes
+            ));
+            verdict = verdict.max(InspectionVerdict::Fail);
+        }
+
+        if self.fail_on_permanant_codes && vehicle.permanant_codes > 0 {
+            reasons.push(format!(
+                "{} permanant trouble code(s) stored.",
+                vehicle.permanant_codes
+            ));
+            verdict = verdict.max(InspectionVerdict::Fail);
+        }
+
+        // Pending codes don't fail a test, but the MIL may come on before the next one
+        if vehicle.pending_codes > 0 {
+            reasons.push(format!(
+                "{} pending trouble code(s) stored, the MIL may come on soon.",
+                vehicle.pending_codes
+            ));
+        }
+
+        let incomplete = self.incomplete_monitors(vehicle);
+        let allowed = self.allowed_incomplete(vehicle.model_year, vehicle.ignition);
+        if incomplete.len() > allowed {
+            reasons.push(format!(
+                "{} monitor(s) incomplete ({}), {} rules allow {allowed}.",
+                incomplete.len(),
+                incomplete.join(", "),
+                self.name
+            ));
+            verdict = verdict.max(InspectionVerdict::NotReady);
+        }
+
+        if vehicle.model_year.is_none() {
+            reasons.push(
+                "Model year is unknown, the strictest monitor allowance was used.".to_string(),
+            );
+        }
+
+        self.report(vehicle, verdict, reasons, allowed)
+    }
+
+    fn incomplete_monitors(&self, vehicle: &InspectionData) -> Vec<&'static str> {
+        vehicle
+            .monitors
+            .iter()
+            .filter(|test| test.available && !test.complete)
+            .filter(|test| {
+                !self
+                    .ignored_monitors
+                    .iter()
+                    .any(|ignored| ignored.eq_ignore_ascii_case(test.name.trim()))
+            })
+            .map(|test| test.name)
+            .collect()
+    }
+
+    fn report(
+        &self,
+        vehicle: &InspectionData,
+        verdict: InspectionVerdict,
+        reasons: Vec<String>,
+        allowed_incomplete: usize,
+    ) -> InspectionReport {
+        InspectionReport {
+            rules: self.name.clone(),
+            verdict,
+            reasons,
+            incomplete_monitors: self.incomplete_monitors(vehicle),
+            allowed_incomplete,
+            vehicle: vehicle.clone(),
+        }
+    }
+}
+
+/// Ordered from best to worst.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub enum InspectionVerdict {
+    Pass,
+
+    /// Vehicle isn't subject to an OBD test
+    Exempt,
+
+    /// Too many monitors are incomplete, drive the vehicle more before testing
+    NotReady,
+    Fail,
+}
+
+impl InspectionVerdict {
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Pass => "pass",
+            Self::Exempt => "exempt",
+            Self::NotReady => "not ready",
+            Self::Fail => "fail",
+        }
+    }
+}
+
+/// What the vehicle reported, used to decide the verdict.
+#[derive(Debug, Clone, Serialize)]
+pub struct InspectionData {
+    pub mil_on: bool,
+    pub confirmed_codes: usize,
+    pub pending_codes: usize,
+    pub permanant_codes: usize,
+
+    /// Readiness since codes were cleared (0101)
+    pub monitors: Vec<Test>,
+
+    pub obd_standard: String,
+    pub obd_compliant: bool,
+    pub fuel_type: String,
+    pub ignition: Option<Ignition>,
+    pub model_year: Option<i32>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct InspectionReport {
+    /// Name of the rules used
+    pub rules: String,
+    pub verdict: InspectionVerdict,
+
+    /// Why the vehicle didn't pass, or anything worth knowing when it did
+    pub reasons: Vec<String>,
+    pub incomplete_monitors: Vec<&'static str>,
+    pub allowed_incomplete: usize,
+    pub vehicle: InspectionData,
+}
+
+impl OBD {
+    /// Read everything an inspection looks at from the vehicle.
+    pub fn get_inspection_data(&mut self) -> InspectionData {
+        let codes = self.get_all_trouble_codes();
+
+        let mut monitors = self.get_common_tests_status().to_vec();
+        monitors.extend(self.get_advanced_tests_status());
+
+        let fuel_type = self.fuel_type().to_string();
+        let ignition = match self.get_engine_type() {
+            EngineType::SparkIgnition => Some(Ignition::Spark),
+            EngineType::CompressionIgnition => Some(Ignition::Compression),
+            EngineType::Unknown if fuel_type.contains("Diesel") => Some(Ignition::Compression),
+            EngineType::Unknown => None,
+        };
+
+        // 4: OBD-I, 5: not OBD compliant, 251-255: unavailable.
+        // Reserved values are given the benefit of the doubt.
+        let standard = self.query(Command::new_pid(b"011C")).a_value() as u8;
+        let obd_compliant = !matches!(standard, 4 | 5 | 251..=255);
+
+        let model_year = self
+            .get_vehicle_context()
+            .cloned()
+            .or_else(|| {
+                self.get_vin()
+                    .and_then(|vin| VehicleContext::from_vin(&vin))
+            })
+            .and_then(|vehicle| vehicle.model_year);
+
+        InspectionData {
+            mil_on: self.has_check_engine_light(),
+            confirmed_codes: codes.iter().filter(|code| code.status.confirmed).count(),
+            pending_codes: codes.iter().filter(|code| code.status.pending).count(),
+            permanant_codes: codes.iter().filter(|code| code.status.permanant).count(),
+            monitors,
+            obd_standard: OBDStandard::from_u8(standard).to_string(),
+            obd_compliant,
+            fuel_type,
+            ignition,
+            model_year,
+        }
+    }
+
+    /// Whether the vehicle would pass an emissions inspection under `rules`.
+    pub fn inspection_readiness(&mut self, rules: &InspectionRules) -> InspectionReport {
+        rules.evaluate(&self.get_inspection_data())
+    }
+}
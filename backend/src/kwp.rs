@@ -0,0 +1,372 @@
+// KWP2000 (ISO 14230) manufacturer diagnostics.
+//
+// Older (mostly pre-2008 European) vehicles only expose non-emissions data
+// through KWP2000 services, either on K-line (ISO 14230-4) or on CAN.
+// Requests are sent through the ELM327 like any other message, the ELM
+// takes care of the header and checksum once the session is initialized.
+
+use std::fmt;
+
+use thiserror::Error;
+
+use crate::{
+    diagnostics::{TroubleCode, TroubleCodeStatus},
+    reply::{self, negative_response},
+    Command, EcuMessage, OBD,
+};
+
+/// KWP2000 service identifiers used by obdium
+pub mod service {
+    pub const START_DIAGNOSTIC_SESSION: u8 = 0x10;
+    pub const READ_DTC_BY_STATUS: u8 = 0x18;
+    pub const READ_ECU_IDENTIFICATION: u8 = 0x1A;
+    pub const READ_DATA_BY_LOCAL_IDENTIFIER: u8 = 0x21;
+    pub const TESTER_PRESENT: u8 = 0x3E;
+}
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("{0}")]
+    Reply(#[from] reply::Error),
+    #[error("Malformed response to service {0:#04X}.")]
+    InvalidResponse(u8),
+    #[error("KWP2000 initialization failed. ELM327 replied: '{0}'.")]
+    InitFailed(String),
+    #[error("{0}")]
+    Obd(crate::obd::Error),
+}
+
+impl From<crate::obd::Error> for Error {
+    fn from(err: crate::obd::Error) -> Self {
+        Error::Obd(err)
+    }
+}
+
+/// How the ELM327 wakes up a K-line ECU
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KwpInit {
+    /// 5 baud initialization (ELM protocol 4, `ATSI`)
+    Slow,
+    /// Fast initialization (ELM protocol 5, `ATFI`)
+    Fast,
+}
+
+/// The physical layer KWP2000 is spoken on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KwpTransport {
+    /// ISO 14230-4 on K-line
+    KLine(KwpInit),
+    /// KWP2000 on CAN (ISO 15765). The protocol selected on connect is kept.
+    Can,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct KwpConfig {
+    pub transport: KwpTransport,
+
+    /// Address of the ECU to talk to.
+    /// On K-line this is a single byte (e.g 0x10 for the engine ECU),
+    /// on CAN the 11-bit request id (e.g 0x7E0).
+    pub target: u16,
+
+    /// Address of the tester (us). 0xF1 is the standard off-board tester address.
+    pub tester: u8,
+
+    /// Interval between keep-alive (wakeup) messages, in milliseconds.
+    /// K-line sessions time out after ~5 seconds of silence.
+    /// `None` disables the wakeup messages.
+    pub wakeup_interval_ms: Option<u32>,
+}
+
+impl Default for KwpConfig {
+    fn default() -> Self {
+        Self {
+            transport: KwpTransport::KLine(KwpInit::Fast),
+            target: 0x10,
+            tester: 0xF1,
+            wakeup_interval_ms: Some(3000),
+        }
+    }
+}
+
+/// Options for ReadECUIdentification (0x1A)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IdentificationOption {
+    EcuIdentificationDataTable,
+    Vin,
+    HardwareNumber,
+    SupplierHardwareNumber,
+    SupplierSoftwareNumber,
+    SystemName,
+    Other(u8),
+}
+
+impl IdentificationOption {
+    pub fn as_u8(&self) -> u8 {
+        match self {
+            Self::EcuIdentificationDataTable => 0x80,
+            Self::Vin => 0x90,
+            Self::HardwareNumber => 0x91,
+            Self::SupplierHardwareNumber => 0x92,
+            Self::SupplierSoftwareNumber => 0x94,
+            Self::SystemName => 0x97,
+            Self::Other(option) => *option,
+        }
+    }
+}
+
+/// Status byte reported with every DTC by ReadDTCByStatus (0x18)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KwpDtcStatus(pub u8);
+
+impl KwpDtcStatus {
+    /// Whether the ECU is requesting the warning lamp on for this code
+    pub fn warning_lamp(&self) -> bool {
+        self.0 & 0x80 != 0
+    }
+
+    /// Fault is present at the time of the request
+    pub fn present(&self) -> bool {
+        (self.0 >> 5) & 0b11 == 0b11
+    }
+
+    /// Fault has been detected but is not present at the time of the request
+    pub fn stored(&self) -> bool {
+        (self.0 >> 5) & 0b11 == 0b01
+    }
+
+    /// Fault is maturing or intermittent
+    pub fn intermittent(&self) -> bool {
+        (self.0 >> 5) & 0b11 == 0b10
+    }
+
+    /// Manufacturer defined failure symptom (lower nibble)
+    pub fn symptom(&self) -> u8 {
+        self.0 & 0x0F
+    }
+}
+
+impl fmt::Display for KwpDtcStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let state = match (self.0 >> 5) & 0b11 {
+            0b00 => "not detected",
+            0b01 => "stored, not present",
+            0b10 => "intermittent",
+            _ => "present",
+        };
+
+        write!(f, "{state}")?;
+        if self.warning_lamp() {
+            write!(f, ", warning lamp on")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct KwpTroubleCode {
+    pub code: TroubleCode,
+    pub status: KwpDtcStatus,
+}
+
+impl OBD {
+    /// Configure the ELM327 for KWP2000 and initialize the bus.
+    ///
+    /// For K-line, this selects protocol 4 (slow init) or 5 (fast init),
+    /// sets the header to talk to `config.target`, sets up TesterPresent
+    /// wakeup messages and performs the init with `ATSI`/`ATFI`.
+    /// For CAN only the header (request id) is changed.
+    pub fn kwp_init(&mut self, config: &KwpConfig) -> Result<(), Error> {
+        match config.transport {
+            KwpTransport::KLine(init) => {
+                let (protocol, init_command): (&'static [u8], &'static [u8]) = match init {
+                    KwpInit::Slow => (b"ATSP4", b"ATSI"),
+                    KwpInit::Fast => (b"ATSP5", b"ATFI"),
+                };
+
+                let target = config.target as u8;
+                self.kwp_expect_ok(Command::new_at(protocol))?;
+                self.kwp_expect_ok(Command::new_arb(&format!(
+                    "ATSH 81 {:02X} {:02X}",
+                    target, config.tester
+                )))?;
+
+                match config.wakeup_interval_ms {
+                    Some(interval) => {
+                        // TesterPresent, response not required
+                        self.kwp_expect_ok(Command::new_arb(&format!(
+                            "ATWM 82 {:02X} {:02X} 3E 02",
+                            target, config.tester
+                        )))?;
+
+                        // ATSW is in units of 20ms
+                        let interval = (interval / 20).clamp(1, 0xFF);
+                        self.kwp_expect_ok(Command::new_arb(&format!("ATSW {:02X}", interval)))?;
+                    }
+                    None => self.kwp_expect_ok(Command::new_arb("ATSW 00"))?,
+                }
+
+                // A failed init replies with "BUS INIT: ...ERROR"
+                let response = self.query_at(Command::new_at(init_command))?;
+                let reply = response.formatted_response.unwrap_or_default();
+                if reply.contains("ERROR") || !reply.contains("OK") {
+                    return Err(Error::InitFailed(reply.trim().to_string()));
+                }
+
+                self.protocol = match init {
+                    KwpInit::Slow => 4,
+                    KwpInit::Fast => 5,
+                };
+            }
+            KwpTransport::Can => {
+                self.kwp_expect_ok(Command::new_arb(&format!("ATSH {:03X}", config.target)))?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Send a KWP2000 request and return the data of the positive response,
+    /// excluding the response service id (`service + 0x40`).
+    ///
+    /// Waits on the ECU while it replies with 'response pending' (0x78).
+    pub fn kwp_request(&mut self, service: u8, data: &[u8]) -> Result<Vec<u8>, Error> {
+        let mut request = format!("{:02X}", service);
+        for byte in data {
+            request.push_str(&format!("{:02X}", byte));
+        }
+
+        let messages = self.query_service(service, Command::new_arb(&request));
+        OBD::kwp_parse_reply(service, &messages)
+    }
+
+    /// StartDiagnosticSession (0x10).
+    /// 0x81 is the standard (default) session, 0x89 is commonly the extended session.
+    pub fn kwp_start_diagnostic_session(&mut self, session: u8) -> Result<(), Error> {
+        self.kwp_request(service::START_DIAGNOSTIC_SESSION, &[session])?;
+        Ok(())
+    }
+
+    /// TesterPresent (0x3E), keeps the diagnostic session alive
+    pub fn kwp_tester_present(&mut self) -> Result<(), Error> {
+        self.kwp_request(service::TESTER_PRESENT, &[0x01])?;
+        Ok(())
+    }
+
+    /// ReadECUIdentification (0x1A).
+    /// Returns the identification record, excluding the echoed option byte.
+    pub fn kwp_read_ecu_identification(
+        &mut self,
+        option: IdentificationOption,
+    ) -> Result<Vec<u8>, Error> {
+        let option = option.as_u8();
+        let reply = self.kwp_request(service::READ_ECU_IDENTIFICATION, &[option])?;
+        match reply.split_first() {
+            Some((&echo, record)) if echo == option => Ok(record.to_vec()),
+            _ => Err(Error::InvalidResponse(service::READ_ECU_IDENTIFICATION)),
+        }
+    }
+
+    /// ReadECUIdentification (0x1A) for records that are ASCII strings,
+    /// such as part numbers or the VIN.
+    pub fn kwp_read_ecu_identification_string(
+        &mut self,
+        option: IdentificationOption,
+    ) -> Result<String, Error> {
+        let record = self.kwp_read_ecu_identification(option)?;
+        Ok(String::from_utf8_lossy(&record)
+            .trim_matches(|c: char| c.is_whitespace() || c == '\0')
+            .to_string())
+    }
+
+    /// ReadDataByLocalIdentifier (0x21).
+    /// Returns the record, excluding the echoed local identifier.
+    /// The layout of the record is manufacturer specific.
+    pub fn kwp_read_local_identifier(&mut self, identifier: u8) -> Result<Vec<u8>, Error> {
+        let reply = self.kwp_request(service::READ_DATA_BY_LOCAL_IDENTIFIER, &[identifier])?;
+        match reply.split_first() {
+            Some((&echo, record)) if echo == identifier => Ok(record.to_vec()),
+            _ => Err(Error::InvalidResponse(
+                service::READ_DATA_BY_LOCAL_IDENTIFIER,
+            )),
+        }
+    }
+
+    /// ReadDTCByStatus (0x18).
+    ///
+    /// `status` selects which codes to report (0x00 for all stored codes,
+    /// 0x02 for codes present at the time of the request) and `group`
+    /// the group of codes (0xFF00 for all groups).
+    pub fn kwp_read_dtcs_by_status(
+        &mut self,
+        status: u8,
+        group: u16,
+    ) -> Result<Vec<KwpTroubleCode>, Error> {
+        let [group_high, group_low] = group.to_be_bytes();
+        let reply = self.kwp_request(
+            service::READ_DTC_BY_STATUS,
+            &[status, group_high, group_low],
+        )?;
+
+        // Response: <number of DTCs> [<DTC high> <DTC low> <status>]...
+        let Some((&count, records)) = reply.split_first() else {
+            return Err(Error::InvalidResponse(service::READ_DTC_BY_STATUS));
+        };
+
+        let codes: Vec<KwpTroubleCode> = records
+            .chunks_exact(3)
+            .take(count as usize)
+            .map(|record| {
+                let status = KwpDtcStatus(record[2]);
+                let code_status = TroubleCodeStatus {
+                    confirmed: status.stored() || status.present(),
+                    pending: status.intermittent(),
+                    ..Default::default()
+                };
+
+                KwpTroubleCode {
+                    code: TroubleCode::from_bytes(record[0], record[1], code_status),
+                    status,
+                }
+            })
+            .collect();
+
+        if codes.len() != count as usize {
+            println!(
+                "kwp_read_dtcs_by_status; ecu reported {count} codes but sent {}.",
+                codes.len()
+            );
+        }
+
+        Ok(codes)
+    }
+
+    fn kwp_expect_ok(&mut self, command: Command) -> Result<(), Error> {
+        let response = self.query_at(command)?;
+        let reply = response.formatted_response.unwrap_or_default();
+        if reply.contains("OK") {
+            Ok(())
+        } else {
+            Err(Error::InitFailed(reply.trim().to_string()))
+        }
+    }
+
+    fn kwp_parse_reply(service: u8, messages: &[EcuMessage]) -> Result<Vec<u8>, Error> {
+        let mut negative = None;
+
+        for message in messages {
+            match message.data.as_slice() {
+                [sid, rest @ ..] if *sid == service.wrapping_add(0x40) => {
+                    return Ok(rest.to_vec());
+                }
+                _ => negative = negative.or(negative_response(service, message)),
+            }
+        }
+
+        match negative {
+            Some(code) => Err(reply::Error::NegativeResponse { service, code }.into()),
+            None if messages.is_empty() => Err(reply::Error::NoResponse(service).into()),
+            None => Err(Error::InvalidResponse(service)),
+        }
+    }
+}
@@ -1,11 +1,24 @@
+pub mod cache;
+pub mod capabilities;
+pub mod catalog;
+pub mod clear;
 mod cmd;
+pub mod control;
 pub mod dicts;
+pub mod drive_cycle;
+pub mod fingerprint;
+pub mod history;
+pub mod inspection;
+pub mod kwp;
 pub mod mid;
 pub mod obd;
 mod pid;
-mod replay;
+pub mod profile;
+pub mod replay;
+pub mod reply;
 mod response;
 pub mod scalar;
+pub mod uds;
 pub mod vin;
 
 use std::sync::atomic::AtomicUsize;
@@ -5,15 +5,17 @@ mod bridge;
 mod stats;
 
 use bridge::events::{
-    do_send_connection_status, listen_connect_elm, listen_decode_vin, listen_send_ports,
+    do_send_connection_status, listen_connect_elm, listen_decode_vin, listen_search_dtcs,
+    listen_send_ports,
 };
 use obdium::{
+    catalog::set_catalog_path,
     vin::{vpic_db_path, APP_DATA_DIR},
     OBD,
 };
 use stats::{
-    critical_frequency_calls, custom_pid_calls, frequent_calls, high_frequency_calls,
-    less_frequent_calls, once_calls, oxygen_sensors,
+    critical_frequency_calls, custom_pid_calls, drive_cycle_watcher, dtc_watcher, frequent_calls,
+    high_frequency_calls, less_frequent_calls, once_calls, oxygen_sensors,
 };
 
 use std::{
@@ -98,6 +100,8 @@ fn track_data(window: &Arc<WebviewWindow>, obd: &Arc<Mutex<OBD>>) {
     oxygen_sensors(window, obd);
     once_calls(window, obd);
     custom_pid_calls(window, obd);
+    dtc_watcher(window, obd);
+    drive_cycle_watcher(window, obd);
 }
 
 fn connect_obd(window: &WebviewWindow, port: String, baud_rate: u32, protocol: u8) -> Option<OBD> {
@@ -145,6 +149,12 @@ fn main() {
             APP_DATA_DIR.get_or_init(|| app_data_dir.clone());
             std::fs::create_dir_all(&app_data_dir)?;
             let db_path = vpic_db_path().unwrap();
+            if let Ok(catalog_path) = app.path().resolve(
+                "data/code-descriptions.sqlite",
+                tauri::path::BaseDirectory::Resource,
+            ) {
+                set_catalog_path(catalog_path);
+            }
             let xz_path = app
                 .path()
                 .resolve("data/vpic.sqlite.xz", tauri::path::BaseDirectory::Resource)
@@ -167,6 +177,7 @@ fn main() {
                 listen_send_ports(&window_arc);
                 listen_track_custom_pid(&window_arc);
                 listen_connect_elm(&window_arc);
+                listen_search_dtcs(&window_arc);
 
                 sleep(Duration::from_secs(1));
 
@@ -1,11 +1,14 @@
 use std::collections::HashMap;
 
+use serde::{Deserialize, Serialize};
+
 use crate::{
     engine::CylinderNumber,
     scalar::{Scalar, Unit},
     BankNumber, Command, SensorNumber, OBD,
 };
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum EvapLeakSize {
     Large,  // 0.150"
     Medium, // 0.090"
@@ -13,25 +16,111 @@ pub enum EvapLeakSize {
     Tiny,   // 0.020"
 }
 
+impl EvapLeakSize {
+    pub const ALL: [EvapLeakSize; 4] = [Self::Large, Self::Medium, Self::Small, Self::Tiny];
+
+    /// Monitor id of the leak test for this size
+    pub fn mid(&self) -> u8 {
+        match self {
+            Self::Large => 0x39,
+            Self::Medium => 0x3A,
+            Self::Small => 0x3B,
+            Self::Tiny => 0x3C,
+        }
+    }
+}
+
+/// A single on-board monitor test result reported through service 06.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct MonitorTest {
+    /// ECU that reported the result
+    pub ecu: String,
+
+    /// Monitor id (e.g 0x21 catalyst bank 1) and test id within the monitor
+    pub mid: u8,
+    pub tid: u8,
+
+    /// Unit and scaling id, see SAE J1979 appendix E.
+    /// Ids 0x80 and above are signed values.
+    pub unit_scaling: u8,
+
     /// Min and maximum allowed values for the specific test
     /// If a value is less than `min` or greater than `max`, the test is considered a FAIL.
-    min: u64,
-    max: u64,
+    pub min: u16,
+    pub max: u16,
 
-    value: u64,
-    mid: [u8; 2],
+    pub value: u16,
 }
 
 impl MonitorTest {
     pub fn has_passed(&self) -> bool {
-        self.value >= self.min && self.value <= self.max
+        if self.is_signed() {
+            let (value, min, max) = (self.value as i16, self.min as i16, self.max as i16);
+            value >= min && value <= max
+        } else {
+            self.value >= self.min && self.value <= self.max
+        }
+    }
+
+    pub fn is_signed(&self) -> bool {
+        self.unit_scaling >= 0x80
     }
 }
 
 impl OBD {
-    pub fn get_supported_mids(&mut self) -> HashMap<String, Vec<String>> {
-        todo!()
+    /// Monitor ids each ECU has test results for, keyed by ECU.
+    pub fn get_supported_mids(&mut self) -> HashMap<String, Vec<u8>> {
+        self.get_supported_ids(0x06, None)
+    }
+
+    /// Every test result reported for monitor `mid`.
+    ///
+    /// Only the CAN (ISO 15765-4) format of service 06 is understood.
+    pub fn get_monitor_tests(&mut self, mid: u8) -> Vec<MonitorTest> {
+        let mut tests = Vec::new();
+
+        for message in self.query_messages(Command::new_arb(&format!("06{mid:02X}"))) {
+            if message.data.first() != Some(&0x46) {
+                continue;
+            }
+
+            // 46 followed by one or more
+            // <mid> <tid> <unit and scaling> <value> <min> <max>
+            for record in message.data[1..].chunks_exact(9) {
+                if record[0] != mid {
+                    continue;
+                }
+
+                tests.push(MonitorTest {
+                    ecu: message.ecu.clone(),
+                    mid,
+                    tid: record[1],
+                    unit_scaling: record[2],
+                    value: u16::from_be_bytes([record[3], record[4]]),
+                    min: u16::from_be_bytes([record[5], record[6]]),
+                    max: u16::from_be_bytes([record[7], record[8]]),
+                });
+            }
+        }
+
+        tests
+    }
+
+    /// Test results of every supported monitor, from every ECU.
+    pub fn get_all_monitor_tests(&mut self) -> Vec<MonitorTest> {
+        let mut mids: Vec<u8> = self
+            .get_supported_mids()
+            .into_values()
+            .flatten()
+            // support ranges, not monitors
+            .filter(|mid| mid % 0x20 != 0)
+            .collect();
+        mids.sort();
+        mids.dedup();
+
+        mids.into_iter()
+            .flat_map(|mid| self.get_monitor_tests(mid))
+            .collect()
     }
 
     pub fn test_oxygen_sensor_monitor(
@@ -56,12 +145,12 @@ impl OBD {
         todo!()
     }
 
-    pub fn test_evap_monitor(&mut self, leak_size: EvapLeakSize) -> MonitorTest {
-        todo!()
+    pub fn test_evap_monitor(&mut self, leak_size: EvapLeakSize) -> Vec<MonitorTest> {
+        self.get_monitor_tests(leak_size.mid())
     }
 
-    pub fn test_purge_flow_monitor(&mut self) -> MonitorTest {
-        todo!()
+    pub fn test_purge_flow_monitor(&mut self) -> Vec<MonitorTest> {
+        self.get_monitor_tests(0x3D)
     }
 
     pub fn test_oxygen_sensor_heater(
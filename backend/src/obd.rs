@@ -1,3 +1,4 @@
+use serde::{Deserialize, Serialize};
 use serialport::SerialPort;
 use sqlite::State;
 use std::collections::HashMap;
@@ -5,21 +6,27 @@ use std::fmt;
 use std::io::{Read, Write};
 use std::str::{self, FromStr};
 use std::thread::sleep;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
+use crate::cache::ResponseCache;
+use crate::capabilities::Capabilities;
+use crate::catalog::VehicleContext;
 use crate::cmd::{Command, CommandType};
+use crate::profile::VehicleProfile;
+use crate::registry::PidError;
+use crate::replay::{RecordedRequest, Recorder};
 use crate::response::Response;
 use crate::scalar::{Scalar, Unit, UnitPreferences};
 use crate::vin::VIN;
 use crate::MODE22_PIDS_DB_PATH;
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum BankNumber {
     Bank1,
     Bank2,
 }
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum SensorNumber {
     Sensor1,
     Sensor2,
@@ -31,6 +38,19 @@ pub enum SensorNumber {
     Sensor8,
 }
 
+impl SensorNumber {
+    pub const ALL: [SensorNumber; 8] = [
+        SensorNumber::Sensor1,
+        SensorNumber::Sensor2,
+        SensorNumber::Sensor3,
+        SensorNumber::Sensor4,
+        SensorNumber::Sensor5,
+        SensorNumber::Sensor6,
+        SensorNumber::Sensor7,
+        SensorNumber::Sensor8,
+    ];
+}
+
 #[derive(Debug)]
 pub enum Error {
     ConnectionFailed,
@@ -75,6 +95,29 @@ pub enum Service {
     Mode22,
 }
 
+/// A complete message from a single ECU.
+///
+/// Multi-frame ISO-TP responses are reassembled into one message.
+/// Headers, PCI bytes and K-line checksums are stripped, so `data`
+/// starts at the service byte (e.g 0x43 or 0x7F).
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct EcuMessage {
+    /// Address of the responding ECU as shown by the ELM327
+    /// (e.g "7E8" on CAN, "10" on K-line)
+    pub ecu: String,
+    pub data: Vec<u8>,
+}
+
+/// A manufacturer specific pid and how to calculate its value
+#[derive(Debug, Clone)]
+pub struct Mode22Pid {
+    /// Request, e.g "221310"
+    pub pid: String,
+    pub equation: String,
+    pub unit: String,
+    pub description: String,
+}
+
 // Fake serial port used for simulating.
 // Specifically demo mode.
 struct DummySerialPort;
@@ -178,26 +221,69 @@ impl Write for DummySerialPort {
 #[derive(Default)]
 pub struct OBD {
     connection: Option<Box<dyn SerialPort>>,
-    elm_version: Option<String>,
-    freeze_frame_query: bool,
-    protocol: u8,
+    pub(crate) elm_version: Option<String>,
+    freeze_frame: Option<u8>,
+    vehicle: Option<VehicleContext>,
+    pub(crate) protocol: u8,
 
     pub(crate) requests_path: String,
     pub(crate) record_requests: bool,
     pub(crate) replay_requests: bool,
+    pub(crate) recorder: Option<Recorder>,
+
+    /// Requests of the recording being replayed, read when first needed
+    pub(crate) recorded: Option<HashMap<String, Vec<RecordedRequest>>>,
+
+    /// When the last request was sent, to time the response
+    pub(crate) sent_at: Option<Instant>,
 
     pub(crate) unit_preferences: UnitPreferences,
+
+    /// Why the last request failed, `None` if it succeeded
+    pub(crate) pid_error: Option<PidError>,
+
+    /// Reply to the last request, by ECU
+    pub(crate) last_messages: Vec<EcuMessage>,
+
+    pub(crate) cache_responses: bool,
+    pub(crate) response_cache: ResponseCache,
+
+    /// Reply already received to a request, used instead of sending it again.
+    /// See `PidDef::decode_response`.
+    pub(crate) answered: Option<(String, Response)>,
+
+    /// Header set with ATSH, `None` while the default one is used
+    pub(crate) header: Option<String>,
+
+    pub(crate) capabilities: Capabilities,
+
+    /// VIN entered by the user for a vehicle that doesn't report one
+    manual_vin: Option<String>,
+
+    /// What's known about the connected vehicle, see `load_vehicle_profile`
+    pub(crate) profile: Option<VehicleProfile>,
 }
 
 impl OBD {
     pub fn new() -> Self {
         Self {
             requests_path: "./data/requests.json".to_string(),
+            cache_responses: true,
             ..Default::default()
         }
     }
 
     pub fn connect(&mut self, port: &str, baud_rate: u32, protocol: u8) -> Result<(), Error> {
+        // could be a different vehicle
+        self.response_cache.clear();
+        self.header = None;
+        self.capabilities = Capabilities::default();
+        self.manual_vin = None;
+        self.profile = None;
+
+        // the recording may have changed since it was last replayed
+        self.recorded = None;
+
         if port == "DEMO MODE" {
             // No connection required
             self.replay_requests = true;
@@ -208,6 +294,7 @@ impl OBD {
 
         self.replay_requests = false;
         self.record_requests = false;
+        self.recorder = None;
 
         if self.connection.is_some() {
             return Ok(());
@@ -244,6 +331,11 @@ impl OBD {
     }
 
     pub fn disconnect(&mut self) {
+        self.response_cache.clear();
+        self.header = None;
+        self.capabilities = Capabilities::default();
+        self.manual_vin = None;
+        self.profile = None;
         if let Some(connection) = self.connection.take() {
             drop(connection);
             self.connection = None;
@@ -396,12 +488,31 @@ impl OBD {
 
     /// Toggles whether PID requests are redirected to freeze frame (service 02).
     ///
-    /// If `self.freeze_frame_query` is `true`, all service 01 PID requests
-    /// will be redirected to service 02 (freeze frame).
-    ///
-    /// Returns the updated value of `freeze_frame_query`.
+    /// If `state` is `true`, all service 01 PID requests
+    /// will be redirected to service 02 (freeze frame), reading frame 0.
     pub fn query_freeze_frame(&mut self, state: bool) {
-        self.freeze_frame_query = state
+        self.freeze_frame = state.then_some(0)
+    }
+
+    /// Redirect service 01 PID requests to the freeze frame numbered `frame`.
+    ///
+    /// `None` goes back to reading live data.
+    pub fn set_freeze_frame(&mut self, frame: Option<u8>) {
+        self.freeze_frame = frame
+    }
+
+    pub fn get_freeze_frame(&self) -> Option<u8> {
+        self.freeze_frame
+    }
+
+    /// Set the make and model year of the connected vehicle.
+    /// Used to look up manufacturer specific DTC descriptions.
+    pub fn set_vehicle_context(&mut self, vehicle: Option<VehicleContext>) {
+        self.vehicle = vehicle
+    }
+
+    pub fn get_vehicle_context(&self) -> Option<&VehicleContext> {
+        self.vehicle.as_ref()
     }
 
     pub fn read_from_user_input(&mut self) {
@@ -438,6 +549,8 @@ impl OBD {
     }
 
     pub fn send_command(&mut self, req: &mut Command) -> Result<(), Error> {
+        self.track_header(req);
+
         // We don't need to send a command since we already
         // know what the respond will be.
         if self.replay_requests {
@@ -458,6 +571,7 @@ impl OBD {
         }
 
         cmd.push(b'\r');
+        self.sent_at = Some(Instant::now());
         match stream.write_all(&cmd) {
             Ok(()) => (),
 
@@ -468,6 +582,22 @@ impl OBD {
         Ok(())
     }
 
+    // Follow the header requests are sent with, responses are cached by it
+    fn track_header(&mut self, request: &Command) {
+        let command: String = request
+            .as_string()
+            .split_whitespace()
+            .collect::<String>()
+            .to_uppercase();
+
+        if let Some(header) = command.strip_prefix("ATSH") {
+            self.header = Some(header.to_string());
+        } else if matches!(command.as_str(), "ATZ" | "ATD" | "ATWS") {
+            // reset to the default header
+            self.header = None;
+        }
+    }
+
     pub fn get_at_response(&mut self) -> Result<Response, Error> {
         let response = self.read_until(b'>')?;
 
@@ -481,6 +611,180 @@ impl OBD {
         Ok(meta_data)
     }
 
+    /// Send an AT command and read the ELM327 reply.
+    /// Honours request recording and replaying the same way `query` does.
+    pub(crate) fn query_at(&mut self, mut request: Command) -> Result<Response, Error> {
+        self.send_command(&mut request)?;
+
+        let response = if self.replay_requests {
+            self.get_recorded_response(&request)
+        } else {
+            self.get_at_response().unwrap_or(Response::no_data())
+        };
+
+        if self.record_requests {
+            self.save_request(&request, &response);
+        }
+
+        Ok(response)
+    }
+
+    /// Send a request and return every ECU message received in reply.
+    ///
+    /// Unlike `query`, the response is not collapsed into a single payload,
+    /// which makes it suitable for requests where multiple ECUs answer
+    /// or where the response spans multiple frames.
+    pub fn query_messages(&mut self, mut request: Command) -> Vec<EcuMessage> {
+        self.pid_error = None;
+        self.last_messages.clear();
+        if self.capabilities.rejects(&request.as_string()) {
+            self.pid_error = Some(PidError::Unsupported);
+            return Vec::new();
+        }
+
+        if let Err(err) = self.send_command(&mut request) {
+            println!("{}\tRequest: '{}'", err, request.as_string());
+            self.pid_error = Some(PidError::Adapter(err.as_str().to_string()));
+            return Vec::new();
+        }
+
+        let raw = if self.replay_requests {
+            self.get_recorded_response(&request)
+                .raw_response
+                .unwrap_or_default()
+        } else {
+            self.read_until(b'>').unwrap_or_default()
+        };
+
+        if self.record_requests {
+            self.save_request(&request, &Response::new(raw.replace("\r", ""), raw.clone()));
+        }
+
+        self.last_messages = OBD::parse_ecu_messages(&raw);
+        self.last_messages.clone()
+    }
+
+    /// Read the next reply from the ELM327 without sending a request.
+    /// Used when an ECU asks for more time (negative response 0x78).
+    pub(crate) fn read_messages(&mut self) -> Vec<EcuMessage> {
+        if self.replay_requests {
+            return Vec::new();
+        }
+
+        let raw = self.read_until(b'>').unwrap_or_default();
+        OBD::parse_ecu_messages(&raw)
+    }
+
+    /// Split a raw ELM327 response (headers on) into per-ECU messages.
+    ///
+    /// Handles 11-bit CAN (e.g "7E8 03 41 0D 00"), 29-bit CAN
+    /// (e.g "18 DA F1 10 03 41 0D 00") and legacy J1850/ISO 9141/ISO 14230
+    /// frames (e.g "48 6B 10 41 0D 00 CS"), where the last byte is a checksum.
+    ///
+    /// CAN frames are reassembled per ECU using the ISO-TP PCI byte.
+    /// Legacy protocols have no transport layer, so each line is its own message.
+    pub fn parse_ecu_messages(raw: &str) -> Vec<EcuMessage> {
+        let mut messages: Vec<EcuMessage> = Vec::new();
+
+        // Expected length of in-progress multi-frame (ISO-TP) messages by ECU
+        let mut pending: HashMap<String, (usize, usize)> = HashMap::new();
+
+        let cleaned = raw.replace("SEARCHING...", "");
+        for line in cleaned.split(['\r', '\n']) {
+            let tokens: Vec<&str> = line.split_whitespace().collect();
+            if tokens.len() < 2 {
+                continue;
+            }
+
+            // 11-bit CAN ids are 3 hex characters. Every other token is a byte.
+            let can_11bit = tokens[0].len() == 3;
+            let bytes: Option<Vec<u8>> = tokens[usize::from(can_11bit)..]
+                .iter()
+                .map(|token| {
+                    if token.len() != 2 {
+                        return None;
+                    }
+                    u8::from_str_radix(token, 16).ok()
+                })
+                .collect();
+
+            // Not a data line, e.g "NO DATA" or "BUS INIT: ...OK"
+            let Some(bytes) = bytes else {
+                continue;
+            };
+
+            let can_29bit = !can_11bit && bytes.len() > 4 && bytes[0] == 0x18 && bytes[1] == 0xDA;
+            let (ecu, frame) = if can_11bit {
+                (tokens[0].to_string(), bytes)
+            } else if can_29bit {
+                (format!("{:02X}", bytes[3]), bytes[4..].to_vec())
+            } else {
+                if bytes.len() < 4 {
+                    continue;
+                }
+
+                // ISO 14230 format bytes with a length of 0 carry an extra length byte
+                let format = bytes[0];
+                let header_len = if format & 0x80 != 0 && format & 0x3F == 0 {
+                    4
+                } else {
+                    3
+                };
+
+                if bytes.len() <= header_len + 1 {
+                    continue;
+                }
+
+                messages.push(EcuMessage {
+                    ecu: format!("{:02X}", bytes[2]),
+                    data: bytes[header_len..bytes.len() - 1].to_vec(),
+                });
+                continue;
+            };
+
+            if frame.is_empty() {
+                continue;
+            }
+
+            let pci = frame[0];
+            match pci >> 4 {
+                0x0 => {
+                    // single frame
+                    let length = (pci & 0x0F) as usize;
+                    let end = (1 + length).min(frame.len());
+                    messages.push(EcuMessage {
+                        ecu,
+                        data: frame[1..end].to_vec(),
+                    });
+                }
+                0x1 => {
+                    // first frame
+                    if frame.len() < 2 {
+                        continue;
+                    }
+
+                    let length = (((pci & 0x0F) as usize) << 8) | frame[1] as usize;
+                    messages.push(EcuMessage {
+                        ecu: ecu.clone(),
+                        data: frame[2..].to_vec(),
+                    });
+                    pending.insert(ecu, (messages.len() - 1, length));
+                }
+                0x2 => {
+                    // consecutive frame
+                    if let Some((index, length)) = pending.get(&ecu) {
+                        let message = &mut messages[*index];
+                        message.data.extend_from_slice(&frame[1..]);
+                        message.data.truncate(*length);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        messages
+    }
+
     pub fn get_pid_response(&mut self) -> Result<Response, Error> {
         let response = self.read_until(b'>')?;
         self.parse_pid_response(&response)
@@ -522,111 +826,22 @@ impl OBD {
         Ok(meta_data)
     }
 
+    /// Supported pids of `service` (e.g "01" or "09") by ECU, as hex strings (e.g "0C").
+    /// Kept in `capabilities` so unsupported pids aren't requested.
     pub fn get_service_supported_pids(&mut self, service: &str) -> HashMap<String, Vec<String>> {
-        // Service must be 2 characters long
-        // An example of a service would be
-        // '01' '05' or '09'
-        if service.len() != 2 {
-            println!("get_service_supported_pids; service ({service}) must be 2 characters long.");
+        let Ok(service) = u8::from_str_radix(service, 16) else {
+            println!("get_service_supported_pids; service ({service}) must be a hex byte.");
             return HashMap::new();
-        }
-
-        let service_as_bytes = service.as_bytes();
-
-        // A hashmap of supported pids for different ECUs
-        // Key -> ECU Name
-        // Value -> List of supported pids as strings
-        let mut supported_pids: HashMap<String, Vec<String>> = HashMap::new();
-
-        // The pids to request service 'service' to get a list of
-        // supported pids by the car for that service
-        let mut requests = Vec::new();
-
-        if service == "01" {
-            requests = vec!["00", "20", "40", "60", "80", "A0", "C0"];
-        } else if service == "05" || service == "09" {
-            requests = vec!["00"];
-        }
-
-        for request_pid in requests {
-            let request_pid_bytes = request_pid.as_bytes();
-            let command = [
-                service_as_bytes[0],
-                service_as_bytes[1],
-                request_pid_bytes[0],
-                request_pid_bytes[1],
-            ];
-            let response = self.query(Command::new_pid(&command));
-
-            let split = format!("41{}", request_pid);
-
-            let mut parsed: HashMap<String, Vec<String>> = self.parse_supported_pids(
-                &response,
-                &split,
-                i32::from_str_radix(request_pid, 16).unwrap_or_default(),
-            );
-
-            for (ecu_name, pids) in parsed.iter_mut() {
-                supported_pids
-                    .entry(ecu_name.to_string())
-                    .and_modify(|existing| existing.extend(pids.clone()))
-                    .or_insert(pids.to_vec());
-            }
-        }
-        supported_pids
-    }
-
-    // todo: inefficient...
-    pub(crate) fn parse_supported_pids(
-        &self,
-        response: &Response,
-        expected_header_split: &str,
-        start_pid: i32,
-    ) -> HashMap<String, Vec<String>> {
-        let sanitized = response.get_payload().unwrap_or_default().replace(" ", "");
-        let mut responses: Vec<&str> = sanitized.split(expected_header_split).collect();
-        responses = responses
-            .iter()
-            .filter(|x| !x.is_empty())
-            .copied()
-            .collect();
-
-        // println!("{:?}", responses);
-        // println!("split: {expected_header_split}");
-
-        let mut pid = start_pid + 1;
-        let mut respective_pids = HashMap::new();
-
-        // This is a loop because it is possible that
-        // the vehicle returns multiple responses from
-        // different ECUs, telling us what pids EACH ECU supports.
-        for (index, data) in responses.iter().enumerate() {
-            let ecu = match response.responding_ecus.get(index) {
-                Some(ecu_name) => ecu_name.clone(),
-                None => String::default(),
-            };
-            let mut supported_pids: Vec<String> = Vec::new();
-
-            // Loop through all characters in 'data'
-            // Get the character as a number from the hex character 'ch'
-            for byte_str in data.as_bytes().chunks(2).flat_map(std::str::from_utf8) {
-                let as_num = u8::from_str_radix(byte_str, 16).unwrap_or_default();
-
-                // Iterate through each character in binary representation
-                // If bit is 1, that is a supported pid.
-                for i in 0..8 {
-                    if (as_num & (1 << (7 - i))) != 0 {
-                        supported_pids.push(format!("{:02X}", pid));
-                    }
-                    pid += 1;
-                }
-            }
+        };
 
-            respective_pids.insert(ecu, supported_pids);
-            pid = start_pid + 1;
-        }
+        let frame = (service == 0x02).then_some(0);
+        let supported = self.get_supported_ids(service, frame);
+        self.capabilities.insert(service, supported.clone());
 
-        respective_pids
+        supported
+            .into_iter()
+            .map(|(ecu, pids)| (ecu, pids.iter().map(|pid| format!("{pid:02X}")).collect()))
+            .collect()
     }
 
     pub fn extract_ecu_names(response: &str) -> Vec<String> {
@@ -784,7 +999,12 @@ impl OBD {
         payload
     }
 
+    /// VIN reported by the vehicle (0902), or the one set with `set_manual_vin`.
     pub fn get_vin(&mut self) -> Option<VIN> {
+        if let Some(vin) = &self.manual_vin {
+            return VIN::new(vin).ok();
+        }
+
         match self.send_command(&mut Command::new_pid(b"0902")) {
             Ok(()) => (),
             Err(_) => return None,
@@ -809,6 +1029,12 @@ impl OBD {
         }
     }
 
+    /// Use `vin` for a vehicle that doesn't report its VIN.
+    /// Forgotten on disconnect.
+    pub fn set_manual_vin(&mut self, vin: Option<&VIN>) {
+        self.manual_vin = vin.map(|vin| vin.get_vin().to_string());
+    }
+
     pub(crate) fn format_response(response: &str) -> String {
         let chunks = response
             .as_bytes()
@@ -820,10 +1046,43 @@ impl OBD {
     }
 
     pub fn query(&mut self, mut request: Command) -> Response {
-        if self.freeze_frame_query && *request.command_type() == CommandType::PIDCommand {
+        if let Some(frame) = self.freeze_frame {
             let pid = request.get_pid();
-            if pid.starts_with(b"01") {
-                request.set_pid(&[b'0', b'2', pid[2], pid[3]]);
+            if *request.command_type() == CommandType::PIDCommand && pid.starts_with(b"01") {
+                // Service 02 requests carry the frame number after the pid
+                request = Command::new_arb(&format!(
+                    "02{}{}{:02X}",
+                    pid[2] as char, pid[3] as char, frame
+                ));
+            }
+        }
+
+        self.pid_error = None;
+        self.last_messages.clear();
+
+        let key = request.as_string();
+        if self.capabilities.rejects(&key) {
+            self.pid_error = Some(PidError::Unsupported);
+            return Response::no_data();
+        }
+
+        if let Some((_, response)) = self
+            .answered
+            .as_ref()
+            .filter(|(answered, _)| *answered == key)
+        {
+            let response = response.clone();
+            self.pid_error = PidError::from_response(&key, &response);
+            self.last_messages =
+                OBD::parse_ecu_messages(&response.raw_response().unwrap_or_default());
+            return response;
+        }
+
+        if self.cache_responses {
+            if let Some(response) = self.response_cache.get(self.header.as_deref(), &key) {
+                self.last_messages =
+                    OBD::parse_ecu_messages(&response.raw_response().unwrap_or_default());
+                return response;
             }
         }
 
@@ -836,6 +1095,7 @@ impl OBD {
                     String::from_utf8_lossy(request.get_at()),
                     String::from_utf8(request.get_pid().to_vec()).unwrap_or_default()
                 );
+                self.pid_error = Some(PidError::Adapter(err.as_str().to_string()));
                 return Response::default();
             }
         };
@@ -843,9 +1103,23 @@ impl OBD {
         let response = if self.replay_requests {
             self.get_recorded_response(&request)
         } else {
-            self.get_pid_response().unwrap_or(Response::no_data())
+            self.get_pid_response().unwrap_or_else(|err| {
+                self.pid_error = Some(PidError::from(&err));
+                Response::no_data()
+            })
         };
 
+        if self.pid_error.is_none() {
+            self.pid_error = PidError::from_response(&request.as_string(), &response);
+        }
+        self.last_messages = OBD::parse_ecu_messages(&response.raw_response().unwrap_or_default());
+
+        // errors aren't kept, the next request may succeed
+        if self.cache_responses && self.pid_error.is_none() {
+            self.response_cache
+                .insert(self.header.as_deref(), &key, &response);
+        }
+
         if self.record_requests {
             self.save_request(&request, &response);
         }
@@ -853,47 +1127,76 @@ impl OBD {
         response
     }
 
-    pub fn set_unit_preferences(&mut self, preferences: UnitPreferences) {
-        self.unit_preferences = preferences;
+    /// Like `query`, but fails with why no usable reply came back
+    /// (e.g the pid is unsupported or the ECU had no data).
+    pub fn try_query(&mut self, request: Command) -> Result<Response, PidError> {
+        let response = self.query(request);
+        match &self.pid_error {
+            Some(err) => Err(err.clone()),
+            None => Ok(response),
+        }
     }
 
-    pub fn get_protocol_name(&mut self) -> Result<String, Error> {
-        let mut request = Command::new_at(b"AT DP");
-        self.send_command(&mut request)?;
-
-        let response = if self.replay_requests {
-            self.get_recorded_response(&request)
-        } else {
-            self.get_at_response().unwrap_or(Response::no_data())
+    /// Data bytes of a service 01 pid, after the pid number.
+    ///
+    /// Used for pids longer than the five bytes `Response` exposes (e.g 0178).
+    /// Reads from the freeze frame when one is selected. Empty if no ECU replied.
+    pub(crate) fn query_pid_bytes(&mut self, pid: &[u8; 4]) -> Vec<u8> {
+        let number = std::str::from_utf8(&pid[2..])
+            .ok()
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+            .unwrap_or_default();
+
+        // 41 <pid> data..., or 42 <pid> <frame> data...
+        let (request, reply, header) = match self.freeze_frame {
+            Some(frame) => (
+                Command::new_arb(&format!("02{number:02X}{frame:02X}")),
+                0x42,
+                3,
+            ),
+            None => (Command::new_pid(pid), 0x41, 2),
         };
 
-        if self.record_requests {
-            self.save_request(&request, &response);
+        let messages = self.query_messages(request);
+        if let Some(message) = messages.iter().find(|message| {
+            message.data.len() > header && message.data[0] == reply && message.data[1] == number
+        }) {
+            return message.data[header..].to_vec();
         }
 
-        response.formatted_response.ok_or(Error::InvalidResponse)
+        // 7F <service> <code>
+        let rejection = messages
+            .iter()
+            .find_map(|message| match message.data.as_slice() {
+                [0x7F, _, code, ..] => Some(*code),
+                _ => None,
+            });
+        self.pid_error = Some(rejection.map_or(PidError::NoData, PidError::from_negative_response));
+
+        Vec::new()
+    }
+
+    pub fn set_unit_preferences(&mut self, preferences: UnitPreferences) {
+        self.unit_preferences = preferences;
+    }
+
+    pub fn get_protocol_name(&mut self) -> Result<String, Error> {
+        self.query_at(Command::new_at(b"AT DP"))?
+            .formatted_response
+            .ok_or(Error::InvalidResponse)
     }
 
     pub fn get_protocol_number(&self) -> u8 {
         self.protocol
     }
 
-    /// Test and run Mode 22 pids from
-    /// /data/model-pids.sqlite
-    pub fn test_mode_22_pids(&mut self, vin: &VIN) {
-        // This does not work when replaying requests
-        if self.replay_requests {
-            return;
-        }
-
-        // Get a Mode 22 pids for the model from the vin
-        // Run them all, see if the output is valid.
-        // Calculate equation and display
-        // Sleep for a short period of time.
-        // Repeat
+    /// Mode 22 pids from /data/model-pids.sqlite
+    /// for the engine manufacturer of `vin`.
+    pub fn get_mode_22_pids(vin: &VIN) -> Vec<Mode22Pid> {
+        let mut pids = Vec::new();
         let model = match vin.get_engine_manufacturer() {
             Ok(em) => em,
-            Err(_) => return,
+            Err(_) => return pids,
         };
 
         // connect to mode 22 database
@@ -901,7 +1204,7 @@ impl OBD {
             Ok(con) => con,
             Err(err) => {
                 println!("when connecting to mode22 database: {err}");
-                return;
+                return pids;
             }
         };
 
@@ -910,7 +1213,7 @@ impl OBD {
             Ok(statement) => statement,
             Err(err) => {
                 println!("when sanitizing statement {query}: {err}");
-                return;
+                return pids;
             }
         };
 
@@ -918,44 +1221,75 @@ impl OBD {
             Ok(_) => {}
             Err(err) => {
                 println!("when binding model '{}' to query {query}: {err}", model);
-                return;
+                return pids;
             }
         };
 
         while let Ok(State::Row) = statement.next() {
-            let pid = statement
-                .read::<String, _>("pid")
-                .expect("reading column pid");
-            let equation = statement
-                .read::<String, _>("equation")
-                .expect("reading column equation");
-            let unit = statement
-                .read::<String, _>("unit")
-                .expect("reading column unit");
-            let description = statement
-                .read::<String, _>("description")
-                .expect("reading description");
-
-            let command = Command::new_arb(&pid);
-            self.query(command).map_no_data(|response| {
-                match self.calculate_dynamic_equation(&equation, &unit, &response) {
-                    Ok(value) => {
-                        println!("successfully calculated pid {pid}. equation: {equation}. unit {unit}");
-                        println!("description: {description}");
-                        println!("calculated: {value}");
-                        value
-                    },
-                    Err(err) => {
-                        println!("error trying to calculate pid {pid}. equation: {equation}. unit {unit}");
-                        println!("description: {description}");
-                        println!("error: {err}");
-                        Scalar::no_data()
-                    }
-                }
+            pids.push(Mode22Pid {
+                pid: statement
+                    .read::<String, _>("pid")
+                    .expect("reading column pid"),
+                equation: statement
+                    .read::<String, _>("equation")
+                    .expect("reading column equation"),
+                unit: statement
+                    .read::<String, _>("unit")
+                    .expect("reading column unit"),
+                description: statement
+                    .read::<String, _>("description")
+                    .expect("reading description"),
             });
+        }
+
+        pids
+    }
+
+    /// Request a Mode 22 pid and calculate its value.
+    /// No data if the vehicle didn't answer or the equation failed.
+    pub fn read_mode_22_pid(&mut self, pid: &Mode22Pid) -> Scalar {
+        let command = Command::new_arb(&pid.pid);
+        self.query(command).map_no_data(|response| {
+            match self.calculate_dynamic_equation(&pid.equation, &pid.unit, &response) {
+                Ok(value) => value,
+                Err(err) => {
+                    println!(
+                        "error trying to calculate pid {}. equation: {}. unit {}. error: {err}",
+                        pid.pid, pid.equation, pid.unit
+                    );
+                    Scalar::no_data()
+                }
+            }
+        })
+    }
+
+    /// Test and run Mode 22 pids from
+    /// /data/model-pids.sqlite
+    ///
+    /// Returns the pids the vehicle answered and could be calculated.
+    pub fn test_mode_22_pids(&mut self, vin: &VIN) -> Vec<String> {
+        let mut working = Vec::new();
+
+        // This does not work when replaying requests
+        if self.replay_requests {
+            return working;
+        }
+
+        // Run them all, see if the output is valid.
+        // Sleep for a short period of time.
+        // Repeat
+        for pid in OBD::get_mode_22_pids(vin) {
+            let value = self.read_mode_22_pid(&pid);
+            println!("pid {}: {}. calculated: {value}", pid.pid, pid.description);
+
+            if value.unit != Unit::NoData {
+                working.push(pid.pid);
+            }
 
             sleep(Duration::from_millis(500));
         }
+
+        working
     }
 
     pub fn calculate_dynamic_equation(
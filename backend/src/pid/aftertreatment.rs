@@ -0,0 +1,362 @@
+// Diesel exhaust aftertreatment.
+//
+// Gathers the particulate filter, diesel exhaust fluid and SCR data reported
+// through service 01 into one place, and gives a summary of its health.
+// Soot mass and distance since the last regeneration aren't part of SAE J1979,
+// so only the standard regeneration data (018B) is used.
+
+use std::fmt;
+
+use serde::Serialize;
+
+use crate::{
+    exhaust::ScrInducement,
+    scalar::{Scalar, Unit},
+    BankNumber, Command, OBD,
+};
+
+/// Particulate filter regeneration, reported by 018B.
+#[derive(Debug, Clone, Serialize)]
+pub struct DpfRegeneration {
+    pub active: bool,
+
+    /// Active (fuel injected to heat the filter) rather than passive
+    pub forced: bool,
+
+    /// How close the filter is to needing a regeneration,
+    /// 100% triggers one. Usually follows the soot load.
+    pub trigger: Scalar,
+    pub average_time_between: Scalar,
+    pub average_distance_between: Scalar,
+}
+
+/// Diesel exhaust fluid (urea), reported by 019B.
+#[derive(Debug, Clone, Serialize)]
+pub struct DieselExhaustFluid {
+    /// Urea concentration, 32.5% when within spec
+    pub concentration: Scalar,
+    pub tank_temp: Scalar,
+    pub tank_level: Scalar,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AftertreatmentData {
+    pub dpf_delta_pressure: Scalar,
+    pub dpf_inlet_pressure: Scalar,
+    pub dpf_outlet_pressure: Scalar,
+    pub dpf_inlet_temp: Scalar,
+    pub dpf_outlet_temp: Scalar,
+    pub regeneration: DpfRegeneration,
+
+    pub def: DieselExhaustFluid,
+
+    /// NOx before and after the SCR catalyst
+    pub nox_upstream: Scalar,
+    pub nox_downstream: Scalar,
+
+    /// Share of NOx removed by the SCR catalyst
+    pub scr_efficiency: Scalar,
+
+    /// `None` when the vehicle doesn't report it
+    pub inducement: Option<ScrInducement>,
+}
+
+/// Ordered from best to worst.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub enum HealthLevel {
+    Good,
+    Attention,
+    Critical,
+}
+
+impl HealthLevel {
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Good => "good",
+            Self::Attention => "needs attention",
+            Self::Critical => "critical",
+        }
+    }
+}
+
+impl fmt::Display for HealthLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AftertreatmentHealth {
+    pub level: HealthLevel,
+    pub findings: Vec<(HealthLevel, String)>,
+}
+
+impl AftertreatmentHealth {
+    fn add(&mut self, level: HealthLevel, finding: String) {
+        self.level = self.level.max(level);
+        self.findings.push((level, finding));
+    }
+}
+
+impl AftertreatmentData {
+    /// Summarize the health of the aftertreatment system.
+    /// Values the vehicle doesn't report are skipped.
+    pub fn assess(&self) -> AftertreatmentHealth {
+        let mut health = AftertreatmentHealth {
+            level: HealthLevel::Good,
+            findings: Vec::new(),
+        };
+
+        if let Some(trigger) = self.regeneration.trigger.value_in(Unit::Percent) {
+            if trigger >= 100.0 && !self.regeneration.active {
+                health.add(
+                    HealthLevel::Critical,
+                    format!("Particulate filter regeneration is overdue ({trigger:.0}%)."),
+                );
+            } else if trigger >= 80.0 {
+                health.add(
+                    HealthLevel::Attention,
+                    format!("Particulate filter is nearly due for regeneration ({trigger:.0}%)."),
+                );
+            }
+        }
+
+        if self.regeneration.active {
+            health.add(
+                HealthLevel::Good,
+                "Particulate filter is regenerating, avoid switching the engine off.".to_string(),
+            );
+        }
+
+        if let Some(delta) = self.dpf_delta_pressure.value_in(Unit::KiloPascal) {
+            if delta > 20.0 {
+                health.add(
+                    HealthLevel::Attention,
+                    format!("High pressure across the particulate filter ({delta:.1} kPa), it may be clogged."),
+                );
+            }
+        }
+
+        if let Some(temp) = self.dpf_outlet_temp.value_in(Unit::Celsius) {
+            if temp > 750.0 {
+                health.add(
+                    HealthLevel::Critical,
+                    format!("Particulate filter outlet temperature is excessive ({temp:.0} °C)."),
+                );
+            }
+        }
+
+        if let Some(level) = self.def.tank_level.value_in(Unit::Percent) {
+            if level < 10.0 {
+                health.add(
+                    HealthLevel::Critical,
+                    format!("Diesel exhaust fluid is almost empty ({level:.0}%)."),
+                );
+            } else if level < 20.0 {
+                health.add(
+                    HealthLevel::Attention,
+                    format!("Diesel exhaust fluid is low ({level:.0}%)."),
+                );
+            }
+        }
+
+        if let Some(concentration) = self.def.concentration.value_in(Unit::Percent) {
+            if !(30.0..=35.0).contains(&concentration) {
+                health.add(
+                    HealthLevel::Attention,
+                    format!("Diesel exhaust fluid quality is out of spec ({concentration:.1}% urea, expected 32.5%)."),
+                );
+            }
+        }
+
+        // efficiency means little when there's almost no NOx to remove
+        let upstream = self
+            .nox_upstream
+            .value_in(Unit::PartsPerMillion)
+            .unwrap_or_default();
+        if let Some(efficiency) = self.scr_efficiency.value_in(Unit::Percent) {
+            if upstream > 50.0 && efficiency < 50.0 {
+                health.add(
+                    HealthLevel::Attention,
+                    format!("SCR catalyst is only removing {efficiency:.0}% of NOx."),
+                );
+            }
+        }
+
+        if let Some(inducement) = &self.inducement {
+            if inducement.active {
+                health.add(
+                    HealthLevel::Critical,
+                    format!(
+                        "SCR inducement is active, engine power may be limited ({}).",
+                        inducement.reasons.join(", ")
+                    ),
+                );
+            } else if !inducement.reasons.is_empty() {
+                health.add(
+                    HealthLevel::Attention,
+                    format!(
+                        "SCR inducement warning ({}).",
+                        inducement.reasons.join(", ")
+                    ),
+                );
+            }
+        }
+
+        health
+    }
+}
+
+impl OBD {
+    pub fn dpf_regeneration_status(&mut self) -> DpfRegeneration {
+        let bytes = self.query_pid_bytes(b"018B");
+        let supported = bytes.first().copied().unwrap_or_default();
+        let status = bytes.get(1).copied().unwrap_or_default();
+
+        // Bits 0-3 of A flag support for the status bits in B,
+        // bits 4-6 flag support for C, D-E and F-G.
+        let trigger = match bytes.get(2) {
+            Some(trigger) if supported & 0x10 != 0 => Scalar::new(
+                (100.0 / 255.0) * *trigger as f32,
+                Unit::Percent,
+                Some(self.unit_preferences),
+            ),
+            _ => Scalar::no_data(),
+        };
+
+        let average_time_between = match bytes.get(3..5) {
+            Some(time) if supported & 0x20 != 0 => Scalar::new(
+                u16::from_be_bytes([time[0], time[1]]) as f32,
+                Unit::Minutes,
+                Some(self.unit_preferences),
+            ),
+            _ => Scalar::no_data(),
+        };
+
+        let average_distance_between = match bytes.get(5..7) {
+            Some(distance) if supported & 0x40 != 0 => Scalar::new(
+                u16::from_be_bytes([distance[0], distance[1]]) as f32,
+                Unit::Kilometers,
+                Some(self.unit_preferences),
+            ),
+            _ => Scalar::no_data(),
+        };
+
+        DpfRegeneration {
+            active: supported & 0x01 != 0 && status & 0x01 != 0,
+            forced: supported & 0x02 != 0 && status & 0x02 != 0,
+            trigger,
+            average_time_between,
+            average_distance_between,
+        }
+    }
+
+    pub fn diesel_exhaust_fluid(&mut self) -> DieselExhaustFluid {
+        let response = self.query(Command::new_pid(b"019B"));
+        if *response.get_payload_size() == 0 {
+            return DieselExhaustFluid {
+                concentration: Scalar::no_data(),
+                tank_temp: Scalar::no_data(),
+                tank_level: Scalar::no_data(),
+            };
+        }
+
+        let supported = response.a_value() as u8;
+        let value = |bit: u8, scalar: Scalar| {
+            if supported & bit != 0 {
+                scalar
+            } else {
+                Scalar::no_data()
+            }
+        };
+
+        DieselExhaustFluid {
+            concentration: value(
+                0b0010,
+                Scalar::new(
+                    response.b_value() * 0.25,
+                    Unit::Percent,
+                    Some(self.unit_preferences),
+                ),
+            ),
+            tank_temp: value(
+                0b0100,
+                Scalar::new(
+                    response.c_value() - 40.0,
+                    Unit::Celsius,
+                    Some(self.unit_preferences),
+                ),
+            ),
+            tank_level: value(
+                0b1000,
+                Scalar::new(
+                    (100.0 / 255.0) * response.d_value(),
+                    Unit::Percent,
+                    Some(self.unit_preferences),
+                ),
+            ),
+        }
+    }
+
+    /// Whether the vehicle has a second aftertreatment bank.
+    /// Going by 017B, or the bank 2 sensors of 017C and 0183.
+    pub fn has_second_aftertreatment_bank(&mut self) -> bool {
+        if self.is_supported(0x01, 0x7B) {
+            return true;
+        }
+
+        // bits 2 and 3 flag support for the bank 2 sensors
+        [b"017C", b"0183"].into_iter().any(|pid| {
+            self.query_pid_bytes(pid)
+                .first()
+                .is_some_and(|supported| supported & 0b1100 != 0)
+        })
+    }
+
+    /// Everything about the aftertreatment system of `bank`.
+    pub fn get_aftertreatment_data(&mut self, bank: BankNumber) -> AftertreatmentData {
+        let (nox_upstream, nox_downstream) = {
+            let [b1s1, b1s2, b2s1, b2s2] = self.nox_sensors();
+            match bank {
+                BankNumber::Bank1 => (b1s1, b1s2),
+                BankNumber::Bank2 => (b2s1, b2s2),
+            }
+        };
+
+        let scr_efficiency = if nox_upstream.unit == Unit::NoData
+            || nox_downstream.unit == Unit::NoData
+            || nox_upstream.value <= 0.0
+        {
+            Scalar::no_data()
+        } else {
+            Scalar::new(
+                (1.0 - (nox_downstream.value / nox_upstream.value)) * 100.0,
+                Unit::Percent,
+                Some(self.unit_preferences),
+            )
+        };
+
+        // older vehicles only report the tank level through 0185
+        let mut def = self.diesel_exhaust_fluid();
+        if def.tank_level.unit == Unit::NoData {
+            def.tank_level = self.nox_reagent_system().tank_level;
+        }
+
+        let (inlet_temp, outlet_temp) = self.dpf_temp(bank);
+        let (delta, inlet, outlet) = self.dpf_pressure(bank);
+
+        AftertreatmentData {
+            dpf_delta_pressure: delta,
+            dpf_inlet_pressure: inlet,
+            dpf_outlet_pressure: outlet,
+            dpf_inlet_temp: inlet_temp,
+            dpf_outlet_temp: outlet_temp,
+            regeneration: self.dpf_regeneration_status(),
+            def,
+            nox_upstream,
+            nox_downstream,
+            scr_efficiency,
+            inducement: self.scr_inducement_status(),
+        }
+    }
+}
@@ -1,4 +1,6 @@
 use crate::{
+    oxygen::OxygenSensorScaling,
+    registry::PidError,
     scalar::{Scalar, Unit},
     Command, SensorNumber, OBD,
 };
@@ -27,7 +29,11 @@ impl OBD {
     }
 
     pub fn intake_air_temp(&mut self) -> Scalar {
-        self.query(Command::new_pid(b"010F")).map_no_data(|r| {
+        self.try_intake_air_temp().unwrap_or(Scalar::no_data())
+    }
+
+    pub fn try_intake_air_temp(&mut self) -> Result<Scalar, PidError> {
+        self.try_query(Command::new_pid(b"010F")).map(|r| {
             Scalar::new(
                 r.a_value() - 40.0,
                 Unit::Celsius,
@@ -37,23 +43,36 @@ impl OBD {
     }
 
     pub fn intake_manifold_abs_pressure(&mut self) -> Scalar {
-        self.query(Command::new_pid(b"010B")).map_no_data(|r| {
-            Scalar::new(r.a_value(), Unit::KiloPascal, Some(self.unit_preferences))
-        })
+        self.try_intake_manifold_abs_pressure()
+            .unwrap_or(Scalar::no_data())
+    }
+
+    pub fn try_intake_manifold_abs_pressure(&mut self) -> Result<Scalar, PidError> {
+        self.try_query(Command::new_pid(b"010B"))
+            .map(|r| Scalar::new(r.a_value(), Unit::KiloPascal, Some(self.unit_preferences)))
     }
 
+    // Mass airflow sensor
     pub fn maf_air_flow_rate(&mut self) -> Scalar {
-        self.query(Command::new_pid(b"0110")).map_no_data(|r| {
+        self.try_maf_air_flow_rate().unwrap_or(Scalar::no_data())
+    }
+
+    pub fn try_maf_air_flow_rate(&mut self) -> Result<Scalar, PidError> {
+        self.try_query(Command::new_pid(b"0110")).map(|r| {
             Scalar::new(
                 ((r.a_value() * 256.0) + r.b_value()) / 100.0,
                 Unit::GramsPerSecond,
                 Some(self.unit_preferences),
             )
         })
-    } // Mass airflow sensor
+    }
 
     pub fn ambient_air_temp(&mut self) -> Scalar {
-        self.query(Command::new_pid(b"0146")).map_no_data(|r| {
+        self.try_ambient_air_temp().unwrap_or(Scalar::no_data())
+    }
+
+    pub fn try_ambient_air_temp(&mut self) -> Result<Scalar, PidError> {
+        self.try_query(Command::new_pid(b"0146")).map(|r| {
             Scalar::new(
                 r.a_value() - 40.0,
                 Unit::Celsius,
@@ -63,7 +82,12 @@ impl OBD {
     }
 
     pub fn max_air_flow_rate_from_maf(&mut self) -> Scalar {
-        self.query(Command::new_pid(b"0150")).map_no_data(|r| {
+        self.try_max_air_flow_rate_from_maf()
+            .unwrap_or(Scalar::no_data())
+    }
+
+    pub fn try_max_air_flow_rate_from_maf(&mut self) -> Result<Scalar, PidError> {
+        self.try_query(Command::new_pid(b"0150")).map(|r| {
             Scalar::new(
                 r.a_value() * 10.0,
                 Unit::GramsPerSecond,
@@ -76,6 +100,14 @@ impl OBD {
     // Voltage being given to the sensor
     // Short term fuel trim
     pub fn read_oxygen_sensor(&mut self, sensor: &SensorNumber) -> (Scalar, Scalar) {
+        self.try_read_oxygen_sensor(sensor)
+            .unwrap_or((Scalar::no_data(), Scalar::no_data()))
+    }
+
+    pub fn try_read_oxygen_sensor(
+        &mut self,
+        sensor: &SensorNumber,
+    ) -> Result<(Scalar, Scalar), PidError> {
         let command = match sensor {
             SensorNumber::Sensor1 => Command::new_pid(b"0114"),
             SensorNumber::Sensor2 => Command::new_pid(b"0115"),
@@ -87,12 +119,9 @@ impl OBD {
             SensorNumber::Sensor8 => Command::new_pid(b"011B"),
         };
 
-        let response = self.query(command);
-        if *response.get_payload_size() == 0 {
-            return (Scalar::no_data(), Scalar::no_data());
-        }
+        let response = self.try_query(command)?;
 
-        (
+        Ok((
             Scalar::new(
                 response.a_value() / 200.0,
                 Unit::Volts,
@@ -103,7 +132,7 @@ impl OBD {
                 Unit::Percent,
                 Some(self.unit_preferences),
             ),
-        )
+        ))
     }
 
     // Returns 2 values
@@ -114,8 +143,21 @@ impl OBD {
     // CD (CD Bytes) - Voltage
     //
     // Unlike read_oxygen_sensor, this doesn't return the
-    // short term fuel trim.
-    pub fn read_oxygen_sensor_abcd(&mut self, sensor: &SensorNumber) -> (Scalar, Scalar) {
+    // short term fuel trim. Both values are scaled by `scaling` (014F).
+    pub fn read_oxygen_sensor_abcd(
+        &mut self,
+        sensor: &SensorNumber,
+        scaling: &OxygenSensorScaling,
+    ) -> (Scalar, Scalar) {
+        self.try_read_oxygen_sensor_abcd(sensor, scaling)
+            .unwrap_or((Scalar::no_data(), Scalar::no_data()))
+    }
+
+    pub fn try_read_oxygen_sensor_abcd(
+        &mut self,
+        sensor: &SensorNumber,
+        scaling: &OxygenSensorScaling,
+    ) -> Result<(Scalar, Scalar), PidError> {
         let command = match sensor {
             SensorNumber::Sensor1 => Command::new_pid(b"0124"),
             SensorNumber::Sensor2 => Command::new_pid(b"0125"),
@@ -127,17 +169,67 @@ impl OBD {
             SensorNumber::Sensor8 => Command::new_pid(b"012B"),
         };
 
-        let response = self.query(command);
-        if *response.get_payload_size() == 0 {
-            return (Scalar::no_data(), Scalar::no_data());
-        }
+        let response = self.try_query(command)?;
 
-        let ratio = (2.0 / 65536.0) * ((256.0 * response.a_value()) + response.b_value());
-        let voltage = (8.0 / 65536.0) * ((256.0 * response.c_value()) + response.d_value());
-        (
+        let ratio = scaling.ratio(u16::from_be_bytes([
+            response.a_value() as u8,
+            response.b_value() as u8,
+        ]));
+        let voltage = scaling.voltage(u16::from_be_bytes([
+            response.c_value() as u8,
+            response.d_value() as u8,
+        ]));
+        Ok((
             Scalar::new(ratio, Unit::Ratio, Some(self.unit_preferences)),
             Scalar::new(voltage, Unit::Volts, Some(self.unit_preferences)),
-        )
+        ))
+    }
+
+    // Returns 2 values
+    // AB (AB Bytes) - Air-Fuel equivalance ratio
+    // CD (CD Bytes) - Current, negative when lean
+    //
+    // Same sensors as read_oxygen_sensor_abcd, for wideband
+    // sensors that report a pumping current instead of a voltage.
+    pub fn read_oxygen_sensor_current(
+        &mut self,
+        sensor: &SensorNumber,
+        scaling: &OxygenSensorScaling,
+    ) -> (Scalar, Scalar) {
+        self.try_read_oxygen_sensor_current(sensor, scaling)
+            .unwrap_or((Scalar::no_data(), Scalar::no_data()))
+    }
+
+    pub fn try_read_oxygen_sensor_current(
+        &mut self,
+        sensor: &SensorNumber,
+        scaling: &OxygenSensorScaling,
+    ) -> Result<(Scalar, Scalar), PidError> {
+        let command = match sensor {
+            SensorNumber::Sensor1 => Command::new_pid(b"0134"),
+            SensorNumber::Sensor2 => Command::new_pid(b"0135"),
+            SensorNumber::Sensor3 => Command::new_pid(b"0136"),
+            SensorNumber::Sensor4 => Command::new_pid(b"0137"),
+            SensorNumber::Sensor5 => Command::new_pid(b"0138"),
+            SensorNumber::Sensor6 => Command::new_pid(b"0139"),
+            SensorNumber::Sensor7 => Command::new_pid(b"013A"),
+            SensorNumber::Sensor8 => Command::new_pid(b"013B"),
+        };
+
+        let response = self.try_query(command)?;
+
+        let ratio = scaling.ratio(u16::from_be_bytes([
+            response.a_value() as u8,
+            response.b_value() as u8,
+        ]));
+        let current = scaling.current(u16::from_be_bytes([
+            response.c_value() as u8,
+            response.d_value() as u8,
+        ]));
+        Ok((
+            Scalar::new(ratio, Unit::Ratio, Some(self.unit_preferences)),
+            Scalar::new(current, Unit::Milliampere, Some(self.unit_preferences)),
+        ))
     }
 
     // Read from sensor a and b
@@ -171,8 +263,19 @@ impl OBD {
     }
 
     pub fn abs_barometric_pressure(&mut self) -> Scalar {
-        self.query(Command::new_pid(b"0133")).map_no_data(|r| {
-            Scalar::new(r.a_value(), Unit::KiloPascal, Some(self.unit_preferences))
-        })
+        self.try_abs_barometric_pressure()
+            .unwrap_or(Scalar::no_data())
+    }
+
+    pub fn try_abs_barometric_pressure(&mut self) -> Result<Scalar, PidError> {
+        self.try_query(Command::new_pid(b"0133"))
+            .map(|r| Scalar::new(r.a_value(), Unit::KiloPascal, Some(self.unit_preferences)))
+    }
+
+    /// Intake manifold absolute pressure of sensor A and sensor B (0187).
+    pub fn intake_manifold_abs_pressure_sensors(&mut self) -> (Scalar, Scalar) {
+        let bytes = self.query_pid_bytes(b"0187");
+        let [a, b] = self.decode_sensor_words(&bytes, |raw| (raw / 32.0, Unit::KiloPascal));
+        (a, b)
     }
 }
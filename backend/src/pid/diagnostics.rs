@@ -1,11 +1,13 @@
 use serde::{Deserialize, Serialize};
-use sqlite::State;
 use std::fmt;
 
 use crate::{
+    catalog::{DescriptionSource, DtcCatalog, DtcInfo, VehicleContext},
     engine::EngineType,
+    registry::PidError,
     scalar::{Scalar, Unit},
-    Command, Error, CODE_DESC_DB_PATH, OBD,
+    uds::{DtcSeverity, FailureType, UdsDtcStatus},
+    Command, EcuMessage, Error, OBD,
 };
 
 #[derive(Debug)]
@@ -85,7 +87,7 @@ impl fmt::Display for AuxiliaryInputStatus {
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub enum TroubleCodeCategory {
     Powertrain,
     Chassis,
@@ -129,72 +131,191 @@ impl fmt::Display for TroubleCodeCategory {
     }
 }
 
-#[derive(Debug, Default, Clone)]
+/// Which states a DTC was reported in.
+///
+/// A code can be in more than one state at once,
+/// e.g a confirmed code that turned on the MIL is usually also permanant.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TroubleCodeStatus {
+    /// Confirmed (stored) code, reported by service 03
+    pub confirmed: bool,
+
+    /// Pending code that has not matured yet, reported by service 07
+    pub pending: bool,
+
+    /// Permanant code that can't be cleared with service 04, reported by service 0A
+    pub permanant: bool,
+
+    /// ISO 14229 status byte when the code was read over UDS
+    pub uds: Option<UdsDtcStatus>,
+}
+
+impl TroubleCodeStatus {
+    pub fn confirmed() -> Self {
+        Self {
+            confirmed: true,
+            ..Default::default()
+        }
+    }
+
+    pub fn pending() -> Self {
+        Self {
+            pending: true,
+            ..Default::default()
+        }
+    }
+
+    pub fn permanant() -> Self {
+        Self {
+            permanant: true,
+            ..Default::default()
+        }
+    }
+
+    /// Combine the states of the same code reported by different services.
+    pub fn merge(&mut self, other: &TroubleCodeStatus) {
+        self.confirmed |= other.confirmed;
+        self.pending |= other.pending;
+        self.permanant |= other.permanant;
+        self.uds = self.uds.or(other.uds);
+    }
+}
+
+impl fmt::Display for TroubleCodeStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut states = Vec::new();
+        if self.confirmed {
+            states.push("confirmed");
+        }
+        if self.pending {
+            states.push("pending");
+        }
+        if self.permanant {
+            states.push("permanant");
+        }
+        if states.is_empty() {
+            states.push("unknown");
+        }
+
+        write!(f, "{}", states.join(", "))?;
+        if let Some(uds) = self.uds {
+            write!(f, " (UDS status {uds})")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct TroubleCode {
     pub category: TroubleCodeCategory,
     pub dtc: String,
     pub description: String,
+    pub description_source: DescriptionSource,
+    pub status: TroubleCodeStatus,
 
-    /// Is this a permanant OBD code or a pending one ?
-    pub permanant: bool,
+    /// Address of the ECU that reported this code (e.g "7E8"), if known
+    pub ecu: Option<String>,
+
+    /// Third byte of a UDS / J1979-2 DTC
+    pub failure_type: Option<FailureType>,
+    pub severity: Option<DtcSeverity>,
+    pub functional_unit: Option<u8>,
 }
 
 impl TroubleCode {
-    pub fn new(category: TroubleCodeCategory, dtc: String, permanant: bool) -> Self {
+    pub fn new(category: TroubleCodeCategory, dtc: String, status: TroubleCodeStatus) -> Self {
         let mut code = Self {
             category,
             dtc,
             description: String::default(),
-            permanant,
+            description_source: DescriptionSource::None,
+            status,
+            ecu: None,
+            failure_type: None,
+            severity: None,
+            functional_unit: None,
         };
 
-        code.set_description();
+        code.set_description(None);
         code
     }
 
-    pub fn set_description(&mut self) {
-        self.description = "none".to_string(); // default
-
-        // connect to trouble code data base
-        let con = match sqlite::Connection::open(CODE_DESC_DB_PATH) {
-            Ok(con) => con,
-            Err(err) => {
-                println!("when connecting to codes database: {err}");
-                return;
-            }
+    /// Decode a DTC from the two bytes an ECU reports it as.
+    ///
+    /// The first two bits of `high` are the system letter (P, C, B, U),
+    /// the next two the first digit and the rest the remaining three digits.
+    pub fn from_bytes(high: u8, low: u8, status: TroubleCodeStatus) -> Self {
+        let category = match high >> 6 {
+            0 => TroubleCodeCategory::Powertrain,
+            1 => TroubleCodeCategory::Chassis,
+            2 => TroubleCodeCategory::Body,
+            _ => TroubleCodeCategory::Network,
         };
 
-        // query description for 'dtc'
-        let query = "SELECT desc FROM codes WHERE id = ?";
-        let mut statement = match con.prepare(query) {
-            Ok(statement) => statement,
-            Err(err) => {
-                println!("when sanitizing statement {query}: {err}");
-                return;
-            }
-        };
+        let dtc = format!(
+            "{}{:01X}{:01X}{:02X}",
+            category.system_letter(),
+            (high >> 4) & 0b11,
+            high & 0b1111,
+            low
+        );
 
-        match statement.bind((1, self.dtc.as_str())) {
-            Ok(_) => {}
-            Err(err) => {
-                println!("when binding dtc '{}' to query {query}: {err}", self.dtc);
-                return;
-            }
-        };
+        Self::new(category, dtc, status)
+    }
 
-        if let Ok(State::Row) = statement.next() {
-            self.description = statement.read::<String, _>("desc").unwrap_or_default();
+    /// Decode a 3-byte UDS DTC. The last byte is the failure type.
+    pub fn from_uds_bytes(bytes: [u8; 3], status: UdsDtcStatus) -> Self {
+        let mut code = Self::from_bytes(bytes[0], bytes[1], TroubleCodeStatus::from_uds(status));
+        code.failure_type = Some(FailureType(bytes[2]));
+        code
+    }
+
+    /// The code as displayed to the user.
+    /// Codes read over UDS include the failure type (e.g "P0420-13").
+    pub fn code(&self) -> String {
+        match self.failure_type {
+            Some(failure_type) => format!("{}-{:02X}", self.dtc, failure_type.0),
+            None => self.dtc.clone(),
         }
     }
+
+    /// Look up the description of this code.
+    ///
+    /// With a `vehicle`, manufacturer specific descriptions for its make are preferred.
+    pub fn set_description(&mut self, vehicle: Option<&VehicleContext>) {
+        let (description, source) = DtcCatalog::global().description(&self.dtc, vehicle);
+        self.description = description.unwrap_or("none").to_string();
+        self.description_source = source;
+    }
+
+    /// Everything the DTC catalog knows about this code.
+    pub fn info(&self, vehicle: Option<&VehicleContext>) -> DtcInfo {
+        DtcCatalog::global().lookup(&self.dtc, vehicle)
+    }
 }
 
 impl fmt::Display for TroubleCode {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        writeln!(f, "Showing details for trouble code {}:", self.dtc)?;
-        writeln!(f, "Trouble code: {}", self.dtc)?;
+        writeln!(f, "Showing details for trouble code {}:", self.code())?;
+        writeln!(f, "Trouble code: {}", self.code())?;
         writeln!(f, "System letter: {}", self.category.system_letter())?;
         writeln!(f, "Location: {}", self.category)?;
-        writeln!(f, "Description: {}", self.description)?;
+        writeln!(f, "Status: {}", self.status)?;
+        if let Some(ecu) = &self.ecu {
+            writeln!(f, "Reported by: {ecu}")?;
+        }
+        if let Some(failure_type) = self.failure_type {
+            writeln!(f, "Failure type: {failure_type}")?;
+        }
+        if let Some(severity) = self.severity {
+            writeln!(f, "Severity: {severity}")?;
+        }
+        writeln!(
+            f,
+            "Description: {} (source: {})",
+            self.description,
+            self.description_source.as_str()
+        )?;
         write!(f, "Overview: {} is an issue related to {}. This code is from the {} component of the vehicle.", self.dtc, self.description, self.category)
     }
 }
@@ -237,33 +358,118 @@ impl Test {
 }
 
 impl OBD {
-    /// Get the DTC that caused the freeze frame.
-    pub fn get_freeze_frame_dtc(&mut self) -> Vec<TroubleCode> {
-        if let Err(err) = self.send_command(&mut Command::new_pid(b"0102")) {
-            println!("when getting dtc3: {err}");
-            return Vec::new();
+    pub fn get_permanant_trouble_codes(&mut self) -> Vec<TroubleCode> {
+        let messages = self.query_messages(Command::new_svc(b"0A"));
+        let codes = OBD::decode_trouble_codes(0x4A, &messages, TroubleCodeStatus::permanant());
+        self.describe_trouble_codes(codes)
+    }
+
+    // Use the manufacturer's descriptions when the vehicle is known
+    fn describe_trouble_codes(&self, mut codes: Vec<TroubleCode>) -> Vec<TroubleCode> {
+        if let Some(vehicle) = self.get_vehicle_context() {
+            for code in codes.iter_mut() {
+                code.set_description(Some(vehicle));
+            }
+        }
+        codes
+    }
+
+    /// Get codes that were detected during the current or last drive cycle
+    /// but haven't matured into confirmed codes yet.
+    pub fn get_pending_trouble_codes(&mut self) -> Vec<TroubleCode> {
+        let messages = self.query_messages(Command::new_svc(b"07"));
+        let codes = OBD::decode_trouble_codes(0x47, &messages, TroubleCodeStatus::pending());
+        self.describe_trouble_codes(codes)
+    }
+
+    /// Get confirmed, pending and permanant codes from every ECU.
+    ///
+    /// A code reported by more than one service is only listed once per ECU,
+    /// with the states from each service combined.
+    /// On CAN, ECUs that answer UDS also report each code's failure type and severity.
+    pub fn get_all_trouble_codes(&mut self) -> Vec<TroubleCode> {
+        self.read_all_trouble_codes().unwrap_or_default()
+    }
+
+    /// Same as `get_all_trouble_codes`, but fails if no ECU answered.
+    ///
+    /// An ECU without codes still answers (e.g "43 00"), so an empty list
+    /// means there are no codes rather than that the read timed out.
+    pub fn read_all_trouble_codes(&mut self) -> Result<Vec<TroubleCode>, PidError> {
+        let confirmed = self.get_trouble_codes();
+        let answered = self
+            .last_messages
+            .iter()
+            .any(|message| message.data.first() == Some(&0x43));
+        if !answered {
+            return Err(self.pid_error.clone().unwrap_or(PidError::NoData));
         }
 
-        let mut raw_response = self.read_iso_tp_response().join("");
-        OBD::decode_trouble_codes(&mut raw_response)
+        let reported = [
+            confirmed,
+            self.get_pending_trouble_codes(),
+            self.get_permanant_trouble_codes(),
+        ]
+        .concat();
+
+        let mut codes: Vec<TroubleCode> = Vec::new();
+        for code in reported {
+            match codes
+                .iter_mut()
+                .find(|existing| existing.dtc == code.dtc && existing.ecu == code.ecu)
+            {
+                Some(existing) => existing.status.merge(&code.status),
+                None => codes.push(code),
+            }
+        }
+
+        // The same codes read over UDS carry the failure type and severity.
+        // A UDS code is merged onto the code its ECU already reported,
+        // the same DTC with another failure type is listed separately.
+        for code in self.get_uds_trouble_codes() {
+            match codes.iter_mut().find(|existing| {
+                existing.dtc == code.dtc
+                    && existing.ecu == code.ecu
+                    && existing
+                        .failure_type
+                        .is_none_or(|failure| Some(failure) == code.failure_type)
+            }) {
+                Some(existing) => {
+                    existing.status.merge(&code.status);
+                    existing.failure_type = code.failure_type;
+                    existing.severity = existing.severity.or(code.severity);
+                    existing.functional_unit = existing.functional_unit.or(code.functional_unit);
+                }
+                None => codes.push(code),
+            }
+        }
+
+        Ok(codes)
     }
 
-    pub fn get_permanant_trouble_codes(&mut self) -> Vec<TroubleCode> {
-        if let Err(err) = self.send_command(&mut Command::new_svc(b"0A")) {
-            println!("when getting dtc6: {err}");
+    // Confirmed and pending codes from the ECUs that support UDS ReadDTCInformation (19).
+    // Only tried on CAN, ECUs that don't support it are skipped.
+    fn get_uds_trouble_codes(&mut self) -> Vec<TroubleCode> {
+        if !self.get_detected_protocol().contains("15765") {
             return Vec::new();
         }
 
-        let mut raw_response = self.read_iso_tp_response().join("");
-        OBD::decode_trouble_codes(&mut raw_response)
+        // pending or confirmed
+        let status_mask = 0x0C;
+        let codes = self
+            .uds_read_dtcs_with_severity(0xFF, status_mask)
+            .or_else(|_| self.uds_read_dtcs(status_mask))
+            .unwrap_or_default();
+        self.describe_trouble_codes(codes)
     }
 
+    /// Clear trouble codes without any checks.
+    /// See `clear_trouble_codes_safely` to keep a snapshot of what gets erased.
     pub fn clear_trouble_codes(&mut self) -> Result<(), Error> {
-        let response = self.query(Command::new_svc(b"04"));
-        let raw = response.formatted_response.unwrap_or_default();
+        let responses = self.request_clear_trouble_codes();
 
-        // positive response
-        if raw == "44" {
+        // every ECU that answered must have cleared its codes
+        if !responses.cleared.is_empty() && responses.rejected.is_empty() {
             Ok(())
         } else {
             Err(Error::DTCClearFailed)
@@ -280,8 +486,59 @@ impl OBD {
             return [Test::no_data(); 3];
         }
 
-        let byte = response.a_value() as u32;
+        OBD::decode_common_tests(response.b_value() as u32)
+    }
+
+    // These tests are engine-type specific
+    // Tests will differ if the engine type
+    // is compression vs. spark ignition
+    //
+    // For Compression engines, two tests are reserved.
+    // If a response is invalid, Tests will have the name "Unknown"
+    pub fn get_advanced_tests_status(&mut self) -> [Test; 8] {
+        let engine_type = self.get_engine_type();
+        let response = self.query(Command::new_pid(b"0101"));
+        if *response.get_payload_size() == 0 {
+            return [Test::no_data(); 8];
+        }
+
+        OBD::decode_advanced_tests(
+            engine_type,
+            response.c_value() as u32,
+            response.d_value() as u32,
+        )
+    }
+
+    // See https://en.wikipedia.org/wiki/OBD-II_PIDs#Service_01_PID_41
+    //
+    // Same tests as 0101, but for the current drive cycle
+    // instead of since codes were last cleared.
+    // Available means the test is enabled this drive cycle.
+    pub fn get_drive_cycle_common_tests_status(&mut self) -> [Test; 3] {
+        let response = self.query(Command::new_pid(b"0141"));
+        if *response.get_payload_size() == 0 {
+            return [Test::no_data(); 3];
+        }
+
+        OBD::decode_common_tests(response.b_value() as u32)
+    }
+
+    pub fn get_drive_cycle_advanced_tests_status(&mut self) -> [Test; 8] {
+        // 0141 doesn't report the engine type, only 0101 does
+        let engine_type = self.get_engine_type();
+        let response = self.query(Command::new_pid(b"0141"));
+        if *response.get_payload_size() == 0 {
+            return [Test::no_data(); 8];
+        }
+
+        OBD::decode_advanced_tests(
+            engine_type,
+            response.c_value() as u32,
+            response.d_value() as u32,
+        )
+    }
 
+    fn decode_common_tests(byte: u32) -> [Test; 3] {
         // "For bits indicating test availability a bit set to 1
         // indicates available, whilst for bits indicating test completeness
         // a bit set to 0 indicates complete. "
@@ -309,22 +566,7 @@ impl OBD {
         [components, fuel_system, misfire]
     }
 
-    // These tests are engine-type specific
-    // Tests will differ if the engine type
-    // is compression vs. spark ignition
-    //
-    // For Compression engines, two tests are reserved.
-    // If a response is invalid, Tests will have the name "Unknown"
-    pub fn get_advanced_tests_status(&mut self) -> [Test; 8] {
-        let engine_type = self.get_engine_type();
-        let response = self.query(Command::new_pid(b"0101"));
-        if *response.get_payload_size() == 0 {
-            return [Test::no_data(); 8];
-        }
-
-        let c_byte = response.c_value() as u32;
-        let d_byte = response.d_value() as u32;
-
+    fn decode_advanced_tests(engine_type: EngineType, c_byte: u32, d_byte: u32) -> [Test; 8] {
         let mut tests: [Test; 8] = [Test::no_data(); 8];
         for (index, test) in tests.iter_mut().enumerate() {
             let bit = 1 << (7 - index);
@@ -390,12 +632,22 @@ impl OBD {
     }
 
     pub fn warm_ups_since_codes_cleared(&mut self) -> Scalar {
-        self.query(Command::new_pid(b"0130"))
-            .map_no_data(|r| Scalar::new(r.a_value(), Unit::NoData, Some(self.unit_preferences)))
+        self.try_warm_ups_since_codes_cleared()
+            .unwrap_or(Scalar::no_data())
+    }
+
+    pub fn try_warm_ups_since_codes_cleared(&mut self) -> Result<Scalar, PidError> {
+        self.try_query(Command::new_pid(b"0130"))
+            .map(|r| Scalar::new(r.a_value(), Unit::NoData, Some(self.unit_preferences)))
     }
 
     pub fn distance_traveled_since_codes_cleared(&mut self) -> Scalar {
-        self.query(Command::new_pid(b"0131")).map_no_data(|r| {
+        self.try_distance_traveled_since_codes_cleared()
+            .unwrap_or(Scalar::no_data())
+    }
+
+    pub fn try_distance_traveled_since_codes_cleared(&mut self) -> Result<Scalar, PidError> {
+        self.try_query(Command::new_pid(b"0131")).map(|r| {
             Scalar::new(
                 (256.0 * r.a_value()) + r.b_value(),
                 Unit::Kilometers,
@@ -409,124 +661,52 @@ impl OBD {
         response.a_value() as u32 & 0x7F
     }
 
-    // Check on this. Might be broken when there are more than 3 DTC's
+    /// Get confirmed codes (service 03).
+    ///
+    /// The 0101 DTC count isn't used to skip the request,
+    /// as some ECUs report a count of 0 while still storing codes.
     pub fn get_trouble_codes(&mut self) -> Vec<TroubleCode> {
-        let n_dtcs = self.get_num_trouble_codes();
-        if n_dtcs == 0 {
-            // no trouble codes
-            return Vec::new();
-        }
-
-        if let Err(err) = self.send_command(&mut Command::new_svc(b"03")) {
-            println!("when getting dtc1: {err}");
-            return Vec::new();
-        }
+        let messages = self.query_messages(Command::new_svc(b"03"));
+        let codes = OBD::decode_trouble_codes(0x43, &messages, TroubleCodeStatus::confirmed());
+        self.describe_trouble_codes(codes)
+    }
+
+    /// Decode the DTCs in replies to service 03, 07 or 0A.
+    ///
+    /// `response_service` is the positive response byte (0x43, 0x47 or 0x4A).
+    /// Every code is given `status` and the address of the ECU that reported it.
+    pub fn decode_trouble_codes(
+        response_service: u8,
+        messages: &[EcuMessage],
+        status: TroubleCodeStatus,
+    ) -> Vec<TroubleCode> {
+        let mut codes = Vec::new();
 
-        let mut raw_response = self.read_iso_tp_response().join("");
-        OBD::decode_trouble_codes(&mut raw_response)
-    }
+        for message in messages {
+            let Some((&service, mut dtc_bytes)) = message.data.split_first() else {
+                continue;
+            };
 
-    pub fn decode_trouble_codes(response: &mut String) -> Vec<TroubleCode> {
-        println!(
-            "==> Decoding trouble codes. Raw response: '{}'",
-            response.escape_default()
-        );
-        let ecu_names = OBD::extract_ecu_names(response);
-        println!(" -> ECU names extracted: '{:?}'", ecu_names);
-        OBD::strip_ecu_names(response, &ecu_names);
-
-        let binding = response.replace("\r", "").replace(" ", "");
-        println!(" -> ECU names stripped, escaped, no spaces: '{binding}'");
-
-        let (sanitized, permanant) = {
-            if binding.contains("43") {
-                (
-                    binding
-                        .split("43")
-                        .filter_map(|chunk| {
-                            if chunk.len() >= 4 {
-                                Some(chunk.trim_end_matches("00"))
-                            } else {
-                                None
-                            }
-                        })
-                        .collect::<Vec<_>>()
-                        .join(""),
-                    false,
-                )
-            } else if binding.contains("4A") {
-                (
-                    binding
-                        .split("4A")
-                        .filter_map(|chunk| {
-                            if chunk.len() >= 4 {
-                                Some(chunk.trim_end_matches("00"))
-                            } else {
-                                None
-                            }
-                        })
-                        .collect::<Vec<_>>()
-                        .join(""),
-                    true,
-                )
-            } else {
-                return Vec::new();
+            if service != response_service {
+                continue;
             }
-        };
-
-        println!("dbg: dtc response from ecu: {sanitized}");
-
-        if sanitized.to_lowercase().contains("nodata") {
-            return Vec::new();
-        }
 
-        let mut codes = Vec::new();
-
-        for chunk in sanitized.as_bytes().chunks(4) {
-            if chunk.len() != 4 {
-                break;
+            // On CAN the reply starts with the number of codes (43 02 01 33 02 44).
+            // Legacy protocols always send 3 codes padded with zeros (43 01 33 02 44 00 00),
+            // so an odd number of bytes means there is a count byte to skip.
+            if dtc_bytes.len() % 2 == 1 {
+                dtc_bytes = &dtc_bytes[1..];
             }
 
-            let as_string = String::from_utf8_lossy(chunk);
-            let a = as_string.chars().nth(0).unwrap_or('\0');
-            let b = as_string.chars().nth(1).unwrap_or('\0');
-            let c = as_string.chars().nth(2).unwrap_or('\0');
-            let d = as_string.chars().nth(3).unwrap_or('\0');
-            let left = u8::from_str_radix(&format!("{}{}", a, b), 16).unwrap_or_default();
-            let right = u8::from_str_radix(&format!("{}{}", c, d), 16).unwrap_or_default();
+            for pair in dtc_bytes.chunks_exact(2) {
+                if pair[0] == 0x00 && pair[1] == 0x00 {
+                    continue;
+                }
 
-            if left == 0x00 && right == 0x00 || (chunk == b"0000") {
-                break;
+                let mut code = TroubleCode::from_bytes(pair[0], pair[1], status);
+                code.ecu = Some(message.ecu.clone());
+                codes.push(code);
             }
-
-            //println!("\nchunk: {:?}, {:?}. a: {a}, b: {b}, c: {c}, d: {d}", as_string, chunk);
-            let bit_7 = (left & 0b1000_0000) >> 7;
-            let bit_6 = (left & 0b0100_0000) >> 6;
-            let bit_5 = (left & 0b0010_0000) >> 5;
-            let bit_4 = (left & 0b0001_0000) >> 4;
-            let c2 = (bit_5 << 1) | bit_4;
-            let new_left = left & 0b00001111;
-            // println!("left: {left:b}, right: {right:b}");
-            // println!("bit 7: {bit_7:b}, bit 6: {bit_6:b}, bit 5: {bit_5:b}, bit 4: {bit_4:b}");
-            // println!("C2: {c2:b}");
-
-            let category = match (bit_7, bit_6) {
-                (0, 0) => TroubleCodeCategory::Powertrain,
-                (0, 1) => TroubleCodeCategory::Chassis,
-                (1, 0) => TroubleCodeCategory::Body,
-                (1, 1) => TroubleCodeCategory::Network,
-                _ => TroubleCodeCategory::Unknown,
-            };
-
-            let dtc_code = format!(
-                "{}{:01X}{:01X}{:02X}",
-                category.system_letter(),
-                c2,
-                new_left,
-                right
-            );
-
-            codes.push(TroubleCode::new(category, dtc_code, permanant));
         }
 
         codes
@@ -549,7 +729,12 @@ impl OBD {
     }
 
     pub fn distance_traveled_with_mil(&mut self) -> Scalar {
-        self.query(Command::new_pid(b"0121")).map_no_data(|r| {
+        self.try_distance_traveled_with_mil()
+            .unwrap_or(Scalar::no_data())
+    }
+
+    pub fn try_distance_traveled_with_mil(&mut self) -> Result<Scalar, PidError> {
+        self.try_query(Command::new_pid(b"0121")).map(|r| {
             Scalar::new(
                 (256.0 * r.a_value()) + r.b_value(),
                 Unit::Kilometers,
@@ -559,7 +744,11 @@ impl OBD {
     }
 
     pub fn time_run_with_mil(&mut self) -> Scalar {
-        self.query(Command::new_pid(b"014D")).map_no_data(|r| {
+        self.try_time_run_with_mil().unwrap_or(Scalar::no_data())
+    }
+
+    pub fn try_time_run_with_mil(&mut self) -> Result<Scalar, PidError> {
+        self.try_query(Command::new_pid(b"014D")).map(|r| {
             Scalar::new(
                 (256.0 * r.a_value()) + r.b_value(),
                 Unit::Minutes,
@@ -569,7 +758,12 @@ impl OBD {
     }
 
     pub fn control_module_voltage(&mut self) -> Scalar {
-        self.query(Command::new_pid(b"0142")).map_no_data(|r| {
+        self.try_control_module_voltage()
+            .unwrap_or(Scalar::no_data())
+    }
+
+    pub fn try_control_module_voltage(&mut self) -> Result<Scalar, PidError> {
+        self.try_query(Command::new_pid(b"0142")).map(|r| {
             Scalar::new(
                 ((256.0 * r.a_value()) + r.b_value()) / 1000.0,
                 Unit::Volts,
@@ -579,7 +773,12 @@ impl OBD {
     }
 
     pub fn time_since_codes_cleared(&mut self) -> Scalar {
-        self.query(Command::new_pid(b"014E")).map_no_data(|r| {
+        self.try_time_since_codes_cleared()
+            .unwrap_or(Scalar::no_data())
+    }
+
+    pub fn try_time_since_codes_cleared(&mut self) -> Result<Scalar, PidError> {
+        self.try_query(Command::new_pid(b"014E")).map(|r| {
             Scalar::new(
                 (256.0 * r.a_value()) + r.b_value(),
                 Unit::Minutes,
@@ -1,6 +1,9 @@
-use std::fmt;
+use std::{collections::HashMap, fmt};
+
+use serde::{Deserialize, Serialize};
 
 use crate::{
+    registry::PidError,
     scalar::{Scalar, Unit},
     Command, SensorNumber, Service, OBD,
 };
@@ -21,7 +24,7 @@ pub enum CylinderNumber {
     Cylinder12,
 }
 
-#[derive(PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum EngineType {
     SparkIgnition,
     CompressionIgnition,
@@ -47,7 +50,11 @@ impl fmt::Display for EngineType {
 
 impl OBD {
     pub fn rpm(&mut self) -> Scalar {
-        self.query(Command::new_pid(b"010C")).map_no_data(|r| {
+        self.try_rpm().unwrap_or(Scalar::no_data())
+    }
+
+    pub fn try_rpm(&mut self) -> Result<Scalar, PidError> {
+        self.try_query(Command::new_pid(b"010C")).map(|r| {
             Scalar::new(
                 ((256.0 * r.a_value()) + r.b_value()) / 4.0,
                 Unit::RPM,
@@ -57,7 +64,11 @@ impl OBD {
     }
 
     pub fn engine_load(&mut self) -> Scalar {
-        self.query(Command::new_pid(b"0104")).map_no_data(|r| {
+        self.try_engine_load().unwrap_or(Scalar::no_data())
+    }
+
+    pub fn try_engine_load(&mut self) -> Result<Scalar, PidError> {
+        self.try_query(Command::new_pid(b"0104")).map(|r| {
             Scalar::new(
                 r.a_value() / 2.55,
                 Unit::Percent,
@@ -67,7 +78,11 @@ impl OBD {
     }
 
     pub fn coolant_temp(&mut self) -> Scalar {
-        self.query(Command::new_pid(b"0105")).map_no_data(|r| {
+        self.try_coolant_temp().unwrap_or(Scalar::no_data())
+    }
+
+    pub fn try_coolant_temp(&mut self) -> Result<Scalar, PidError> {
+        self.try_query(Command::new_pid(b"0105")).map(|r| {
             Scalar::new(
                 r.a_value() - 40.0,
                 Unit::Celsius,
@@ -105,7 +120,11 @@ impl OBD {
     }
 
     pub fn engine_fuel_rate(&mut self) -> Scalar {
-        self.query(Command::new_pid(b"019D")).map_no_data(|r| {
+        self.try_engine_fuel_rate().unwrap_or(Scalar::no_data())
+    }
+
+    pub fn try_engine_fuel_rate(&mut self) -> Result<Scalar, PidError> {
+        self.try_query(Command::new_pid(b"019D")).map(|r| {
             Scalar::new(
                 r.a_value(),
                 Unit::GramsPerSecond,
@@ -115,11 +134,15 @@ impl OBD {
     }
 
     pub fn engine_runtime(&mut self) -> Scalar {
+        self.try_engine_runtime().unwrap_or(Scalar::no_data())
+    }
+
+    pub fn try_engine_runtime(&mut self) -> Result<Scalar, PidError> {
         if self.get_engine_type() == EngineType::CompressionIgnition {
-            return self.engine_runtime_diesel();
+            return self.try_engine_runtime_diesel();
         }
 
-        self.query(Command::new_pid(b"011F")).map_no_data(|r| {
+        self.try_query(Command::new_pid(b"011F")).map(|r| {
             Scalar::new(
                 (256.0 * r.a_value()) + r.b_value(),
                 Unit::Seconds,
@@ -128,11 +151,8 @@ impl OBD {
         })
     }
 
-    fn engine_runtime_diesel(&mut self) -> Scalar {
-        let response = self.query(Command::new_pid(b"017F"));
-        if *response.get_payload_size() == 0 {
-            return Scalar::no_data();
-        }
+    fn try_engine_runtime_diesel(&mut self) -> Result<Scalar, PidError> {
+        let response = self.try_query(Command::new_pid(b"017F"))?;
 
         let b = response.b_value();
         let c = response.c_value();
@@ -142,18 +162,19 @@ impl OBD {
         let c_power = f32::powf(2f32, 16f32);
         let d_power = f32::powf(2f32, 8f32);
 
-        Scalar::new(
+        Ok(Scalar::new(
             (b * b_power) + (c * c_power) + (d * d_power) + e,
             Unit::Seconds,
             Some(self.unit_preferences),
-        )
+        ))
     }
 
     pub fn odometer(&mut self) -> Scalar {
-        let response = self.query(Command::new_pid(b"01A6"));
-        if *response.get_payload_size() == 0 {
-            return Scalar::no_data();
-        }
+        self.try_odometer().unwrap_or(Scalar::no_data())
+    }
+
+    pub fn try_odometer(&mut self) -> Result<Scalar, PidError> {
+        let response = self.try_query(Command::new_pid(b"01A6"))?;
 
         let a = response.a_value();
         let b = response.b_value();
@@ -164,20 +185,24 @@ impl OBD {
         let b_power = f32::powf(2f32, 16f32);
         let c_power = f32::powf(2f32, 8f32);
 
-        Scalar::new(
+        Ok(Scalar::new(
             ((a * a_power) + (b * b_power) + (c * c_power) + d) / 10.0,
             Unit::Kilometers,
             Some(self.unit_preferences),
-        )
+        ))
     }
 
     pub fn engine_oil_temp(&mut self, mode: Service) -> Scalar {
+        self.try_engine_oil_temp(mode).unwrap_or(Scalar::no_data())
+    }
+
+    pub fn try_engine_oil_temp(&mut self, mode: Service) -> Result<Scalar, PidError> {
         let command = match mode {
             Service::Mode01 => Command::new_pid(b"015C"),
             Service::Mode22 => Command::new_arb("221154"),
         };
 
-        self.query(command).map_no_data(|r| {
+        self.try_query(command).map(|r| {
             Scalar::new(
                 r.a_value() - 40.0,
                 Unit::Celsius,
@@ -187,11 +212,14 @@ impl OBD {
     }
 
     pub fn engine_oil_temp_sensors(&mut self) -> (Scalar, Scalar) {
+        self.try_engine_oil_temp_sensors()
+            .unwrap_or((Scalar::no_data(), Scalar::no_data()))
+    }
+
+    /// Sensors the vehicle doesn't have are no data.
+    pub fn try_engine_oil_temp_sensors(&mut self) -> Result<(Scalar, Scalar), PidError> {
         let mut oil_temp = (Scalar::no_data(), Scalar::no_data());
-        let response = self.query(Command::new_pid(b"0167"));
-        if *response.get_payload_size() == 0 {
-            return oil_temp;
-        }
+        let response = self.try_query(Command::new_pid(b"0167"))?;
 
         let sensors_supported = self.sensors_supported_for(response.a_value() as u8);
 
@@ -211,11 +239,15 @@ impl OBD {
             );
         }
 
-        oil_temp
+        Ok(oil_temp)
     }
 
     pub fn engine_oil_pressure(&mut self) -> Scalar {
-        self.query(Command::new_arb("221470")).map_no_data(|r| {
+        self.try_engine_oil_pressure().unwrap_or(Scalar::no_data())
+    }
+
+    pub fn try_engine_oil_pressure(&mut self) -> Result<Scalar, PidError> {
+        self.try_query(Command::new_arb("221470")).map(|r| {
             Scalar::new(
                 r.a_value() * 3.985,
                 Unit::KiloPascal,
@@ -225,7 +257,12 @@ impl OBD {
     }
 
     pub fn drivers_demand_engine_torque(&mut self) -> Scalar {
-        self.query(Command::new_pid(b"0161")).map_no_data(|r| {
+        self.try_drivers_demand_engine_torque()
+            .unwrap_or(Scalar::no_data())
+    }
+
+    pub fn try_drivers_demand_engine_torque(&mut self) -> Result<Scalar, PidError> {
+        self.try_query(Command::new_pid(b"0161")).map(|r| {
             Scalar::new(
                 r.a_value() - 125.0,
                 Unit::Percent,
@@ -235,7 +272,11 @@ impl OBD {
     }
 
     pub fn actual_engine_torque(&mut self) -> Scalar {
-        self.query(Command::new_pid(b"0162")).map_no_data(|r| {
+        self.try_actual_engine_torque().unwrap_or(Scalar::no_data())
+    }
+
+    pub fn try_actual_engine_torque(&mut self) -> Result<Scalar, PidError> {
+        self.try_query(Command::new_pid(b"0162")).map(|r| {
             Scalar::new(
                 r.a_value() - 125.0,
                 Unit::Percent,
@@ -245,7 +286,12 @@ impl OBD {
     }
 
     pub fn reference_engine_torque(&mut self) -> Scalar {
-        self.query(Command::new_pid(b"0163")).map_no_data(|r| {
+        self.try_reference_engine_torque()
+            .unwrap_or(Scalar::no_data())
+    }
+
+    pub fn try_reference_engine_torque(&mut self) -> Result<Scalar, PidError> {
+        self.try_query(Command::new_pid(b"0163")).map(|r| {
             Scalar::new(
                 (256.0 * r.a_value()) + r.b_value(),
                 Unit::NewtonMeters,
@@ -255,8 +301,12 @@ impl OBD {
     }
 
     pub fn engine_oil_life(&mut self) -> Scalar {
-        self.query(Command::new_arb("221940"))
-            .map_no_data(|r| Scalar::new(r.a_value(), Unit::Percent, None))
+        self.try_engine_oil_life().unwrap_or(Scalar::no_data())
+    }
+
+    pub fn try_engine_oil_life(&mut self) -> Result<Scalar, PidError> {
+        self.try_query(Command::new_arb("221940"))
+            .map(|r| Scalar::new(r.a_value(), Unit::Percent, None))
     }
 
     // Returns 5 values.
@@ -293,15 +343,107 @@ impl OBD {
     }
 
     pub fn get_engine_type(&mut self) -> EngineType {
+        if let Some(profile) = self
+            .profile
+            .as_ref()
+            .filter(|profile| profile.engine_type != EngineType::Unknown)
+        {
+            return profile.engine_type;
+        }
+
         let response = self.query(Command::new_pid(b"0101"));
         if *response.get_payload_size() == 0 {
             return EngineType::Unknown;
         }
 
-        match response.b_value() as u32 & 0b00001000 {
-            0 => EngineType::SparkIgnition,
-            1 => EngineType::CompressionIgnition,
-            _ => unreachable!(),
+        if response.b_value() as u32 & 0b00001000 == 0 {
+            EngineType::SparkIgnition
+        } else {
+            EngineType::CompressionIgnition
+        }
+    }
+
+    /// Total engine run time, then time spent idling and with PTO engaged (017F).
+    pub fn engine_run_time_details(&mut self) -> (Scalar, Scalar, Scalar) {
+        let bytes = self.query_pid_bytes(b"017F");
+        let supported = bytes.first().copied().unwrap_or_default();
+
+        let mut times = [Scalar::no_data(), Scalar::no_data(), Scalar::no_data()];
+        for (index, time) in times.iter_mut().enumerate() {
+            let start = 1 + index * 4;
+            let Some(seconds) = bytes.get(start..start + 4) else {
+                break;
+            };
+
+            if supported & (1 << index) != 0 {
+                *time = Scalar::new(
+                    u32::from_be_bytes([seconds[0], seconds[1], seconds[2], seconds[3]]) as f32,
+                    Unit::Seconds,
+                    Some(self.unit_preferences),
+                );
+            }
+        }
+
+        let [total, idle, pto] = times;
+        (total, idle, pto)
+    }
+
+    /// Odometer as reported by every ECU (01A6), keyed by ECU.
+    ///
+    /// The engine, transmission and instrument cluster can each keep their own,
+    /// a mismatch between them can point to a replaced module or tampering.
+    pub fn odometer_by_ecu(&mut self) -> HashMap<String, Scalar> {
+        let mut odometers = HashMap::new();
+
+        for message in self.query_messages(Command::new_pid(b"01A6")) {
+            // 41 A6 A B C D
+            if message.data.len() < 6 || message.data[0] != 0x41 || message.data[1] != 0xA6 {
+                continue;
+            }
+
+            let distance = u32::from_be_bytes([
+                message.data[2],
+                message.data[3],
+                message.data[4],
+                message.data[5],
+            ]);
+
+            odometers.insert(
+                message.ecu,
+                Scalar::new(
+                    distance as f32 / 10.0,
+                    Unit::Kilometers,
+                    Some(self.unit_preferences),
+                ),
+            );
         }
+
+        odometers
+    }
+
+    /// Gear the transmission is in, and its ratio (01A4).
+    pub fn transmission_actual_gear(&mut self) -> (Scalar, Scalar) {
+        let bytes = self.query_pid_bytes(b"01A4");
+        let supported = bytes.first().copied().unwrap_or_default();
+
+        let gear = match bytes.get(1) {
+            Some(gear) if supported & 0b0010 != 0 => Scalar::new(
+                (gear >> 4) as f32,
+                Unit::Unknown,
+                Some(self.unit_preferences),
+            ),
+            _ => Scalar::no_data(),
+        };
+
+        let ratio = match bytes.get(2..4) {
+            Some(ratio) if supported & 0b0001 != 0 => Scalar::new(
+                u16::from_be_bytes([ratio[0], ratio[1]]) as f32 / 1000.0,
+                Unit::Ratio,
+                Some(self.unit_preferences),
+            ),
+            _ => Scalar::no_data(),
+        };
+
+        (gear, ratio)
     }
 }
@@ -1,12 +1,47 @@
+use serde::Serialize;
+
 use crate::{
+    registry::PidError,
     scalar::{Scalar, Unit},
     BankNumber, Command, SensorNumber, OBD,
 };
 
+/// Diesel exhaust fluid (urea) dosing, reported by 0185.
+#[derive(Debug, Clone, Serialize)]
+pub struct ReagentSystem {
+    pub average_consumption: Scalar,
+    pub demanded_consumption: Scalar,
+    pub tank_level: Scalar,
+
+    /// Time the NOx warning and inducement system has been active
+    pub warning_time: Scalar,
+}
+
+/// SCR inducement (limp mode when the NOx control system is faulty), reported by 0188.
+#[derive(Debug, Clone, Serialize)]
+pub struct ScrInducement {
+    pub active: bool,
+
+    /// Why inducement was triggered
+    pub reasons: Vec<&'static str>,
+    pub distance_while_active: Scalar,
+}
+
+const SCR_INDUCEMENT_REASONS: [&str; 4] = [
+    "Reagent level too low",
+    "Incorrect reagent",
+    "Deviation of reagent consumption",
+    "NOx emissions too high",
+];
+
 impl OBD {
     // Commanded exhaust gas recirculation
     pub fn commanded_egr(&mut self) -> Scalar {
-        self.query(Command::new_pid(b"012C")).map_no_data(|r| {
+        self.try_commanded_egr().unwrap_or(Scalar::no_data())
+    }
+
+    pub fn try_commanded_egr(&mut self) -> Result<Scalar, PidError> {
+        self.try_query(Command::new_pid(b"012C")).map(|r| {
             Scalar::new(
                 (100.0 / 255.0) * r.a_value(),
                 Unit::Percent,
@@ -17,7 +52,11 @@ impl OBD {
 
     // Exhaust gas recirculation error
     pub fn egr_error(&mut self) -> Scalar {
-        self.query(Command::new_pid(b"012D")).map_no_data(|r| {
+        self.try_egr_error().unwrap_or(Scalar::no_data())
+    }
+
+    pub fn try_egr_error(&mut self) -> Result<Scalar, PidError> {
+        self.try_query(Command::new_pid(b"012D")).map(|r| {
             Scalar::new(
                 ((100.0 / 128.0) * r.a_value()) - 100.0,
                 Unit::Percent,
@@ -27,6 +66,15 @@ impl OBD {
     }
 
     pub fn catalyst_temp(&mut self, bank: BankNumber, sensor: SensorNumber) -> Scalar {
+        self.try_catalyst_temp(bank, sensor)
+            .unwrap_or(Scalar::no_data())
+    }
+
+    pub fn try_catalyst_temp(
+        &mut self,
+        bank: BankNumber,
+        sensor: SensorNumber,
+    ) -> Result<Scalar, PidError> {
         let command = match (bank, sensor) {
             (BankNumber::Bank1, SensorNumber::Sensor1) => Command::new_pid(b"013C"),
             (BankNumber::Bank2, SensorNumber::Sensor1) => Command::new_pid(b"013D"),
@@ -34,11 +82,11 @@ impl OBD {
             (BankNumber::Bank2, SensorNumber::Sensor2) => Command::new_pid(b"013F"),
             _ => {
                 println!("catalyst temperature only supports bank 1, bank 2, sensor 1, and sensor 2 queries.");
-                return Scalar::no_data();
+                return Err(PidError::Unsupported);
             }
         };
 
-        self.query(command).map_no_data(|r| {
+        self.try_query(command).map(|r| {
             Scalar::new(
                 (((256.0 * r.a_value()) + r.b_value()) / 10.0) - 40.0,
                 Unit::Celsius,
@@ -48,16 +96,24 @@ impl OBD {
     }
 
     pub fn boost_guage_pressure(&mut self) -> Scalar {
-        let map = self.intake_manifold_abs_pressure();
-        let baro = self.abs_barometric_pressure();
+        self.try_boost_guage_pressure().unwrap_or(Scalar::no_data())
+    }
+
+    /// Fails with the error of the first of map and baro that couldn't be read.
+    pub fn try_boost_guage_pressure(&mut self) -> Result<Scalar, PidError> {
+        let map = self.try_intake_manifold_abs_pressure()?;
+        let baro = self.try_abs_barometric_pressure()?;
 
-        // result in kPa, gets converted to PSI. if conversion fails, do no data
-        // conversion never fail. map and baro will always be Unit::Kilopascal
-        (map - baro).convert(Unit::PSI).unwrap_or(Scalar::no_data())
+        // baro is converted to the unit of map
+        (map - baro).convert(Unit::PSI).ok_or(PidError::NoData)
     }
 
     pub fn turbocharger_rpm(&mut self) -> Scalar {
-        self.query(Command::new_pid(b"0174")).map_no_data(|r| {
+        self.try_turbocharger_rpm().unwrap_or(Scalar::no_data())
+    }
+
+    pub fn try_turbocharger_rpm(&mut self) -> Result<Scalar, PidError> {
+        self.try_query(Command::new_pid(b"0174")).map(|r| {
             Scalar::new(
                 (256.0 * r.a_value()) + r.b_value(),
                 Unit::RPM,
@@ -66,8 +122,131 @@ impl OBD {
         })
     }
 
-    // TODO!
-    pub fn exhaust_gas_temp(&self) -> Scalar {
-        Scalar::no_data()
+    /// Exhaust gas temperature of sensors 1 to 4 on `bank` (0178, 0179).
+    /// Sensors the vehicle doesn't have are no data.
+    pub fn exhaust_gas_temp(&mut self, bank: BankNumber) -> [Scalar; 4] {
+        let pid = match bank {
+            BankNumber::Bank1 => b"0178",
+            BankNumber::Bank2 => b"0179",
+        };
+
+        let bytes = self.query_pid_bytes(pid);
+        self.decode_sensor_words(&bytes, |raw| ((raw / 10.0) - 40.0, Unit::Celsius))
+    }
+
+    /// Diesel particulate filter pressures on `bank` (017A, 017B).
+    /// Returns the pressure across the filter, then the inlet and outlet pressures.
+    pub fn dpf_pressure(&mut self, bank: BankNumber) -> (Scalar, Scalar, Scalar) {
+        let pid = match bank {
+            BankNumber::Bank1 => b"017A",
+            BankNumber::Bank2 => b"017B",
+        };
+
+        let bytes = self.query_pid_bytes(pid);
+        let [_, inlet, outlet] =
+            self.decode_sensor_words(&bytes, |raw| (raw / 100.0, Unit::KiloPascal));
+
+        // the pressure across the filter is signed
+        let supported = bytes.first().copied().unwrap_or_default();
+        let delta = match bytes.get(1..3) {
+            Some(delta) if supported & 1 != 0 => Scalar::new(
+                i16::from_be_bytes([delta[0], delta[1]]) as f32 / 100.0,
+                Unit::KiloPascal,
+                Some(self.unit_preferences),
+            ),
+            _ => Scalar::no_data(),
+        };
+
+        (delta, inlet, outlet)
+    }
+
+    /// Diesel particulate filter inlet and outlet temperatures on `bank` (017C).
+    pub fn dpf_temp(&mut self, bank: BankNumber) -> (Scalar, Scalar) {
+        let bytes = self.query_pid_bytes(b"017C");
+        let [b1_inlet, b1_outlet, b2_inlet, b2_outlet] =
+            self.decode_sensor_words(&bytes, |raw| ((raw / 10.0) - 40.0, Unit::Celsius));
+
+        match bank {
+            BankNumber::Bank1 => (b1_inlet, b1_outlet),
+            BankNumber::Bank2 => (b2_inlet, b2_outlet),
+        }
+    }
+
+    /// NOx concentration of bank 1 sensor 1, bank 1 sensor 2,
+    /// bank 2 sensor 1 and bank 2 sensor 2 (0183).
+    pub fn nox_sensors(&mut self) -> [Scalar; 4] {
+        let bytes = self.query_pid_bytes(b"0183");
+        self.decode_sensor_words(&bytes, |raw| (raw, Unit::PartsPerMillion))
+    }
+
+    /// Diesel exhaust fluid dosing of the NOx control system (0185).
+    pub fn nox_reagent_system(&mut self) -> ReagentSystem {
+        let bytes = self.query_pid_bytes(b"0185");
+        let [average_consumption, demanded_consumption] =
+            self.decode_sensor_words(&bytes, |raw| (raw * 0.005, Unit::LitresPerHour));
+
+        let supported = bytes.first().copied().unwrap_or_default();
+        let tank_level = match bytes.get(5) {
+            Some(level) if supported & 0b0100 != 0 => Scalar::new(
+                (100.0 / 255.0) * *level as f32,
+                Unit::Percent,
+                Some(self.unit_preferences),
+            ),
+            _ => Scalar::no_data(),
+        };
+
+        let warning_time = match bytes.get(6..10) {
+            Some(time) if supported & 0b1000 != 0 => Scalar::new(
+                u32::from_be_bytes([time[0], time[1], time[2], time[3]]) as f32,
+                Unit::Seconds,
+                Some(self.unit_preferences),
+            ),
+            _ => Scalar::no_data(),
+        };
+
+        ReagentSystem {
+            average_consumption,
+            demanded_consumption,
+            tank_level,
+            warning_time,
+        }
+    }
+
+    /// Particulate matter concentration measured on bank 1 and bank 2 (0186).
+    pub fn pm_sensors(&mut self) -> (Scalar, Scalar) {
+        let bytes = self.query_pid_bytes(b"0186");
+        let [bank1, bank2] =
+            self.decode_sensor_words(&bytes, |raw| (raw * 0.0125, Unit::MilligramsPerCubicMeter));
+
+        (bank1, bank2)
+    }
+
+    /// Status of the SCR inducement system (0188).
+    /// `None` when the vehicle doesn't report it.
+    pub fn scr_inducement_status(&mut self) -> Option<ScrInducement> {
+        let bytes = self.query_pid_bytes(b"0188");
+        let status = *bytes.first()?;
+
+        let reasons = SCR_INDUCEMENT_REASONS
+            .iter()
+            .enumerate()
+            .filter(|(bit, _)| status & (1 << bit) != 0)
+            .map(|(_, reason)| *reason)
+            .collect();
+
+        let distance_while_active = match bytes.get(1..3) {
+            Some(distance) => Scalar::new(
+                u16::from_be_bytes([distance[0], distance[1]]) as f32,
+                Unit::Kilometers,
+                Some(self.unit_preferences),
+            ),
+            None => Scalar::no_data(),
+        };
+
+        Some(ScrInducement {
+            active: status & 0x80 != 0,
+            reasons,
+            distance_while_active,
+        })
     }
 }
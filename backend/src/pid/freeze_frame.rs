@@ -0,0 +1,194 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    diagnostics::{TroubleCode, TroubleCodeStatus},
+    registry,
+    scalar::Scalar,
+    Command, Error, OBD,
+};
+
+/// Most vehicles only store frame 0, but the standard allows up to 256.
+/// Stop looking after this many frames.
+const MAX_FREEZE_FRAMES: u8 = 16;
+
+/// A decoded value captured in a freeze frame.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FreezeFrameValue {
+    /// Service 01 pid the value was stored as (e.g "0C")
+    pub pid: String,
+    pub name: String,
+    pub value: Scalar,
+}
+
+/// Snapshot of the data an ECU stored when a DTC was set.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FreezeFrame {
+    /// The ECU that stored the frame
+    pub ecu: String,
+    pub frame: u8,
+
+    /// The DTC that caused the frame to be stored
+    pub dtc: Option<TroubleCode>,
+    pub values: Vec<FreezeFrameValue>,
+}
+
+impl OBD {
+    /// Read every stored freeze frame, from every ECU that has one.
+    pub fn get_freeze_frames(&mut self) -> Vec<FreezeFrame> {
+        let mut frames = Vec::new();
+
+        for frame in 0..MAX_FREEZE_FRAMES {
+            let dtcs = self.get_freeze_frame_dtcs(frame);
+            if dtcs.is_empty() {
+                break;
+            }
+
+            for (ecu, dtc) in dtcs {
+                frames.push(self.read_freeze_frame(&ecu, frame, Some(dtc)));
+            }
+        }
+
+        frames
+    }
+
+    /// Get the DTC that caused the first freeze frame, for each ECU that stored one.
+    pub fn get_freeze_frame_dtc(&mut self) -> Vec<TroubleCode> {
+        self.get_freeze_frame_dtcs(0)
+            .into_iter()
+            .map(|(_, dtc)| dtc)
+            .collect()
+    }
+
+    /// Get the DTC that caused freeze frame `frame`, by ECU.
+    ///
+    /// ECUs that reply with 0000 have not stored that frame and are left out.
+    pub fn get_freeze_frame_dtcs(&mut self, frame: u8) -> Vec<(String, TroubleCode)> {
+        let mut dtcs = Vec::new();
+
+        for message in self.query_messages(Command::new_arb(&format!("0202{frame:02X}"))) {
+            // 42 02 <frame> <dtc high> <dtc low>
+            if message.data.len() < 5 || message.data[0] != 0x42 || message.data[1] != 0x02 {
+                continue;
+            }
+
+            let (high, low) = (message.data[3], message.data[4]);
+            if high == 0 && low == 0 {
+                continue;
+            }
+
+            let mut dtc = TroubleCode::from_bytes(high, low, TroubleCodeStatus::default());
+            dtc.ecu = Some(message.ecu.clone());
+            dtcs.push((message.ecu, dtc));
+        }
+
+        dtcs
+    }
+
+    /// Get the pids stored in freeze frame `frame`, by ECU.
+    pub fn get_freeze_frame_supported_pids(&mut self, frame: u8) -> HashMap<String, Vec<u8>> {
+        self.get_supported_ids(0x02, Some(frame))
+    }
+
+    /// Capture every supported pid in freeze frame `frame` stored by `ecu`.
+    pub fn read_freeze_frame(
+        &mut self,
+        ecu: &str,
+        frame: u8,
+        dtc: Option<TroubleCode>,
+    ) -> FreezeFrame {
+        let mut snapshot = FreezeFrame {
+            ecu: ecu.to_string(),
+            frame,
+            dtc,
+            values: Vec::new(),
+        };
+
+        let mut supported_by_ecu = self.get_freeze_frame_supported_pids(frame);
+        let other_ecus = supported_by_ecu.keys().any(|other| other != ecu);
+        let supported = supported_by_ecu.remove(ecu).unwrap_or_default();
+
+        if supported.is_empty() {
+            return snapshot;
+        }
+
+        // Only listen to the ECU that stored the frame
+        // so values from different ECUs are not mixed up
+        if let Err(err) = self.filter_receive_address(ecu) {
+            println!("when filtering freeze frame responses from ecu {ecu}: {err}");
+            if other_ecus {
+                return snapshot;
+            }
+        }
+
+        let previous_frame = self.get_freeze_frame();
+        self.set_freeze_frame(Some(frame));
+
+        // Requests made by the registry getters are redirected
+        // to service 02 while a frame is being read.
+        for def in registry::PIDS {
+            if def.service != 0x01 || def.read.is_none() || !supported.contains(&def.pid) {
+                continue;
+            }
+
+            let pid = format!("{:02X}", def.pid);
+            for (name, value) in def.decode_cards(self) {
+                snapshot.values.push(FreezeFrameValue {
+                    pid: pid.clone(),
+                    name: name.to_string(),
+                    value,
+                });
+            }
+        }
+
+        self.set_freeze_frame(previous_frame);
+        if let Err(err) = self.query_at(Command::new_arb("ATAR")) {
+            println!("when restoring automatic receive address: {err}");
+        }
+
+        snapshot
+    }
+
+    // Make the ELM327 only accept replies from `ecu`
+    fn filter_receive_address(&mut self, ecu: &str) -> Result<(), Error> {
+        let command = self.receive_address_filter(ecu);
+        let reply = self
+            .query_at(Command::new_arb(&command))?
+            .formatted_response
+            .unwrap_or_default();
+
+        // the ELM327 answers '?' to commands it doesn't understand
+        if !reply.contains("OK") {
+            return Err(Error::InvalidResponse);
+        }
+
+        Ok(())
+    }
+
+    // The AT command that makes the ELM327 only accept replies from `ecu`
+    fn receive_address_filter(&mut self, ecu: &str) -> String {
+        // The protocol requested at connect is 0 when set to automatic,
+        // so go by the one the adapter settled on.
+        let detected = self.get_detected_protocol();
+        let (can, extended) = if detected.is_empty() {
+            (
+                matches!(self.protocol, 6..=9),
+                matches!(self.protocol, 7 | 9),
+            )
+        } else {
+            (detected.contains("CAN"), detected.contains("29"))
+        };
+
+        match (can, extended) {
+            // 11-bit CAN, the ECU is its CAN id (e.g 7E8)
+            (true, false) => format!("ATCRA {ecu}"),
+
+            // 29-bit CAN, the ECU is the source address in 18 DA F1 xx
+            (true, true) => format!("ATCRA 18DAF1{ecu}"),
+
+            // J1850, ISO 9141 and ISO 14230, the ECU is the source address in the header
+            (false, _) => format!("ATSR {ecu}"),
+        }
+    }
+}
@@ -1,6 +1,7 @@
 use std::fmt;
 
 use crate::{
+    registry::PidError,
     scalar::{Scalar, Unit},
     BankNumber, Command, OBD,
 };
@@ -71,6 +72,11 @@ impl FuelSystemStatus {
 
 impl OBD {
     pub fn short_term_fuel_trim(&mut self, bank: &BankNumber) -> Scalar {
+        self.try_short_term_fuel_trim(bank)
+            .unwrap_or(Scalar::no_data())
+    }
+
+    pub fn try_short_term_fuel_trim(&mut self, bank: &BankNumber) -> Result<Scalar, PidError> {
         let mut command = Command::default();
 
         match bank {
@@ -78,7 +84,7 @@ impl OBD {
             BankNumber::Bank2 => command.set_pid(b"0108"),
         }
 
-        self.query(command).map_no_data(|r| {
+        self.try_query(command).map(|r| {
             Scalar::new(
                 (r.a_value() / 1.28) - 100.0,
                 Unit::Percent,
@@ -88,12 +94,17 @@ impl OBD {
     }
 
     pub fn long_term_fuel_trim(&mut self, bank: &BankNumber) -> Scalar {
+        self.try_long_term_fuel_trim(bank)
+            .unwrap_or(Scalar::no_data())
+    }
+
+    pub fn try_long_term_fuel_trim(&mut self, bank: &BankNumber) -> Result<Scalar, PidError> {
         let command = match bank {
             BankNumber::Bank1 => Command::new_pid(b"0107"),
             BankNumber::Bank2 => Command::new_pid(b"0109"),
         };
 
-        self.query(command).map_no_data(|r| {
+        self.try_query(command).map(|r| {
             Scalar::new(
                 (r.a_value() / 1.28) - 100.0,
                 Unit::Percent,
@@ -119,7 +130,11 @@ impl OBD {
     }
 
     pub fn fuel_pressure(&mut self) -> Scalar {
-        self.query(Command::new_pid(b"010A")).map_no_data(|r| {
+        self.try_fuel_pressure().unwrap_or(Scalar::no_data())
+    }
+
+    pub fn try_fuel_pressure(&mut self) -> Result<Scalar, PidError> {
+        self.try_query(Command::new_pid(b"010A")).map(|r| {
             Scalar::new(
                 r.a_value() * 3.0,
                 Unit::KiloPascal,
@@ -129,7 +144,11 @@ impl OBD {
     }
 
     pub fn fuel_tank_level(&mut self) -> Scalar {
-        self.query(Command::new_pid(b"012F")).map_no_data(|r| {
+        self.try_fuel_tank_level().unwrap_or(Scalar::no_data())
+    }
+
+    pub fn try_fuel_tank_level(&mut self) -> Result<Scalar, PidError> {
+        self.try_query(Command::new_pid(b"012F")).map(|r| {
             Scalar::new(
                 (100.0 / 255.0) * r.a_value(),
                 Unit::Percent,
@@ -139,7 +158,11 @@ impl OBD {
     }
 
     pub fn fuel_rail_pressure(&mut self) -> Scalar {
-        self.query(Command::new_pid(b"0122")).map_no_data(|r| {
+        self.try_fuel_rail_pressure().unwrap_or(Scalar::no_data())
+    }
+
+    pub fn try_fuel_rail_pressure(&mut self) -> Result<Scalar, PidError> {
+        self.try_query(Command::new_pid(b"0122")).map(|r| {
             Scalar::new(
                 0.079 * ((256.0 * r.a_value()) + r.b_value()),
                 Unit::KiloPascal,
@@ -149,7 +172,12 @@ impl OBD {
     }
 
     pub fn fuel_rail_guage_pressure(&mut self) -> Scalar {
-        self.query(Command::new_pid(b"0123")).map_no_data(|r| {
+        self.try_fuel_rail_guage_pressure()
+            .unwrap_or(Scalar::no_data())
+    }
+
+    pub fn try_fuel_rail_guage_pressure(&mut self) -> Result<Scalar, PidError> {
+        self.try_query(Command::new_pid(b"0123")).map(|r| {
             Scalar::new(
                 10.0 * ((256.0 * r.a_value()) + r.b_value()),
                 Unit::KiloPascal,
@@ -169,7 +197,12 @@ impl OBD {
     }
 
     pub fn ethanol_fuel_percentage(&mut self) -> Scalar {
-        self.query(Command::new_pid(b"0152")).map_no_data(|r| {
+        self.try_ethanol_fuel_percentage()
+            .unwrap_or(Scalar::no_data())
+    }
+
+    pub fn try_ethanol_fuel_percentage(&mut self) -> Result<Scalar, PidError> {
+        self.try_query(Command::new_pid(b"0152")).map(|r| {
             Scalar::new(
                 (100.0 / 255.0) * r.a_value(),
                 Unit::Percent,
@@ -179,7 +212,12 @@ impl OBD {
     }
 
     pub fn fuel_injection_timing(&mut self) -> Scalar {
-        self.query(Command::new_pid(b"015D")).map_no_data(|r| {
+        self.try_fuel_injection_timing()
+            .unwrap_or(Scalar::no_data())
+    }
+
+    pub fn try_fuel_injection_timing(&mut self) -> Result<Scalar, PidError> {
+        self.try_query(Command::new_pid(b"015D")).map(|r| {
             Scalar::new(
                 (((256.0 * r.a_value()) + r.b_value()) / 128.0) - 210.0,
                 Unit::Degrees,
@@ -189,7 +227,11 @@ impl OBD {
     }
 
     pub fn commanded_evap_purge(&mut self) -> Scalar {
-        self.query(Command::new_pid(b"012E")).map_no_data(|r| {
+        self.try_commanded_evap_purge().unwrap_or(Scalar::no_data())
+    }
+
+    pub fn try_commanded_evap_purge(&mut self) -> Result<Scalar, PidError> {
+        self.try_query(Command::new_pid(b"012E")).map(|r| {
             Scalar::new(
                 (100.0 / 255.0) * r.a_value(),
                 Unit::Percent,
@@ -199,7 +241,12 @@ impl OBD {
     }
 
     pub fn evap_system_vapor_pressure(&mut self) -> Scalar {
-        self.query(Command::new_pid(b"0132")).map_no_data(|r| {
+        self.try_evap_system_vapor_pressure()
+            .unwrap_or(Scalar::no_data())
+    }
+
+    pub fn try_evap_system_vapor_pressure(&mut self) -> Result<Scalar, PidError> {
+        self.try_query(Command::new_pid(b"0132")).map(|r| {
             Scalar::new(
                 ((256.0 * r.a_value()) + r.b_value()) / 4.0,
                 Unit::Pascal,
@@ -209,7 +256,11 @@ impl OBD {
     }
 
     pub fn cylinder_fuel_rate(&mut self) -> Scalar {
-        self.query(Command::new_pid(b"01A2")).map_no_data(|r| {
+        self.try_cylinder_fuel_rate().unwrap_or(Scalar::no_data())
+    }
+
+    pub fn try_cylinder_fuel_rate(&mut self) -> Result<Scalar, PidError> {
+        self.try_query(Command::new_pid(b"01A2")).map(|r| {
             Scalar::new(
                 ((256.0 * r.a_value()) + r.b_value()) / 32.0,
                 Unit::MiligramsPerStroke,
@@ -217,4 +268,11 @@ impl OBD {
             )
         })
     }
+
+    /// Share of fuel delivered by fuel system A bank 1, A bank 2,
+    /// B bank 1 and B bank 2 (019F). Used by dual fuel systems (e.g port and direct injection).
+    pub fn fuel_system_percentage_use(&mut self) -> [Scalar; 4] {
+        let bytes = self.query_pid_bytes(b"019F");
+        self.decode_sensor_words(&bytes, |raw| ((100.0 / 65535.0) * raw, Unit::Percent))
+    }
 }
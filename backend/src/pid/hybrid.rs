@@ -0,0 +1,256 @@
+// Hybrid and electric vehicle battery.
+//
+// SAE J1979 only covers the pack as a whole (015B, 019A). Cell voltages and
+// temperatures are manufacturer specific, so they are read through the service 22
+// pids of the vehicle's model in model-pids.sqlite when it has them.
+
+use std::time::Instant;
+
+use serde::Serialize;
+use sqlite::State;
+
+use crate::{
+    registry::PidError,
+    scalar::{Scalar, Unit},
+    Command, MODE22_PIDS_DB_PATH, OBD,
+};
+
+/// Hybrid/EV battery system data, reported by 019A.
+#[derive(Debug, Clone, Serialize)]
+pub struct HybridSystemData {
+    /// e.g "Charge sustaining". "Unknown" if not reported.
+    pub charging_state: &'static str,
+    pub battery_voltage: Scalar,
+
+    /// Positive while discharging, negative while charging
+    pub battery_current: Scalar,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct EvBatteryData {
+    pub state_of_charge: Scalar,
+    pub voltage: Scalar,
+    pub current: Scalar,
+
+    /// Positive while discharging, negative while charging or regenerating
+    pub power: Scalar,
+    pub charging_state: &'static str,
+
+    pub cell_voltage_min: Scalar,
+    pub cell_voltage_max: Scalar,
+    pub temp_min: Scalar,
+    pub temp_max: Scalar,
+}
+
+/// Keywords that identify a manufacturer pid in the description of model-pids.sqlite.
+/// Every keyword must appear.
+const STATE_OF_CHARGE_KEYWORDS: &[&str] = &["state of charge"];
+const CELL_VOLTAGE_MIN_KEYWORDS: &[&str] = &["cell", "volt", "min"];
+const CELL_VOLTAGE_MAX_KEYWORDS: &[&str] = &["cell", "volt", "max"];
+const TEMP_MIN_KEYWORDS: &[&str] = &["batt", "temp", "min"];
+const TEMP_MAX_KEYWORDS: &[&str] = &["batt", "temp", "max"];
+
+/// Energy drawn from the battery over a drive,
+/// integrated from battery power and vehicle speed.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct EnergyMeter {
+    /// Net energy used in kWh. Regeneration counts against it.
+    pub energy_used: f32,
+
+    /// Distance driven in km
+    pub distance: f32,
+
+    #[serde(skip)]
+    last_update: Option<Instant>,
+}
+
+impl EnergyMeter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add the energy used since the last update.
+    /// The first update only starts the clock.
+    pub fn update(&mut self, power: &Scalar, speed: &Scalar) {
+        let now = Instant::now();
+        let Some(last) = self.last_update.replace(now) else {
+            return;
+        };
+
+        let hours = now.duration_since(last).as_secs_f32() / 3600.0;
+        if let Some(power) = power.value_in(Unit::Kilowatts) {
+            self.energy_used += power * hours;
+        }
+        if let Some(speed) = speed.value_in(Unit::KilometersPerHour) {
+            self.distance += speed * hours;
+        }
+    }
+
+    pub fn energy(&self) -> Scalar {
+        Scalar::new(self.energy_used, Unit::KilowattHours, None)
+    }
+
+    /// Energy used per 100 km. No data until the vehicle has moved.
+    pub fn consumption(&self) -> Scalar {
+        if self.distance < 0.1 {
+            return Scalar::no_data();
+        }
+
+        Scalar::new(
+            self.energy_used / self.distance * 100.0,
+            Unit::KilowattHoursPer100Kilometers,
+            None,
+        )
+    }
+}
+
+/// Battery power in kW from its voltage and current.
+pub fn battery_power(voltage: &Scalar, current: &Scalar) -> Scalar {
+    match (
+        voltage.value_in(Unit::Volts),
+        current.value_in(Unit::Amperes),
+    ) {
+        (Some(voltage), Some(current)) => {
+            Scalar::new(voltage * current / 1000.0, Unit::Kilowatts, None)
+        }
+        _ => Scalar::no_data(),
+    }
+}
+
+impl OBD {
+    pub fn hybrid_battery_remaining_life(&mut self) -> Scalar {
+        self.try_hybrid_battery_remaining_life()
+            .unwrap_or(Scalar::no_data())
+    }
+
+    pub fn try_hybrid_battery_remaining_life(&mut self) -> Result<Scalar, PidError> {
+        self.try_query(Command::new_pid(b"015B")).map(|r| {
+            Scalar::new(
+                (100.0 / 255.0) * r.a_value(),
+                Unit::Percent,
+                Some(self.unit_preferences),
+            )
+        })
+    }
+
+    pub fn hybrid_system_data(&mut self) -> HybridSystemData {
+        let bytes = self.query_pid_bytes(b"019A");
+        let supported = bytes.first().copied().unwrap_or_default();
+
+        let charging_state = match bytes.get(1) {
+            Some(state) if supported & 0b0001 != 0 => match state & 0b0011 {
+                0 => "Charge sustaining",
+                1 => "Charge depleting",
+                2 => "Charge increasing",
+                _ => "Unknown",
+            },
+            _ => "Unknown",
+        };
+
+        let battery_voltage = match bytes.get(2..4) {
+            Some(voltage) if supported & 0b0010 != 0 => Scalar::new(
+                u16::from_be_bytes([voltage[0], voltage[1]]) as f32 / 64.0,
+                Unit::Volts,
+                Some(self.unit_preferences),
+            ),
+            _ => Scalar::no_data(),
+        };
+
+        let battery_current = match bytes.get(4..6) {
+            Some(current) if supported & 0b0100 != 0 => Scalar::new(
+                i16::from_be_bytes([current[0], current[1]]) as f32 / 10.0,
+                Unit::Amperes,
+                Some(self.unit_preferences),
+            ),
+            _ => Scalar::no_data(),
+        };
+
+        HybridSystemData {
+            charging_state,
+            battery_voltage,
+            battery_current,
+        }
+    }
+
+    /// Everything known about the traction battery.
+    ///
+    /// Standard pids are used where they exist, manufacturer pids fill in the rest.
+    /// Values neither reports are no data.
+    pub fn get_ev_battery(&mut self) -> EvBatteryData {
+        let system = self.hybrid_system_data();
+        let model = self
+            .get_vin()
+            .and_then(|vin| vin.get_engine_manufacturer().ok());
+
+        let manufacturer_value = |obd: &mut OBD, keywords: &[&str]| match &model {
+            Some(model) => obd.mode22_value(model, keywords),
+            None => Scalar::no_data(),
+        };
+
+        let mut state_of_charge = manufacturer_value(self, STATE_OF_CHARGE_KEYWORDS);
+        if state_of_charge.unit == Unit::NoData {
+            state_of_charge = self.hybrid_battery_remaining_life();
+        }
+
+        EvBatteryData {
+            state_of_charge,
+            power: battery_power(&system.battery_voltage, &system.battery_current),
+            voltage: system.battery_voltage,
+            current: system.battery_current,
+            charging_state: system.charging_state,
+            cell_voltage_min: manufacturer_value(self, CELL_VOLTAGE_MIN_KEYWORDS),
+            cell_voltage_max: manufacturer_value(self, CELL_VOLTAGE_MAX_KEYWORDS),
+            temp_min: manufacturer_value(self, TEMP_MIN_KEYWORDS),
+            temp_max: manufacturer_value(self, TEMP_MAX_KEYWORDS),
+        }
+    }
+
+    // Query the first service 22 pid of `model` whose description has every keyword.
+    fn mode22_value(&mut self, model: &str, keywords: &[&str]) -> Scalar {
+        let con = match sqlite::Connection::open(MODE22_PIDS_DB_PATH) {
+            Ok(con) => con,
+            Err(err) => {
+                println!("when connecting to mode22 database: {err}");
+                return Scalar::no_data();
+            }
+        };
+
+        let query = format!(
+            "SELECT pid, equation, unit FROM vehicle_pids WHERE model = ?{} LIMIT 1",
+            " AND description LIKE ?".repeat(keywords.len())
+        );
+
+        let (pid, equation, unit) = {
+            let mut statement = match con.prepare(&query) {
+                Ok(statement) => statement,
+                Err(err) => {
+                    println!("when sanitizing statement {query}: {err}");
+                    return Scalar::no_data();
+                }
+            };
+
+            let mut params = vec![model.to_string()];
+            params.extend(keywords.iter().map(|keyword| format!("%{keyword}%")));
+            for (i, param) in params.iter().enumerate() {
+                if let Err(err) = statement.bind((i + 1, param.as_str())) {
+                    println!("when binding '{param}' to query {query}: {err}");
+                    return Scalar::no_data();
+                }
+            }
+
+            if !matches!(statement.next(), Ok(State::Row)) {
+                return Scalar::no_data();
+            }
+
+            (
+                statement.read::<String, _>("pid").unwrap_or_default(),
+                statement.read::<String, _>("equation").unwrap_or_default(),
+                statement.read::<String, _>("unit").unwrap_or_default(),
+            )
+        };
+
+        let response = self.query(Command::new_arb(&pid));
+        self.calculate_dynamic_equation(&equation, &unit, &response)
+            .unwrap_or(Scalar::no_data())
+    }
+}
@@ -1,6 +1,12 @@
+pub mod aftertreatment;
 pub mod air;
 pub mod diagnostics;
 pub mod engine;
 pub mod exhaust;
+pub mod freeze_frame;
 pub mod fuel;
+pub mod hybrid;
+pub mod oxygen;
+pub mod registry;
+pub mod sample;
 pub mod sensors;
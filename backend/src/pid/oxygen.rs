@@ -0,0 +1,149 @@
+// Oxygen sensor layout and scaling.
+//
+// Vehicles report which oxygen sensors are fitted through 0113 (up to 2 banks of
+// 4 sensors) or 011D (up to 4 banks of 2 sensors). Either way, bit N of the
+// bitmap is the sensor read through pid 0114 + N, 0124 + N and 0134 + N,
+// so the layout decides which bank and position a sensor number refers to.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    scalar::{Scalar, Unit},
+    Command, SensorNumber, OBD,
+};
+
+/// Where an oxygen sensor is fitted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OxygenSensorLocation {
+    /// Bank 1 to 4
+    pub bank: u8,
+
+    /// 1 is upstream of the catalyst, higher numbers are further downstream
+    pub position: u8,
+
+    /// Used to read the sensor
+    pub sensor: SensorNumber,
+}
+
+impl OxygenSensorLocation {
+    /// e.g "Bank 1 Sensor 2"
+    pub fn name(&self) -> String {
+        format!("Bank {} Sensor {}", self.bank, self.position)
+    }
+
+    /// e.g "B1S2"
+    pub fn short_name(&self) -> String {
+        format!("B{}S{}", self.bank, self.position)
+    }
+}
+
+/// Full scale of the wideband oxygen sensor pids (0124-012B, 0134-013B).
+/// Reported by 014F, defaults from SAE J1979 when it isn't.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OxygenSensorScaling {
+    pub max_ratio: f32,
+
+    /// Volts
+    pub max_voltage: f32,
+
+    /// Milliamperes
+    pub max_current: f32,
+}
+
+impl Default for OxygenSensorScaling {
+    fn default() -> Self {
+        Self {
+            max_ratio: 2.0,
+            max_voltage: 8.0,
+            max_current: 128.0,
+        }
+    }
+}
+
+impl OxygenSensorScaling {
+    pub(crate) fn ratio(&self, raw: u16) -> f32 {
+        self.max_ratio * raw as f32 / 65536.0
+    }
+
+    pub(crate) fn voltage(&self, raw: u16) -> f32 {
+        self.max_voltage * raw as f32 / 65536.0
+    }
+
+    // Centered on 0x8000, negative currents are lean
+    pub(crate) fn current(&self, raw: u16) -> f32 {
+        self.max_current * (raw as f32 - 32768.0) / 32768.0
+    }
+}
+
+impl OBD {
+    /// Oxygen sensors present, from 0113 or, if the vehicle uses the
+    /// 4 bank layout, 011D. Ordered by sensor number.
+    /// Taken from the vehicle profile when one is loaded.
+    pub fn get_oxygen_sensor_map(&mut self) -> Vec<OxygenSensorLocation> {
+        if let Some(profile) = self
+            .profile
+            .as_ref()
+            .filter(|profile| !profile.oxygen_sensors.is_empty())
+        {
+            return profile.oxygen_sensors.clone();
+        }
+
+        let response = self.query(Command::new_pid(b"0113"));
+        if *response.get_payload_size() != 0 {
+            return oxygen_sensor_map(response.a_value() as u8, 4);
+        }
+
+        let response = self.query(Command::new_pid(b"011D"));
+        if *response.get_payload_size() != 0 {
+            return oxygen_sensor_map(response.a_value() as u8, 2);
+        }
+
+        Vec::new()
+    }
+
+    /// Scaling of the wideband oxygen sensor pids.
+    ///
+    /// 014F reports a maximum of 0 for values it doesn't override,
+    /// those keep the default.
+    pub fn oxygen_sensor_scaling(&mut self) -> OxygenSensorScaling {
+        let mut scaling = OxygenSensorScaling::default();
+        let (max_ratio, max_voltage, max_current, _) = self.max_values_for();
+
+        for (max, value) in [
+            (&mut scaling.max_ratio, max_ratio),
+            (&mut scaling.max_voltage, max_voltage),
+            (&mut scaling.max_current, max_current),
+        ] {
+            if value.unit != Unit::NoData && value.value > 0.0 {
+                *max = value.value;
+            }
+        }
+
+        scaling
+    }
+
+    /// Narrowband voltage and short term fuel trim of every sensor present.
+    pub fn read_oxygen_sensors(&mut self) -> Vec<(OxygenSensorLocation, Scalar, Scalar)> {
+        self.get_oxygen_sensor_map()
+            .into_iter()
+            .map(|location| {
+                let (voltage, trim) = self.read_oxygen_sensor(&location.sensor);
+                (location, voltage, trim)
+            })
+            .collect()
+    }
+}
+
+// Bit N of `bitmap` is sensor N + 1, `per_bank` sensors to a bank.
+fn oxygen_sensor_map(bitmap: u8, per_bank: u8) -> Vec<OxygenSensorLocation> {
+    SensorNumber::ALL
+        .iter()
+        .enumerate()
+        .filter(|(bit, _)| bitmap & (1 << bit) != 0)
+        .map(|(bit, sensor)| OxygenSensorLocation {
+            bank: bit as u8 / per_bank + 1,
+            position: bit as u8 % per_bank + 1,
+            sensor: *sensor,
+        })
+        .collect()
+}
@@ -0,0 +1,2392 @@
+// Every known service 01 pid, in one place.
+//
+// Each pid is described once: what it is, how long its response is, the
+// getter that decodes it, the cards its values are shown on and how often
+// they're polled. The supported pid list, the card pollers, freeze
+// frames and the terminal all read from here, so a pid added to `PIDS` shows
+// up everywhere. Oxygen sensor pids aren't polled from here, only the sensors
+// the vehicle has are (see `oxygen`).
+
+use thiserror::Error;
+
+use crate::{
+    obd,
+    scalar::{Scalar, Unit},
+    BankNumber, NegativeResponseCode, Response, SensorNumber, Service, OBD,
+};
+
+/// Why a pid couldn't be read.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum PidError {
+    #[error("Pid is not supported by the vehicle.")]
+    Unsupported,
+
+    /// The ECU had nothing to report, e.g the engine is off
+    #[error("No data was received.")]
+    NoData,
+    #[error("No response from the vehicle.")]
+    Timeout,
+    #[error("Request was rejected: {0}.")]
+    NegativeResponse(NegativeResponseCode),
+    #[error("Response is {got} bytes, expected {expected}.")]
+    Malformed { expected: usize, got: usize },
+
+    /// The ELM327 or serial connection failed
+    #[error("Adapter error: {0}")]
+    Adapter(String),
+}
+
+impl PidError {
+    /// Short name of the error, sent with the cards to the frontend.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Unsupported => "unsupported",
+            Self::NoData => "no data",
+            Self::Timeout => "timeout",
+            Self::NegativeResponse(_) => "negative response",
+            Self::Malformed { .. } => "malformed",
+            Self::Adapter(_) => "adapter error",
+        }
+    }
+
+    pub(crate) fn from_negative_response(code: u8) -> Self {
+        match NegativeResponseCode::from_u8(code) {
+            NegativeResponseCode::ServiceNotSupported
+            | NegativeResponseCode::SubFunctionNotSupported
+            | NegativeResponseCode::RequestOutOfRange => Self::Unsupported,
+            code => Self::NegativeResponse(code),
+        }
+    }
+
+    /// Classify the reply to a service 01 or 02 request.
+    /// `None` when it's a usable reply.
+    pub(crate) fn from_response(request: &str, response: &Response) -> Option<Self> {
+        let raw = response.raw_response().unwrap_or_default();
+        if let Some(status) = [
+            "STOPPED",
+            "BUS BUSY",
+            "BUS ERROR",
+            "CAN ERROR",
+            "BUFFER FULL",
+        ]
+        .into_iter()
+        .find(|status| raw.contains(status))
+        {
+            return Some(Self::Adapter(status.to_string()));
+        }
+
+        // 7F <service> <code>
+        let formatted = response.full_response().unwrap_or_default();
+        let bytes: Vec<&str> = formatted.split_whitespace().collect();
+        if let ["7F", _, code, ..] = bytes.as_slice() {
+            return Some(Self::from_negative_response(
+                u8::from_str_radix(code, 16).unwrap_or_default(),
+            ));
+        }
+
+        if *response.get_payload_size() == 0 {
+            return Some(Self::NoData);
+        }
+
+        // Service 02 replies carry the frame number after the pid
+        let header = match request.get(..2) {
+            Some("01") => 2,
+            Some("02") => 3,
+            _ => return None,
+        };
+
+        let def = find(0x01, u8::from_str_radix(request.get(2..4)?, 16).ok()?)?;
+        let got = bytes.len().saturating_sub(header);
+        if got < def.bytes {
+            return Some(Self::Malformed {
+                expected: def.bytes,
+                got,
+            });
+        }
+
+        None
+    }
+}
+
+impl From<&obd::Error> for PidError {
+    fn from(err: &obd::Error) -> Self {
+        match err {
+            obd::Error::NoData => Self::NoData,
+
+            // nothing came back before the prompt
+            obd::Error::InvalidResponse => Self::Timeout,
+            err => Self::Adapter(err.as_str().to_string()),
+        }
+    }
+}
+
+/// How often a pid is polled while connected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Poll {
+    /// Only read on request
+    Never,
+
+    /// Read once after connecting
+    Once,
+    Critical,
+    High,
+    Frequent,
+    LessFrequent,
+}
+
+/// Decodes every value of a pid, in the order of `PidDef::cards`.
+pub type PidReader = fn(&mut OBD) -> Vec<Scalar>;
+
+#[derive(Debug, Clone, Copy)]
+pub struct PidDef {
+    pub service: u8,
+    pub pid: u8,
+    pub bytes: usize,
+    pub name: &'static str,
+    pub unit: &'static str,
+    pub formula: &'static str,
+
+    /// Lowest and highest value SAE J1979 defines, in `unit`.
+    /// `None` for pids holding values of different units.
+    pub range: Option<(f32, f32)>,
+
+    /// Name of each value the pid holds, shown as a card in the frontend.
+    /// Empty for pids that aren't a measurement (e.g bitmaps and statuses).
+    pub cards: &'static [&'static str],
+    pub poll: Poll,
+
+    /// `None` when the pid has no numeric decoder
+    pub read: Option<PidReader>,
+}
+
+impl PidDef {
+    /// e.g "010C"
+    pub fn command(&self) -> String {
+        format!("{:02X}{:02X}", self.service, self.pid)
+    }
+
+    /// Request the pid from the vehicle and decode it.
+    pub fn read(&self, obd: &mut OBD) -> Result<Vec<Scalar>, PidError> {
+        let Some(read) = self.read else {
+            return Err(PidError::Unsupported);
+        };
+
+        let values = read(obd);
+        match obd.pid_error.take() {
+            Some(err) => Err(err),
+            None if values.iter().all(|value| value.unit == Unit::NoData) => Err(PidError::NoData),
+            None => Ok(values),
+        }
+    }
+
+    /// Decode `response`, a reply already received to this pid's request,
+    /// without sending the request again.
+    pub fn decode_response(
+        &self,
+        obd: &mut OBD,
+        response: Response,
+    ) -> Vec<(&'static str, Scalar)> {
+        obd.answered = Some((self.command(), response));
+        let cards = self.decode_cards(obd);
+        obd.answered = None;
+        cards
+    }
+
+    /// Each value paired with the name of its card.
+    pub fn read_cards(&self, obd: &mut OBD) -> Result<Vec<(&'static str, Scalar)>, PidError> {
+        Ok(self.cards.iter().copied().zip(self.read(obd)?).collect())
+    }
+
+    /// Like `read_cards`, values that couldn't be read are no data.
+    pub fn decode_cards(&self, obd: &mut OBD) -> Vec<(&'static str, Scalar)> {
+        let values = self.read(obd).unwrap_or_default();
+        self.cards
+            .iter()
+            .enumerate()
+            .map(|(i, card)| (*card, values.get(i).cloned().unwrap_or(Scalar::no_data())))
+            .collect()
+    }
+}
+
+/// Look up a pid in `PIDS`.
+pub fn find(service: u8, pid: u8) -> Option<&'static PidDef> {
+    PIDS.iter()
+        .find(|def| def.service == service && def.pid == pid)
+}
+
+/// Look up a pid from its command (e.g "010C").
+pub fn find_command(command: &str) -> Option<&'static PidDef> {
+    let command = command.trim().replace(' ', "");
+    if command.len() != 4 {
+        return None;
+    }
+
+    let service = u8::from_str_radix(&command[..2], 16).ok()?;
+    let pid = u8::from_str_radix(&command[2..], 16).ok()?;
+    find(service, pid)
+}
+
+/// Pids polled at `poll`.
+pub fn polled(poll: Poll) -> impl Iterator<Item = &'static PidDef> {
+    PIDS.iter()
+        .filter(move |def| def.poll == poll && def.read.is_some())
+}
+
+pub const PIDS: &[PidDef] = &[
+    PidDef {
+        service: 0x01,
+        pid: 0x01,
+        bytes: 4,
+        name: "Monitor status since DTCs cleared",
+        unit: "",
+        formula: "",
+        range: None,
+        cards: &[],
+        poll: Poll::Never,
+        read: None,
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x02,
+        bytes: 2,
+        name: "DTC that caused freeze frame to be stored",
+        unit: "",
+        formula: "",
+        range: None,
+        cards: &[],
+        poll: Poll::Never,
+        read: None,
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x03,
+        bytes: 2,
+        name: "Fuel system status",
+        unit: "",
+        formula: "",
+        range: None,
+        cards: &[],
+        poll: Poll::Never,
+        read: None,
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x04,
+        bytes: 1,
+        name: "Engine load",
+        unit: "%",
+        formula: "100/255 * A",
+        range: Some((0.0, 100.0)),
+        cards: &["Engine Load"],
+        poll: Poll::High,
+        read: Some(|obd| vec![obd.engine_load()]),
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x05,
+        bytes: 1,
+        name: "Coolant temp.",
+        unit: "°C",
+        formula: "A - 40",
+        range: Some((-40.0, 215.0)),
+        cards: &["Coolant Temp."],
+        poll: Poll::LessFrequent,
+        read: Some(|obd| vec![obd.coolant_temp()]),
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x06,
+        bytes: 1,
+        name: "Short term fuel trim (Bank 1)",
+        unit: "%",
+        formula: "(100/128 * A) - 100",
+        range: Some((-100.0, 99.2)),
+        cards: &["Short Term Fuel Trim (Bank 1)"],
+        poll: Poll::Frequent,
+        read: Some(|obd| vec![obd.short_term_fuel_trim(&BankNumber::Bank1)]),
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x07,
+        bytes: 1,
+        name: "Long term fuel trim (Bank 1)",
+        unit: "%",
+        formula: "(100/128 * A) - 100",
+        range: Some((-100.0, 99.2)),
+        cards: &["Long Term Fuel Trim (Bank 1)"],
+        poll: Poll::LessFrequent,
+        read: Some(|obd| vec![obd.long_term_fuel_trim(&BankNumber::Bank1)]),
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x08,
+        bytes: 1,
+        name: "Short term fuel trim (Bank 2)",
+        unit: "%",
+        formula: "(100/128 * A) - 100",
+        range: Some((-100.0, 99.2)),
+        cards: &["Short Term Fuel Trim (Bank 2)"],
+        poll: Poll::Frequent,
+        read: Some(|obd| vec![obd.short_term_fuel_trim(&BankNumber::Bank2)]),
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x09,
+        bytes: 1,
+        name: "Long term fuel trim (Bank 2)",
+        unit: "%",
+        formula: "(100/128 * A) - 100",
+        range: Some((-100.0, 99.2)),
+        cards: &["Long Term Fuel Trim (Bank 2)"],
+        poll: Poll::LessFrequent,
+        read: Some(|obd| vec![obd.long_term_fuel_trim(&BankNumber::Bank2)]),
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x0A,
+        bytes: 1,
+        name: "Fuel pressure",
+        unit: "kPa",
+        formula: "3 * A",
+        range: Some((0.0, 765.0)),
+        cards: &["Fuel Pressure"],
+        poll: Poll::High,
+        read: Some(|obd| vec![obd.fuel_pressure()]),
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x0B,
+        bytes: 1,
+        name: "Intake manifold abs. pressure",
+        unit: "kPa",
+        formula: "A",
+        range: Some((0.0, 255.0)),
+        cards: &["Intake Manifold Abs. Pressure"],
+        poll: Poll::Frequent,
+        read: Some(|obd| vec![obd.intake_manifold_abs_pressure()]),
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x0C,
+        bytes: 2,
+        name: "Engine speed",
+        unit: "RPM",
+        formula: "((256 * A)+B) / 4",
+        range: Some((0.0, 16383.75)),
+        cards: &["Engine Speed"],
+        poll: Poll::Critical,
+        read: Some(|obd| vec![obd.rpm()]),
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x0D,
+        bytes: 1,
+        name: "Vehicle speed",
+        unit: "km/h",
+        formula: "A",
+        range: Some((0.0, 255.0)),
+        cards: &["Vehicle Speed"],
+        poll: Poll::Critical,
+        read: Some(|obd| vec![obd.vehicle_speed()]),
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x0E,
+        bytes: 1,
+        name: "Timing advance",
+        unit: "°",
+        formula: "A/2 - 64",
+        range: Some((-64.0, 63.5)),
+        cards: &["Timing Advance"],
+        poll: Poll::High,
+        read: Some(|obd| vec![obd.timing_advance()]),
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x0F,
+        bytes: 1,
+        name: "Intake air temp.",
+        unit: "°C",
+        formula: "A - 40",
+        range: Some((-40.0, 215.0)),
+        cards: &["Intake Air Temp."],
+        poll: Poll::Frequent,
+        read: Some(|obd| vec![obd.intake_air_temp()]),
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x10,
+        bytes: 2,
+        name: "MAF airflow rate",
+        unit: "g/s",
+        formula: "((256 * A)+B) / 100",
+        range: Some((0.0, 655.35)),
+        cards: &["MAF Airflow Rate"],
+        poll: Poll::High,
+        read: Some(|obd| vec![obd.maf_air_flow_rate()]),
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x11,
+        bytes: 1,
+        name: "Throttle pos.",
+        unit: "%",
+        formula: "100/255 * A",
+        range: Some((0.0, 100.0)),
+        cards: &["Throttle Pos."],
+        poll: Poll::High,
+        read: Some(|obd| vec![obd.throttle_position()]),
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x12,
+        bytes: 1,
+        name: "Commanded secondary air status",
+        unit: "",
+        formula: "",
+        range: None,
+        cards: &[],
+        poll: Poll::Never,
+        read: None,
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x13,
+        bytes: 1,
+        name: "Oxygen sensors present (in 2 banks)",
+        unit: "",
+        formula: "",
+        range: None,
+        cards: &[],
+        poll: Poll::Never,
+        read: None,
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x14,
+        bytes: 2,
+        name: "Oxygen Sensor 1 (A: Voltage B: STFT)",
+        unit: "(V, %)",
+        formula: "V: A / 200 %: 100/128B - 100",
+        range: None,
+        cards: &["O2 Sensor (1) Voltage (1)", "O2 Sensor (1) STFT"],
+        poll: Poll::Never,
+        read: Some(|obd| {
+            let (voltage, trim) = obd.read_oxygen_sensor(&SensorNumber::Sensor1);
+            vec![voltage, trim]
+        }),
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x15,
+        bytes: 2,
+        name: "Oxygen Sensor 2 (A: Voltage B: STFT)",
+        unit: "(V, %)",
+        formula: "V: A / 200 %: 100/128B - 100",
+        range: None,
+        cards: &["O2 Sensor (2) Voltage (1)", "O2 Sensor (2) STFT"],
+        poll: Poll::Never,
+        read: Some(|obd| {
+            let (voltage, trim) = obd.read_oxygen_sensor(&SensorNumber::Sensor2);
+            vec![voltage, trim]
+        }),
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x16,
+        bytes: 2,
+        name: "Oxygen Sensor 3 (A: Voltage B: STFT)",
+        unit: "(V, %)",
+        formula: "V: A / 200 %: 100/128B - 100",
+        range: None,
+        cards: &["O2 Sensor (3) Voltage (1)", "O2 Sensor (3) STFT"],
+        poll: Poll::Never,
+        read: Some(|obd| {
+            let (voltage, trim) = obd.read_oxygen_sensor(&SensorNumber::Sensor3);
+            vec![voltage, trim]
+        }),
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x17,
+        bytes: 2,
+        name: "Oxygen Sensor 4 (A: Voltage B: STFT)",
+        unit: "(V, %)",
+        formula: "V: A / 200 %: 100/128B - 100",
+        range: None,
+        cards: &["O2 Sensor (4) Voltage (1)", "O2 Sensor (4) STFT"],
+        poll: Poll::Never,
+        read: Some(|obd| {
+            let (voltage, trim) = obd.read_oxygen_sensor(&SensorNumber::Sensor4);
+            vec![voltage, trim]
+        }),
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x18,
+        bytes: 2,
+        name: "Oxygen Sensor 5 (A: Voltage B: STFT)",
+        unit: "(V, %)",
+        formula: "V: A / 200 %: 100/128B - 100",
+        range: None,
+        cards: &["O2 Sensor (5) Voltage (1)", "O2 Sensor (5) STFT"],
+        poll: Poll::Never,
+        read: Some(|obd| {
+            let (voltage, trim) = obd.read_oxygen_sensor(&SensorNumber::Sensor5);
+            vec![voltage, trim]
+        }),
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x19,
+        bytes: 2,
+        name: "Oxygen Sensor 6 (A: Voltage B: STFT)",
+        unit: "(V, %)",
+        formula: "V: A / 200 %: 100/128B - 100",
+        range: None,
+        cards: &["O2 Sensor (6) Voltage (1)", "O2 Sensor (6) STFT"],
+        poll: Poll::Never,
+        read: Some(|obd| {
+            let (voltage, trim) = obd.read_oxygen_sensor(&SensorNumber::Sensor6);
+            vec![voltage, trim]
+        }),
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x1A,
+        bytes: 2,
+        name: "Oxygen Sensor 7 (A: Voltage B: STFT)",
+        unit: "(V, %)",
+        formula: "V: A / 200 %: 100/128B - 100",
+        range: None,
+        cards: &["O2 Sensor (7) Voltage (1)", "O2 Sensor (7) STFT"],
+        poll: Poll::Never,
+        read: Some(|obd| {
+            let (voltage, trim) = obd.read_oxygen_sensor(&SensorNumber::Sensor7);
+            vec![voltage, trim]
+        }),
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x1B,
+        bytes: 2,
+        name: "Oxygen Sensor 8 (A: Voltage B: STFT)",
+        unit: "(V, %)",
+        formula: "V: A / 200 %: 100/128B - 100",
+        range: None,
+        cards: &["O2 Sensor (8) Voltage (1)", "O2 Sensor (8) STFT"],
+        poll: Poll::Never,
+        read: Some(|obd| {
+            let (voltage, trim) = obd.read_oxygen_sensor(&SensorNumber::Sensor8);
+            vec![voltage, trim]
+        }),
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x1C,
+        bytes: 1,
+        name: "OBD standards this vehicle conforms to",
+        unit: "",
+        formula: "",
+        range: None,
+        cards: &[],
+        poll: Poll::Never,
+        read: None,
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x1D,
+        bytes: 1,
+        name: "Oxygen sensors present (in 4 banks)",
+        unit: "",
+        formula: "",
+        range: None,
+        cards: &[],
+        poll: Poll::Never,
+        read: None,
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x1E,
+        bytes: 1,
+        name: "Aux input status",
+        unit: "",
+        formula: "",
+        range: None,
+        cards: &[],
+        poll: Poll::Never,
+        read: None,
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x1F,
+        bytes: 2,
+        name: "Engine runtime (Session)",
+        unit: "s",
+        formula: "(256 * A) + B",
+        range: Some((0.0, 65535.0)),
+        cards: &["Engine Runtime (Session)"],
+        poll: Poll::LessFrequent,
+        read: Some(|obd| vec![obd.engine_runtime()]),
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x21,
+        bytes: 2,
+        name: "Dist. with check engine light",
+        unit: "km",
+        formula: "(256 * A) + B",
+        range: Some((0.0, 65535.0)),
+        cards: &["Dist. With Check Engine Light"],
+        poll: Poll::Once,
+        read: Some(|obd| vec![obd.distance_traveled_with_mil()]),
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x22,
+        bytes: 2,
+        name: "Fuel Rail Pressure",
+        unit: "kPa",
+        formula: "0.079(256A + B)",
+        range: Some((0.0, 5177.265)),
+        cards: &["Fuel Rail Pressure"],
+        poll: Poll::High,
+        read: Some(|obd| vec![obd.fuel_rail_pressure()]),
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x23,
+        bytes: 2,
+        name: "Fuel Rail Gauge Pressure",
+        unit: "kPa",
+        formula: "10(256A + B)",
+        range: Some((0.0, 655350.0)),
+        cards: &["Fuel Rail Gauge Pressure"],
+        poll: Poll::High,
+        read: Some(|obd| vec![obd.fuel_rail_guage_pressure()]),
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x24,
+        bytes: 4,
+        name: "Oxygen Sensor 1 (AB: AFR CD: Voltage)",
+        unit: "(ratio, V)",
+        formula: "ratio: 2/65536(256A+B) V: 8/65536(256C+D)",
+        range: None,
+        cards: &["O2 Sensor (1) AFR", "O2 Sensor (1) Voltage (2)"],
+        poll: Poll::Never,
+        read: Some(|obd| {
+            let scaling = obd.oxygen_sensor_scaling();
+            let (ratio, voltage) = obd.read_oxygen_sensor_abcd(&SensorNumber::Sensor1, &scaling);
+            vec![ratio, voltage]
+        }),
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x25,
+        bytes: 4,
+        name: "Oxygen Sensor 2 (AB: AFR CD: Voltage)",
+        unit: "(ratio, V)",
+        formula: "ratio: 2/65536(256A+B) V: 8/65536(256C+D)",
+        range: None,
+        cards: &["O2 Sensor (2) AFR", "O2 Sensor (2) Voltage (2)"],
+        poll: Poll::Never,
+        read: Some(|obd| {
+            let scaling = obd.oxygen_sensor_scaling();
+            let (ratio, voltage) = obd.read_oxygen_sensor_abcd(&SensorNumber::Sensor2, &scaling);
+            vec![ratio, voltage]
+        }),
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x26,
+        bytes: 4,
+        name: "Oxygen Sensor 3 (AB: AFR CD: Voltage)",
+        unit: "(ratio, V)",
+        formula: "ratio: 2/65536(256A+B) V: 8/65536(256C+D)",
+        range: None,
+        cards: &["O2 Sensor (3) AFR", "O2 Sensor (3) Voltage (2)"],
+        poll: Poll::Never,
+        read: Some(|obd| {
+            let scaling = obd.oxygen_sensor_scaling();
+            let (ratio, voltage) = obd.read_oxygen_sensor_abcd(&SensorNumber::Sensor3, &scaling);
+            vec![ratio, voltage]
+        }),
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x27,
+        bytes: 4,
+        name: "Oxygen Sensor 4 (AB: AFR CD: Voltage)",
+        unit: "(ratio, V)",
+        formula: "ratio: 2/65536(256A+B) V: 8/65536(256C+D)",
+        range: None,
+        cards: &["O2 Sensor (4) AFR", "O2 Sensor (4) Voltage (2)"],
+        poll: Poll::Never,
+        read: Some(|obd| {
+            let scaling = obd.oxygen_sensor_scaling();
+            let (ratio, voltage) = obd.read_oxygen_sensor_abcd(&SensorNumber::Sensor4, &scaling);
+            vec![ratio, voltage]
+        }),
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x28,
+        bytes: 4,
+        name: "Oxygen Sensor 5 (AB: AFR CD: Voltage)",
+        unit: "(ratio, V)",
+        formula: "ratio: 2/65536(256A+B) V: 8/65536(256C+D)",
+        range: None,
+        cards: &["O2 Sensor (5) AFR", "O2 Sensor (5) Voltage (2)"],
+        poll: Poll::Never,
+        read: Some(|obd| {
+            let scaling = obd.oxygen_sensor_scaling();
+            let (ratio, voltage) = obd.read_oxygen_sensor_abcd(&SensorNumber::Sensor5, &scaling);
+            vec![ratio, voltage]
+        }),
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x29,
+        bytes: 4,
+        name: "Oxygen Sensor 6 (AB: AFR CD: Voltage)",
+        unit: "(ratio, V)",
+        formula: "ratio: 2/65536(256A+B) V: 8/65536(256C+D)",
+        range: None,
+        cards: &["O2 Sensor (6) AFR", "O2 Sensor (6) Voltage (2)"],
+        poll: Poll::Never,
+        read: Some(|obd| {
+            let scaling = obd.oxygen_sensor_scaling();
+            let (ratio, voltage) = obd.read_oxygen_sensor_abcd(&SensorNumber::Sensor6, &scaling);
+            vec![ratio, voltage]
+        }),
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x2A,
+        bytes: 4,
+        name: "Oxygen Sensor 7 (AB: AFR CD: Voltage)",
+        unit: "(ratio, V)",
+        formula: "ratio: 2/65536(256A+B) V: 8/65536(256C+D)",
+        range: None,
+        cards: &["O2 Sensor (7) AFR", "O2 Sensor (7) Voltage (2)"],
+        poll: Poll::Never,
+        read: Some(|obd| {
+            let scaling = obd.oxygen_sensor_scaling();
+            let (ratio, voltage) = obd.read_oxygen_sensor_abcd(&SensorNumber::Sensor7, &scaling);
+            vec![ratio, voltage]
+        }),
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x2B,
+        bytes: 4,
+        name: "Oxygen Sensor 8 (AB: AFR CD: Voltage)",
+        unit: "(ratio, V)",
+        formula: "ratio: 2/65536(256A+B) V: 8/65536(256C+D)",
+        range: None,
+        cards: &["O2 Sensor (8) AFR", "O2 Sensor (8) Voltage (2)"],
+        poll: Poll::Never,
+        read: Some(|obd| {
+            let scaling = obd.oxygen_sensor_scaling();
+            let (ratio, voltage) = obd.read_oxygen_sensor_abcd(&SensorNumber::Sensor8, &scaling);
+            vec![ratio, voltage]
+        }),
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x2C,
+        bytes: 1,
+        name: "Commanded EGR",
+        unit: "%",
+        formula: "100/255 * A",
+        range: Some((0.0, 100.0)),
+        cards: &["Commanded EGR"],
+        poll: Poll::LessFrequent,
+        read: Some(|obd| vec![obd.commanded_egr()]),
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x2D,
+        bytes: 1,
+        name: "EGR Error",
+        unit: "%",
+        formula: "(100/128 * A) - 100",
+        range: Some((-100.0, 99.2)),
+        cards: &["EGR Error"],
+        poll: Poll::LessFrequent,
+        read: Some(|obd| vec![obd.egr_error()]),
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x2E,
+        bytes: 1,
+        name: "Commanded EVAP purge",
+        unit: "%",
+        formula: "100/255 * A",
+        range: Some((0.0, 100.0)),
+        cards: &["Commanded EVAP Purge"],
+        poll: Poll::LessFrequent,
+        read: Some(|obd| vec![obd.commanded_evap_purge()]),
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x2F,
+        bytes: 1,
+        name: "Fuel Tank Level Input",
+        unit: "%",
+        formula: "100/255 * A",
+        range: Some((0.0, 100.0)),
+        cards: &["Fuel Tank Level"],
+        poll: Poll::Never,
+        read: Some(|obd| vec![obd.fuel_tank_level()]),
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x30,
+        bytes: 1,
+        name: "Warm-ups since codes cleared",
+        unit: "",
+        formula: "A",
+        range: Some((0.0, 255.0)),
+        cards: &["Warm-Ups Since Codes Cleared"],
+        poll: Poll::Once,
+        read: Some(|obd| vec![obd.warm_ups_since_codes_cleared()]),
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x31,
+        bytes: 2,
+        name: "Dist. since codes cleared",
+        unit: "km",
+        formula: "(256 * A)+B",
+        range: Some((0.0, 65535.0)),
+        cards: &["Dist. Since Codes Cleared"],
+        poll: Poll::Once,
+        read: Some(|obd| vec![obd.distance_traveled_since_codes_cleared()]),
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x32,
+        bytes: 2,
+        name: "EVAP System Vapor Pressure",
+        unit: "Pa",
+        formula: "((256 * A)+B) / 4",
+        range: Some((-8192.0, 8191.75)),
+        cards: &["EVAP System Vapor Pressure"],
+        poll: Poll::LessFrequent,
+        read: Some(|obd| vec![obd.evap_system_vapor_pressure()]),
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x33,
+        bytes: 1,
+        name: "Absolute Barometric Pressure",
+        unit: "kPa",
+        formula: "A",
+        range: Some((0.0, 255.0)),
+        cards: &["Absolute Barometric Pressure"],
+        poll: Poll::LessFrequent,
+        read: Some(|obd| vec![obd.abs_barometric_pressure()]),
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x34,
+        bytes: 4,
+        name: "Oxygen Sensor 1 (AB: AFR CD: Current)",
+        unit: "(ratio, mA)",
+        formula: "(ratio: 2/65536(256A+B) mA: ((256C + D) / 256) - 128",
+        range: None,
+        cards: &["O2 Sensor (1) AFR", "O2 Sensor (1) Current"],
+        poll: Poll::Never,
+        read: Some(|obd| {
+            let scaling = obd.oxygen_sensor_scaling();
+            let (ratio, current) = obd.read_oxygen_sensor_current(&SensorNumber::Sensor1, &scaling);
+            vec![ratio, current]
+        }),
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x35,
+        bytes: 4,
+        name: "Oxygen Sensor 2 (AB: AFR CD: Current)",
+        unit: "(ratio, mA)",
+        formula: "(ratio: 2/65536(256A+B) mA: ((256C + D) / 256) - 128",
+        range: None,
+        cards: &["O2 Sensor (2) AFR", "O2 Sensor (2) Current"],
+        poll: Poll::Never,
+        read: Some(|obd| {
+            let scaling = obd.oxygen_sensor_scaling();
+            let (ratio, current) = obd.read_oxygen_sensor_current(&SensorNumber::Sensor2, &scaling);
+            vec![ratio, current]
+        }),
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x36,
+        bytes: 4,
+        name: "Oxygen Sensor 3 (AB: AFR CD: Current)",
+        unit: "(ratio, mA)",
+        formula: "(ratio: 2/65536(256A+B) mA: ((256C + D) / 256) - 128",
+        range: None,
+        cards: &["O2 Sensor (3) AFR", "O2 Sensor (3) Current"],
+        poll: Poll::Never,
+        read: Some(|obd| {
+            let scaling = obd.oxygen_sensor_scaling();
+            let (ratio, current) = obd.read_oxygen_sensor_current(&SensorNumber::Sensor3, &scaling);
+            vec![ratio, current]
+        }),
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x37,
+        bytes: 4,
+        name: "Oxygen Sensor 4 (AB: AFR CD: Current)",
+        unit: "(ratio, mA)",
+        formula: "(ratio: 2/65536(256A+B) mA: ((256C + D) / 256) - 128",
+        range: None,
+        cards: &["O2 Sensor (4) AFR", "O2 Sensor (4) Current"],
+        poll: Poll::Never,
+        read: Some(|obd| {
+            let scaling = obd.oxygen_sensor_scaling();
+            let (ratio, current) = obd.read_oxygen_sensor_current(&SensorNumber::Sensor4, &scaling);
+            vec![ratio, current]
+        }),
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x38,
+        bytes: 4,
+        name: "Oxygen Sensor 5 (AB: AFR CD: Current)",
+        unit: "(ratio, mA)",
+        formula: "(ratio: 2/65536(256A+B) mA: ((256C + D) / 256) - 128",
+        range: None,
+        cards: &["O2 Sensor (5) AFR", "O2 Sensor (5) Current"],
+        poll: Poll::Never,
+        read: Some(|obd| {
+            let scaling = obd.oxygen_sensor_scaling();
+            let (ratio, current) = obd.read_oxygen_sensor_current(&SensorNumber::Sensor5, &scaling);
+            vec![ratio, current]
+        }),
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x39,
+        bytes: 4,
+        name: "Oxygen Sensor 6 (AB: AFR CD: Current)",
+        unit: "(ratio, mA)",
+        formula: "(ratio: 2/65536(256A+B) mA: ((256C + D) / 256) - 128",
+        range: None,
+        cards: &["O2 Sensor (6) AFR", "O2 Sensor (6) Current"],
+        poll: Poll::Never,
+        read: Some(|obd| {
+            let scaling = obd.oxygen_sensor_scaling();
+            let (ratio, current) = obd.read_oxygen_sensor_current(&SensorNumber::Sensor6, &scaling);
+            vec![ratio, current]
+        }),
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x3A,
+        bytes: 4,
+        name: "Oxygen Sensor 7 (AB: AFR CD: Current)",
+        unit: "(ratio, mA)",
+        formula: "(ratio: 2/65536(256A+B) mA: ((256C + D) / 256) - 128",
+        range: None,
+        cards: &["O2 Sensor (7) AFR", "O2 Sensor (7) Current"],
+        poll: Poll::Never,
+        read: Some(|obd| {
+            let scaling = obd.oxygen_sensor_scaling();
+            let (ratio, current) = obd.read_oxygen_sensor_current(&SensorNumber::Sensor7, &scaling);
+            vec![ratio, current]
+        }),
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x3B,
+        bytes: 4,
+        name: "Oxygen Sensor 8 (AB: AFR CD: Current)",
+        unit: "(ratio, mA)",
+        formula: "(ratio: 2/65536(256A+B) mA: ((256C + D) / 256) - 128",
+        range: None,
+        cards: &["O2 Sensor (8) AFR", "O2 Sensor (8) Current"],
+        poll: Poll::Never,
+        read: Some(|obd| {
+            let scaling = obd.oxygen_sensor_scaling();
+            let (ratio, current) = obd.read_oxygen_sensor_current(&SensorNumber::Sensor8, &scaling);
+            vec![ratio, current]
+        }),
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x3C,
+        bytes: 2,
+        name: "Catalyst Temp. (Bank 1: Sensor 1)",
+        unit: "°C",
+        formula: "(((256 * A)+B) / 10) - 40",
+        range: Some((-40.0, 6513.5)),
+        cards: &["Catalyst Temp. (Bank 1: Sensor 1)"],
+        poll: Poll::LessFrequent,
+        read: Some(|obd| vec![obd.catalyst_temp(BankNumber::Bank1, SensorNumber::Sensor1)]),
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x3D,
+        bytes: 2,
+        name: "Catalyst Temp. (Bank 2: Sensor 1)",
+        unit: "°C",
+        formula: "(((256 * A)+B) / 10) - 40",
+        range: Some((-40.0, 6513.5)),
+        cards: &["Catalyst Temp. (Bank 2: Sensor 1)"],
+        poll: Poll::LessFrequent,
+        read: Some(|obd| vec![obd.catalyst_temp(BankNumber::Bank2, SensorNumber::Sensor1)]),
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x3E,
+        bytes: 2,
+        name: "Catalyst Temp. (Bank 1: Sensor 2)",
+        unit: "°C",
+        formula: "(((256 * A)+B) / 10) - 40",
+        range: Some((-40.0, 6513.5)),
+        cards: &["Catalyst Temp. (Bank 1: Sensor 2)"],
+        poll: Poll::LessFrequent,
+        read: Some(|obd| vec![obd.catalyst_temp(BankNumber::Bank1, SensorNumber::Sensor2)]),
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x3F,
+        bytes: 2,
+        name: "Catalyst Temp. (Bank 2: Sensor 2)",
+        unit: "°C",
+        formula: "(((256 * A)+B) / 10) - 40",
+        range: Some((-40.0, 6513.5)),
+        cards: &["Catalyst Temp. (Bank 2: Sensor 2)"],
+        poll: Poll::LessFrequent,
+        read: Some(|obd| vec![obd.catalyst_temp(BankNumber::Bank2, SensorNumber::Sensor2)]),
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x41,
+        bytes: 4,
+        name: "Monitor status this drive cycle",
+        unit: "",
+        formula: "",
+        range: None,
+        cards: &[],
+        poll: Poll::Never,
+        read: None,
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x42,
+        bytes: 2,
+        name: "Control module voltage",
+        unit: "V",
+        formula: "((256 * A)+B) / 1000",
+        range: Some((0.0, 65.535)),
+        cards: &["Control Module Voltage"],
+        poll: Poll::LessFrequent,
+        read: Some(|obd| vec![obd.control_module_voltage()]),
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x43,
+        bytes: 2,
+        name: "Absolute load value",
+        unit: "%",
+        formula: "(100/255) * (256A + B)",
+        range: None,
+        cards: &[],
+        poll: Poll::Never,
+        read: None,
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x44,
+        bytes: 2,
+        name: "Commanded Air-Fuel Equivalence Ratio",
+        unit: "ratio",
+        formula: "(2/65536) * (256A + B)",
+        range: None,
+        cards: &[],
+        poll: Poll::Never,
+        read: None,
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x45,
+        bytes: 1,
+        name: "Relative throttle pos.",
+        unit: "%",
+        formula: "100/255 * A",
+        range: Some((0.0, 100.0)),
+        cards: &["Relative Throttle Pos."],
+        poll: Poll::High,
+        read: Some(|obd| vec![obd.relative_throttle_pos()]),
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x46,
+        bytes: 1,
+        name: "Ambient air temp.",
+        unit: "°C",
+        formula: "A - 40",
+        range: Some((-40.0, 215.0)),
+        cards: &["Ambient Air Temp."],
+        poll: Poll::LessFrequent,
+        read: Some(|obd| vec![obd.ambient_air_temp()]),
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x47,
+        bytes: 1,
+        name: "Abs. throttle pos. (B)",
+        unit: "%",
+        formula: "100/255 * A",
+        range: Some((0.0, 100.0)),
+        cards: &["Abs. Throttle Pos. (B)"],
+        poll: Poll::High,
+        read: Some(|obd| vec![obd.abs_throttle_position_b()]),
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x48,
+        bytes: 1,
+        name: "Abs. throttle pos. (C)",
+        unit: "%",
+        formula: "100/255 * A",
+        range: Some((0.0, 100.0)),
+        cards: &["Abs. Throttle Pos. (C)"],
+        poll: Poll::High,
+        read: Some(|obd| vec![obd.abs_throttle_position_c()]),
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x49,
+        bytes: 1,
+        name: "Accelerator pedal pos. (D)",
+        unit: "%",
+        formula: "100/255 * A",
+        range: Some((0.0, 100.0)),
+        cards: &["Accelerator Pedal Pos. (D)"],
+        poll: Poll::High,
+        read: Some(|obd| vec![obd.acc_pedal_position_d()]),
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x4A,
+        bytes: 1,
+        name: "Accelerator pedal pos. (E)",
+        unit: "%",
+        formula: "100/255 * A",
+        range: Some((0.0, 100.0)),
+        cards: &["Accelerator Pedal Pos. (E)"],
+        poll: Poll::High,
+        read: Some(|obd| vec![obd.acc_pedal_position_e()]),
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x4B,
+        bytes: 1,
+        name: "Accelerator pedal pos. (F)",
+        unit: "%",
+        formula: "100/255 * A",
+        range: Some((0.0, 100.0)),
+        cards: &["Accelerator Pedal Pos. (F)"],
+        poll: Poll::High,
+        read: Some(|obd| vec![obd.acc_pedal_position_f()]),
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x4D,
+        bytes: 2,
+        name: "Time with check engine light",
+        unit: "mins",
+        formula: "256A + B",
+        range: Some((0.0, 65535.0)),
+        cards: &["Time With Check Engine Light"],
+        poll: Poll::Once,
+        read: Some(|obd| vec![obd.time_run_with_mil()]),
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x4E,
+        bytes: 2,
+        name: "Time since codes cleared",
+        unit: "mins",
+        formula: "256A + B",
+        range: Some((0.0, 65535.0)),
+        cards: &["Time Since Codes Cleared"],
+        poll: Poll::Once,
+        read: Some(|obd| vec![obd.time_since_codes_cleared()]),
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x4F,
+        bytes: 4,
+        name:
+            "Max. value for AFR, O2 sensor voltage and current, and intake manifold abs. pressure",
+        unit: "ratio, V, mA, kPa",
+        formula: "A, B, C, D * 10",
+        range: None,
+        cards: &[
+            "Maximum AFR Value",
+            "Maximum O2 Sensor Voltage",
+            "Maximum O2 Sensor Current",
+            "Maximum Intake Abs. Pressure",
+        ],
+        poll: Poll::LessFrequent,
+        read: Some(|obd| {
+            let (ratio, voltage, current, pressure) = obd.max_values_for();
+            vec![ratio, voltage, current, pressure]
+        }),
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x50,
+        bytes: 4,
+        name: "MAF maximum airflow rate",
+        unit: "g/s",
+        formula: "A * 10",
+        range: Some((0.0, 2550.0)),
+        cards: &["MAF Maximum Airflow Rate"],
+        poll: Poll::High,
+        read: Some(|obd| vec![obd.max_air_flow_rate_from_maf()]),
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x51,
+        bytes: 1,
+        name: "Fuel Type",
+        unit: "",
+        formula: "",
+        range: None,
+        cards: &[],
+        poll: Poll::Never,
+        read: None,
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x52,
+        bytes: 1,
+        name: "Ethanol fuel percentage",
+        unit: "%",
+        formula: "100/255 * A",
+        range: Some((0.0, 100.0)),
+        cards: &["Ethanol Fuel Percentage"],
+        poll: Poll::Once,
+        read: Some(|obd| vec![obd.ethanol_fuel_percentage()]),
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x53,
+        bytes: 2,
+        name: "Absolute Evap system Vapor Pressure",
+        unit: "kPa",
+        formula: "((256 * A)+B) / 200",
+        range: None,
+        cards: &[],
+        poll: Poll::Never,
+        read: None,
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x54,
+        bytes: 2,
+        name: "Evap system vapor pressure",
+        unit: "Pa",
+        formula: "(256 * A) + B",
+        range: None,
+        cards: &[],
+        poll: Poll::Never,
+        read: None,
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x55,
+        bytes: 2,
+        name: "Short term secondary oxygen sensor trim, A: bank 1, B: bank 3",
+        unit: "%",
+        formula: "100/128(A OR B) - 100",
+        range: None,
+        cards: &[],
+        poll: Poll::Never,
+        read: None,
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x56,
+        bytes: 2,
+        name: "Long term secondary oxygen sensor trim, A: bank 1, B: bank 3",
+        unit: "%",
+        formula: "100/128(A OR B) - 100",
+        range: None,
+        cards: &[],
+        poll: Poll::Never,
+        read: None,
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x57,
+        bytes: 2,
+        name: "Short term secondary oxygen sensor trim, A: bank 2, B: bank 4",
+        unit: "%",
+        formula: "100/128(A OR B) - 100",
+        range: None,
+        cards: &[],
+        poll: Poll::Never,
+        read: None,
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x58,
+        bytes: 2,
+        name: "Long term secondary oxygen sensor trim, A: bank 2, B: bank 4",
+        unit: "%",
+        formula: "100/128(A OR B) - 100",
+        range: None,
+        cards: &[],
+        poll: Poll::Never,
+        read: None,
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x59,
+        bytes: 2,
+        name: "Fuel rail absolute pressure",
+        unit: "kPa",
+        formula: "10(256A + B)",
+        range: None,
+        cards: &[],
+        poll: Poll::Never,
+        read: None,
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x5A,
+        bytes: 1,
+        name: "Relative accelerator pedal position",
+        unit: "%",
+        formula: "100/255 * A",
+        range: None,
+        cards: &[],
+        poll: Poll::Never,
+        read: None,
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x5B,
+        bytes: 1,
+        name: "Hybrid battery pack remaining life",
+        unit: "%",
+        formula: "100/255 * A",
+        range: Some((0.0, 100.0)),
+        cards: &["Hybrid Battery Remaining Life"],
+        poll: Poll::Never,
+        read: Some(|obd| vec![obd.hybrid_battery_remaining_life()]),
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x5C,
+        bytes: 1,
+        name: "Engine oil temp. (mode 01)",
+        unit: "°C",
+        formula: "A - 40",
+        range: Some((-40.0, 210.0)),
+        cards: &["Engine Oil Temp. (Mode 01)"],
+        poll: Poll::LessFrequent,
+        read: Some(|obd| vec![obd.engine_oil_temp(Service::Mode01)]),
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x5D,
+        bytes: 2,
+        name: "Fuel injection timing",
+        unit: "°",
+        formula: "(((256 * A)+B) / 128) - 210",
+        range: Some((-210.0, 301.992)),
+        cards: &["Fuel Injection Timing"],
+        poll: Poll::LessFrequent,
+        read: Some(|obd| vec![obd.fuel_injection_timing()]),
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x5E,
+        bytes: 2,
+        name: "Engine fuel rate",
+        unit: "L/h",
+        formula: "((256 * A)+B) / 20",
+        range: None,
+        cards: &[],
+        poll: Poll::Never,
+        read: None,
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x5F,
+        bytes: 1,
+        name: "Emission requirements to which vehicle is designed",
+        unit: "",
+        formula: "",
+        range: None,
+        cards: &[],
+        poll: Poll::Never,
+        read: None,
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x61,
+        bytes: 1,
+        name: "Drivers demand engine torque",
+        unit: "%",
+        formula: "A - 125",
+        range: Some((-125.0, 130.0)),
+        cards: &["Drivers Demand Engine Torque"],
+        poll: Poll::High,
+        read: Some(|obd| vec![obd.drivers_demand_engine_torque()]),
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x62,
+        bytes: 1,
+        name: "Actual engine torque",
+        unit: "%",
+        formula: "A - 125",
+        range: Some((-125.0, 130.0)),
+        cards: &["Actual Engine Torque"],
+        poll: Poll::High,
+        read: Some(|obd| vec![obd.actual_engine_torque()]),
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x63,
+        bytes: 2,
+        name: "Reference engine torque",
+        unit: "Nm",
+        formula: "256A + B",
+        range: Some((0.0, 65535.0)),
+        cards: &["Reference Engine Torque"],
+        poll: Poll::High,
+        read: Some(|obd| vec![obd.reference_engine_torque()]),
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x64,
+        bytes: 5,
+        name: "Engine percent torque data",
+        unit: "%",
+        formula: "Subtract 125 from A - E",
+        range: Some((-125.0, 130.0)),
+        cards: &[
+            "Idle Engine Torque",
+            "Engine Point 1 Torque",
+            "Engine Point 2 Torque",
+            "Engine Point 3 Torque",
+            "Engine Point 4 Torque",
+        ],
+        poll: Poll::High,
+        read: Some(|obd| {
+            let (idle, point_1, point_2, point_3, point_4) = obd.engine_percent_torque_data();
+            vec![idle, point_1, point_2, point_3, point_4]
+        }),
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x65,
+        bytes: 2,
+        name: "Auxiliary input / output supported",
+        unit: "",
+        formula: "",
+        range: None,
+        cards: &[],
+        poll: Poll::Never,
+        read: None,
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x66,
+        bytes: 5,
+        name: "Mass air flow sensor",
+        unit: "g/s",
+        formula: "{A0}== Sensor A Supported",
+        range: None,
+        cards: &["MAF Airflow Rate (Sensor A)", "MAF Airflow Rate (Sensor B)"],
+        poll: Poll::Never,
+        read: Some(|obd| {
+            let (a, b) = obd.read_mass_air_flow_sensor();
+            vec![a, b]
+        }),
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x67,
+        bytes: 3,
+        name: "Engine coolant temperature",
+        unit: "°C",
+        formula: "{A0}== Sensor 1 Supported",
+        range: Some((-40.0, 215.0)),
+        cards: &["Coolant Temp. (Sensors: A)", "Coolant Temp. (Sensors: B)"],
+        poll: Poll::LessFrequent,
+        read: Some(|obd| {
+            let (a, b) = obd.coolant_temp_sensors();
+            vec![a, b]
+        }),
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x68,
+        bytes: 7,
+        name: "Intake air temperature sensor",
+        unit: "°C",
+        formula: "{A0}== Sensor 1 Supported",
+        range: None,
+        cards: &[],
+        poll: Poll::Never,
+        read: None,
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x6A,
+        bytes: 5,
+        name: "Commanded Diesel intake air flow control and relative intake air flow position",
+        unit: "",
+        formula: "",
+        range: None,
+        cards: &[],
+        poll: Poll::Never,
+        read: None,
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x6B,
+        bytes: 5,
+        name: "Exhaust gas recirculation temperature",
+        unit: "",
+        formula: "",
+        range: None,
+        cards: &[],
+        poll: Poll::Never,
+        read: None,
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x6C,
+        bytes: 5,
+        name: "Commanded throttle actuator control and relative throttle position",
+        unit: "",
+        formula: "",
+        range: None,
+        cards: &[],
+        poll: Poll::Never,
+        read: None,
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x6D,
+        bytes: 11,
+        name: "Fuel pressure control system",
+        unit: "",
+        formula: "",
+        range: None,
+        cards: &[],
+        poll: Poll::Never,
+        read: None,
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x6E,
+        bytes: 9,
+        name: "Injection pressure control system",
+        unit: "",
+        formula: "",
+        range: None,
+        cards: &[],
+        poll: Poll::Never,
+        read: None,
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x6F,
+        bytes: 3,
+        name: "Turbocharger compressor inlet pressure",
+        unit: "",
+        formula: "",
+        range: None,
+        cards: &[],
+        poll: Poll::Never,
+        read: None,
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x70,
+        bytes: 10,
+        name: "Boost pressure control",
+        unit: "",
+        formula: "",
+        range: None,
+        cards: &[],
+        poll: Poll::Never,
+        read: None,
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x71,
+        bytes: 6,
+        name: "Variable Geometry turbo (VGT) control",
+        unit: "",
+        formula: "",
+        range: None,
+        cards: &[],
+        poll: Poll::Never,
+        read: None,
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x72,
+        bytes: 5,
+        name: "Wastegate control",
+        unit: "",
+        formula: "",
+        range: None,
+        cards: &[],
+        poll: Poll::Never,
+        read: None,
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x73,
+        bytes: 5,
+        name: "Exhaust pressure",
+        unit: "",
+        formula: "",
+        range: None,
+        cards: &[],
+        poll: Poll::Never,
+        read: None,
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x74,
+        bytes: 5,
+        name: "Turbocharger RPM",
+        unit: "RPM",
+        formula: "",
+        range: None,
+        cards: &["Turbocharger RPM"],
+        poll: Poll::Critical,
+        read: Some(|obd| vec![obd.turbocharger_rpm()]),
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x75,
+        bytes: 7,
+        name: "Turbocharger temperature",
+        unit: "°C",
+        formula: "",
+        range: None,
+        cards: &[],
+        poll: Poll::Never,
+        read: None,
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x76,
+        bytes: 7,
+        name: "Turbocharger temperature",
+        unit: "°C",
+        formula: "",
+        range: None,
+        cards: &[],
+        poll: Poll::Never,
+        read: None,
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x77,
+        bytes: 5,
+        name: "Charge air cooler temperature (CACT)",
+        unit: "°C",
+        formula: "",
+        range: None,
+        cards: &[],
+        poll: Poll::Never,
+        read: None,
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x78,
+        bytes: 9,
+        name: "Exhaust Gas temperature (EGT) Bank 1",
+        unit: "°C",
+        formula: "",
+        range: None,
+        cards: &[
+            "Exhaust Gas Temp. (Bank 1: Sensor 1)",
+            "Exhaust Gas Temp. (Bank 1: Sensor 2)",
+            "Exhaust Gas Temp. (Bank 1: Sensor 3)",
+            "Exhaust Gas Temp. (Bank 1: Sensor 4)",
+        ],
+        poll: Poll::Never,
+        read: Some(|obd| obd.exhaust_gas_temp(BankNumber::Bank1).to_vec()),
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x79,
+        bytes: 9,
+        name: "Exhaust Gas temperature (EGT) Bank 2",
+        unit: "°C",
+        formula: "",
+        range: None,
+        cards: &[
+            "Exhaust Gas Temp. (Bank 2: Sensor 1)",
+            "Exhaust Gas Temp. (Bank 2: Sensor 2)",
+            "Exhaust Gas Temp. (Bank 2: Sensor 3)",
+            "Exhaust Gas Temp. (Bank 2: Sensor 4)",
+        ],
+        poll: Poll::Never,
+        read: Some(|obd| obd.exhaust_gas_temp(BankNumber::Bank2).to_vec()),
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x7A,
+        bytes: 7,
+        name: "Diesel particulate filter (DPF)",
+        unit: "",
+        formula: "",
+        range: None,
+        cards: &[
+            "DPF Delta Pressure (Bank 1)",
+            "DPF Inlet Pressure (Bank 1)",
+            "DPF Outlet Pressure (Bank 1)",
+        ],
+        poll: Poll::Never,
+        read: Some(|obd| {
+            let (delta, inlet, outlet) = obd.dpf_pressure(BankNumber::Bank1);
+            vec![delta, inlet, outlet]
+        }),
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x7B,
+        bytes: 7,
+        name: "Diesel particulate filter (DPF)",
+        unit: "",
+        formula: "",
+        range: None,
+        cards: &[
+            "DPF Delta Pressure (Bank 2)",
+            "DPF Inlet Pressure (Bank 2)",
+            "DPF Outlet Pressure (Bank 2)",
+        ],
+        poll: Poll::Never,
+        read: Some(|obd| {
+            let (delta, inlet, outlet) = obd.dpf_pressure(BankNumber::Bank2);
+            vec![delta, inlet, outlet]
+        }),
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x7C,
+        bytes: 9,
+        name: "Diesel Particulate filter (DPF) temperature",
+        unit: "°C",
+        formula: "(((256 * A)+B) / 10) - 40",
+        range: Some((-40.0, 6513.5)),
+        cards: &[
+            "DPF Inlet Temp. (Bank 1)",
+            "DPF Outlet Temp. (Bank 1)",
+            "DPF Inlet Temp. (Bank 2)",
+            "DPF Outlet Temp. (Bank 2)",
+        ],
+        poll: Poll::Never,
+        read: Some(|obd| {
+            let (b1_inlet, b1_outlet) = obd.dpf_temp(BankNumber::Bank1);
+            let (b2_inlet, b2_outlet) = obd.dpf_temp(BankNumber::Bank2);
+            vec![b1_inlet, b1_outlet, b2_inlet, b2_outlet]
+        }),
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x7D,
+        bytes: 1,
+        name: "NOx NTE",
+        unit: "",
+        formula: "",
+        range: None,
+        cards: &[],
+        poll: Poll::Never,
+        read: None,
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x7E,
+        bytes: 1,
+        name: "PM NTE",
+        unit: "",
+        formula: "",
+        range: None,
+        cards: &[],
+        poll: Poll::Never,
+        read: None,
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x7F,
+        bytes: 13,
+        name: "Engine runtime",
+        unit: "s",
+        formula: "B(2^24) + C(2^16) + D(2^8) + E",
+        range: None,
+        cards: &["Engine Run Time", "Engine Idle Time", "Engine PTO Time"],
+        poll: Poll::Never,
+        read: Some(|obd| {
+            let (total, idle, pto) = obd.engine_run_time_details();
+            vec![total, idle, pto]
+        }),
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x81,
+        bytes: 41,
+        name: "Engine runtime for Auxiliary Emissions Control Device(AECD)",
+        unit: "",
+        formula: "",
+        range: None,
+        cards: &[],
+        poll: Poll::Never,
+        read: None,
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x82,
+        bytes: 41,
+        name: "Engine runtime for Auxiliary Emissions Control Device(AECD)",
+        unit: "",
+        formula: "",
+        range: None,
+        cards: &[],
+        poll: Poll::Never,
+        read: None,
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x83,
+        bytes: 9,
+        name: "NOx sensor",
+        unit: "",
+        formula: "",
+        range: None,
+        cards: &[
+            "NOx (Bank 1: Sensor 1)",
+            "NOx (Bank 1: Sensor 2)",
+            "NOx (Bank 2: Sensor 1)",
+            "NOx (Bank 2: Sensor 2)",
+        ],
+        poll: Poll::Never,
+        read: Some(|obd| obd.nox_sensors().to_vec()),
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x84,
+        bytes: 1,
+        name: "Manifold surface temperature",
+        unit: "",
+        formula: "",
+        range: None,
+        cards: &[],
+        poll: Poll::Never,
+        read: None,
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x85,
+        bytes: 10,
+        name: "NOx reagent system",
+        unit: "%",
+        formula: "100/255 * F",
+        range: None,
+        cards: &[
+            "DEF Average Consumption",
+            "DEF Demanded Consumption",
+            "DEF Tank Level",
+            "NOx Warning Time",
+        ],
+        poll: Poll::Never,
+        read: Some(|obd| {
+            let reagent = obd.nox_reagent_system();
+            vec![
+                reagent.average_consumption,
+                reagent.demanded_consumption,
+                reagent.tank_level,
+                reagent.warning_time,
+            ]
+        }),
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x86,
+        bytes: 5,
+        name: "Particulate matter (PM) sensor",
+        unit: "",
+        formula: "",
+        range: None,
+        cards: &["PM Concentration (Bank 1)", "PM Concentration (Bank 2)"],
+        poll: Poll::Never,
+        read: Some(|obd| {
+            let (bank_1, bank_2) = obd.pm_sensors();
+            vec![bank_1, bank_2]
+        }),
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x87,
+        bytes: 5,
+        name: "Intake manifold abs. pressure (sensors A and B)",
+        unit: "kPa",
+        formula: "",
+        range: None,
+        cards: &[
+            "Intake Manifold Abs. Pressure (Sensor A)",
+            "Intake Manifold Abs. Pressure (Sensor B)",
+        ],
+        poll: Poll::Never,
+        read: Some(|obd| {
+            let (a, b) = obd.intake_manifold_abs_pressure_sensors();
+            vec![a, b]
+        }),
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x88,
+        bytes: 13,
+        name: "SCR Induce System",
+        unit: "",
+        formula: "",
+        range: None,
+        cards: &[],
+        poll: Poll::Never,
+        read: None,
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x89,
+        bytes: 41,
+        name: "Run Time for AECD #11-#15",
+        unit: "",
+        formula: "",
+        range: None,
+        cards: &[],
+        poll: Poll::Never,
+        read: None,
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x8A,
+        bytes: 41,
+        name: "Run Time for AECD #16-#20",
+        unit: "",
+        formula: "",
+        range: None,
+        cards: &[],
+        poll: Poll::Never,
+        read: None,
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x8B,
+        bytes: 7,
+        name: "Diesel Aftertreatment",
+        unit: "",
+        formula: "",
+        range: None,
+        cards: &[
+            "DPF Regeneration Trigger",
+            "DPF Avg. Time Between Regenerations",
+            "DPF Avg. Dist. Between Regenerations",
+        ],
+        poll: Poll::Never,
+        read: Some(|obd| {
+            let regeneration = obd.dpf_regeneration_status();
+            vec![
+                regeneration.trigger,
+                regeneration.average_time_between,
+                regeneration.average_distance_between,
+            ]
+        }),
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x8C,
+        bytes: 17,
+        name: "O2 Sensor (Wide Range)",
+        unit: "",
+        formula: "",
+        range: None,
+        cards: &[],
+        poll: Poll::Never,
+        read: None,
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x8D,
+        bytes: 1,
+        name: "Throttle Position G",
+        unit: "%",
+        formula: "",
+        range: None,
+        cards: &[],
+        poll: Poll::Never,
+        read: None,
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x8E,
+        bytes: 1,
+        name: "Engine Friction - Percent Torque",
+        unit: "%",
+        formula: "A - 125",
+        range: None,
+        cards: &[],
+        poll: Poll::Never,
+        read: None,
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x8F,
+        bytes: 7,
+        name: "PM Sensor Bank 1 & 2",
+        unit: "",
+        formula: "",
+        range: None,
+        cards: &[],
+        poll: Poll::Never,
+        read: None,
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x90,
+        bytes: 3,
+        name: "WWH-OBD Vehicle OBD System Information",
+        unit: "h",
+        formula: "",
+        range: None,
+        cards: &[],
+        poll: Poll::Never,
+        read: None,
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x91,
+        bytes: 5,
+        name: "WWH-OBD Vehicle OBD System Information",
+        unit: "h",
+        formula: "",
+        range: None,
+        cards: &[],
+        poll: Poll::Never,
+        read: None,
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x92,
+        bytes: 2,
+        name: "Fuel System Control",
+        unit: "",
+        formula: "",
+        range: None,
+        cards: &[],
+        poll: Poll::Never,
+        read: None,
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x93,
+        bytes: 3,
+        name: "WWH-OBD Vehicle OBD Counters support",
+        unit: "h",
+        formula: "",
+        range: None,
+        cards: &[],
+        poll: Poll::Never,
+        read: None,
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x94,
+        bytes: 12,
+        name: "NOx Warning And Inducement System",
+        unit: "",
+        formula: "",
+        range: None,
+        cards: &[],
+        poll: Poll::Never,
+        read: None,
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x98,
+        bytes: 9,
+        name: "Exhaust Gas Temperature Sensor",
+        unit: "°C",
+        formula: "",
+        range: None,
+        cards: &[],
+        poll: Poll::Never,
+        read: None,
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x99,
+        bytes: 9,
+        name: "Exhaust Gas Temperature Sensor",
+        unit: "°C",
+        formula: "",
+        range: None,
+        cards: &[],
+        poll: Poll::Never,
+        read: None,
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x9A,
+        bytes: 6,
+        name: "Hybrid/EV Vehicle System Data, Battery, Voltage",
+        unit: "",
+        formula: "",
+        range: None,
+        cards: &["Hybrid Battery Voltage", "Hybrid Battery Current"],
+        poll: Poll::Never,
+        read: Some(|obd| {
+            let hybrid = obd.hybrid_system_data();
+            vec![hybrid.battery_voltage, hybrid.battery_current]
+        }),
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x9B,
+        bytes: 4,
+        name: "Diesel Exhaust Fluid Sensor Data",
+        unit: "%",
+        formula: "100/255 * D",
+        range: None,
+        cards: &["DEF Concentration", "DEF Tank Temp.", "DEF Tank Level"],
+        poll: Poll::Never,
+        read: Some(|obd| {
+            let def = obd.diesel_exhaust_fluid();
+            vec![def.concentration, def.tank_temp, def.tank_level]
+        }),
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x9C,
+        bytes: 17,
+        name: "O2 Sensor Data",
+        unit: "",
+        formula: "",
+        range: None,
+        cards: &[],
+        poll: Poll::Never,
+        read: None,
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x9D,
+        bytes: 4,
+        name: "Engine Fuel Rate",
+        unit: "g/s",
+        formula: "",
+        range: None,
+        cards: &["Engine Fuel Rate"],
+        poll: Poll::High,
+        read: Some(|obd| vec![obd.engine_fuel_rate()]),
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x9E,
+        bytes: 2,
+        name: "Engine Exhaust Flow Rate",
+        unit: "kg/h",
+        formula: "",
+        range: None,
+        cards: &[],
+        poll: Poll::Never,
+        read: None,
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0x9F,
+        bytes: 9,
+        name: "Fuel System Percentage Use",
+        unit: "",
+        formula: "",
+        range: None,
+        cards: &[
+            "Fuel System A Use (Bank 1)",
+            "Fuel System A Use (Bank 2)",
+            "Fuel System B Use (Bank 1)",
+            "Fuel System B Use (Bank 2)",
+        ],
+        poll: Poll::Never,
+        read: Some(|obd| obd.fuel_system_percentage_use().to_vec()),
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0xA1,
+        bytes: 9,
+        name: "NOx Sensor Corrected Data",
+        unit: "ppm",
+        formula: "",
+        range: None,
+        cards: &[],
+        poll: Poll::Never,
+        read: None,
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0xA2,
+        bytes: 2,
+        name: "Cylinder Fuel Rate",
+        unit: "mg/stroke",
+        formula: "((256 * A)+B) / 32",
+        range: Some((0.0, 2047.97)),
+        cards: &["Cylinder Fuel Rate"],
+        poll: Poll::High,
+        read: Some(|obd| vec![obd.cylinder_fuel_rate()]),
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0xA3,
+        bytes: 9,
+        name: "Evap System Vapor Pressure",
+        unit: "Pa",
+        formula: "",
+        range: None,
+        cards: &[],
+        poll: Poll::Never,
+        read: None,
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0xA4,
+        bytes: 4,
+        name: "Transmission Actual Gear",
+        unit: "ratio",
+        formula: "((256 * C) + D) / 1000",
+        range: None,
+        cards: &["Transmission Gear", "Transmission Gear Ratio"],
+        poll: Poll::Never,
+        read: Some(|obd| {
+            let (gear, ratio) = obd.transmission_actual_gear();
+            vec![gear, ratio]
+        }),
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0xA5,
+        bytes: 4,
+        name: "Commanded Diesel Exhaust Fluid Dosing",
+        unit: "%",
+        formula: "B / 2",
+        range: None,
+        cards: &[],
+        poll: Poll::Never,
+        read: None,
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0xA6,
+        bytes: 4,
+        name: "Odometer",
+        unit: "km",
+        formula: "(A(2^24) + B(2^16) + C(2^8) + D) / 10",
+        range: Some((0.0, 429496729.5)),
+        cards: &["Odometer"],
+        poll: Poll::Once,
+        read: Some(|obd| vec![obd.odometer()]),
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0xA7,
+        bytes: 4,
+        name: "NOx Sensor Concentration Sensors 3 and 4",
+        unit: "",
+        formula: "",
+        range: None,
+        cards: &[],
+        poll: Poll::Never,
+        read: None,
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0xA8,
+        bytes: 4,
+        name: "NOx Sensor Corrected Concentration Sensors 3 and 4",
+        unit: "",
+        formula: "",
+        range: None,
+        cards: &[],
+        poll: Poll::Never,
+        read: None,
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0xA9,
+        bytes: 4,
+        name: "ABS Disable Switch State",
+        unit: "",
+        formula: "{A0}= 1:Supported; 0:Unsupported",
+        range: None,
+        cards: &[],
+        poll: Poll::Never,
+        read: None,
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0xC3,
+        bytes: 0,
+        name: "Fuel Level Input A/B",
+        unit: "%",
+        formula: "",
+        range: None,
+        cards: &[],
+        poll: Poll::Never,
+        read: None,
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0xC4,
+        bytes: 0,
+        name: "Exhaust Particulate Control System Diagnostic Time/Count",
+        unit: "seconds / Count",
+        formula: "",
+        range: None,
+        cards: &[],
+        poll: Poll::Never,
+        read: None,
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0xC5,
+        bytes: 0,
+        name: "Fuel Pressure A and B",
+        unit: "kPa",
+        formula: "",
+        range: None,
+        cards: &[],
+        poll: Poll::Never,
+        read: None,
+    },
+    PidDef {
+        service: 0x01,
+        pid: 0xC7,
+        bytes: 0,
+        name: "Distance Since Reflash or Module Replacement",
+        unit: "km",
+        formula: "",
+        range: None,
+        cards: &[],
+        poll: Poll::Never,
+        read: None,
+    },
+];
@@ -0,0 +1,116 @@
+// Values with where and when they were read.
+//
+// A `Scalar` only holds a value and its unit. For logging and analysis a
+// sample also keeps when it was taken, which ECU replied, the bytes it replied
+// with and whether the value is within the range the pid is defined for.
+
+use std::{str::FromStr, time::Instant};
+
+use chrono::Utc;
+use once_cell::sync::Lazy;
+use serde::Serialize;
+
+use crate::{
+    registry::{self, PidDef, PidError},
+    scalar::{Scalar, Unit},
+    OBD,
+};
+
+// Reference for the monotonic timestamp of every sample
+static EPOCH: Lazy<Instant> = Lazy::new(Instant::now);
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Sample {
+    /// Card the value is shown as (e.g "Engine Speed")
+    pub name: &'static str,
+    pub service: u8,
+    pub pid: u8,
+    pub value: Scalar,
+
+    /// Address of the ECU that replied (e.g "7E8").
+    /// `None` if the reply couldn't be matched to an ECU.
+    pub ecu: Option<String>,
+
+    /// Data bytes of the reply, after the pid number (and frame number in a freeze frame)
+    pub raw: Vec<u8>,
+
+    /// Within the range SAE J1979 defines for the pid.
+    /// Always true for pids without a defined range.
+    pub valid: bool,
+
+    /// RFC 3339 timestamp
+    pub at: String,
+
+    /// Seconds since the first sample was taken, unaffected by clock changes
+    pub elapsed: f64,
+
+    #[serde(skip)]
+    pub instant: Instant,
+}
+
+impl PidDef {
+    /// Read the pid, one sample for each of its values.
+    pub fn sample(&self, obd: &mut OBD) -> Result<Vec<Sample>, PidError> {
+        let values = self.read(obd)?;
+        let instant = Instant::now();
+        let at = Utc::now().to_rfc3339();
+        let elapsed = instant.duration_since(*EPOCH).as_secs_f64();
+
+        // 41 <pid> data..., or 42 <pid> <frame> data...
+        let (reply, header) = match obd.get_freeze_frame() {
+            Some(_) => (0x42, 3),
+            None => (0x40 + self.service, 2),
+        };
+        let message = obd.last_messages.iter().find(|message| {
+            message.data.len() > header && message.data[0] == reply && message.data[1] == self.pid
+        });
+
+        Ok(self
+            .cards
+            .iter()
+            .zip(values)
+            .map(|(name, value)| Sample {
+                name,
+                service: self.service,
+                pid: self.pid,
+                valid: self.in_range(&value),
+                ecu: message.map(|message| message.ecu.clone()),
+                raw: message
+                    .map(|message| message.data[header..].to_vec())
+                    .unwrap_or_default(),
+                value,
+                at: at.clone(),
+                elapsed,
+                instant,
+            })
+            .collect())
+    }
+
+    /// Whether `value` is within `range`, once converted back to the unit of the pid.
+    /// Values of pids without a unit obdium knows are compared as they are.
+    pub fn in_range(&self, value: &Scalar) -> bool {
+        let Some((min, max)) = self.range else {
+            return true;
+        };
+
+        let value = match Unit::from_str(self.unit) {
+            Ok(unit) => value.value_in(unit),
+            Err(_) => Some(value.value),
+        };
+        value.is_some_and(|value| (min..=max).contains(&value))
+    }
+}
+
+impl OBD {
+    /// Read a pid from the registry as samples.
+    pub fn sample(&mut self, service: u8, pid: u8) -> Result<Vec<Sample>, PidError> {
+        registry::find(service, pid)
+            .ok_or(PidError::Unsupported)?
+            .sample(self)
+    }
+
+    /// Read several pids from the registry, one result for each in the same order.
+    pub fn sample_pids(&mut self, pids: &[&PidDef]) -> Vec<Result<Vec<Sample>, PidError>> {
+        pids.iter().map(|def| def.sample(self)).collect()
+    }
+}
@@ -1,11 +1,16 @@
 use crate::{
+    registry::PidError,
     scalar::{Scalar, Unit},
     Command, SensorNumber, OBD,
 };
 
 impl OBD {
     pub fn vehicle_speed(&mut self) -> Scalar {
-        self.query(Command::new_pid(b"010D")).map_no_data(|r| {
+        self.try_vehicle_speed().unwrap_or(Scalar::no_data())
+    }
+
+    pub fn try_vehicle_speed(&mut self) -> Result<Scalar, PidError> {
+        self.try_query(Command::new_pid(b"010D")).map(|r| {
             Scalar::new(
                 r.a_value(),
                 Unit::KilometersPerHour,
@@ -15,7 +20,11 @@ impl OBD {
     }
 
     pub fn timing_advance(&mut self) -> Scalar {
-        self.query(Command::new_pid(b"010E")).map_no_data(|r| {
+        self.try_timing_advance().unwrap_or(Scalar::no_data())
+    }
+
+    pub fn try_timing_advance(&mut self) -> Result<Scalar, PidError> {
+        self.try_query(Command::new_pid(b"010E")).map(|r| {
             Scalar::new(
                 (r.a_value() / 2.0) - 64.0,
                 Unit::Degrees,
@@ -53,7 +62,11 @@ impl OBD {
     }
 
     pub fn throttle_position(&mut self) -> Scalar {
-        self.query(Command::new_pid(b"0111")).map_no_data(|r| {
+        self.try_throttle_position().unwrap_or(Scalar::no_data())
+    }
+
+    pub fn try_throttle_position(&mut self) -> Result<Scalar, PidError> {
+        self.try_query(Command::new_pid(b"0111")).map(|r| {
             Scalar::new(
                 r.a_value() * (100.0 / 255.0),
                 Unit::Percent,
@@ -63,7 +76,12 @@ impl OBD {
     }
 
     pub fn relative_throttle_pos(&mut self) -> Scalar {
-        self.query(Command::new_pid(b"0145")).map_no_data(|r| {
+        self.try_relative_throttle_pos()
+            .unwrap_or(Scalar::no_data())
+    }
+
+    pub fn try_relative_throttle_pos(&mut self) -> Result<Scalar, PidError> {
+        self.try_query(Command::new_pid(b"0145")).map(|r| {
             Scalar::new(
                 (100.0 / 255.0) * r.a_value(),
                 Unit::Percent,
@@ -73,7 +91,12 @@ impl OBD {
     }
 
     pub fn abs_throttle_position_b(&mut self) -> Scalar {
-        self.query(Command::new_pid(b"0147")).map_no_data(|r| {
+        self.try_abs_throttle_position_b()
+            .unwrap_or(Scalar::no_data())
+    }
+
+    pub fn try_abs_throttle_position_b(&mut self) -> Result<Scalar, PidError> {
+        self.try_query(Command::new_pid(b"0147")).map(|r| {
             Scalar::new(
                 (100.0 / 255.0) * r.a_value(),
                 Unit::Percent,
@@ -83,7 +106,12 @@ impl OBD {
     }
 
     pub fn abs_throttle_position_c(&mut self) -> Scalar {
-        self.query(Command::new_pid(b"0148")).map_no_data(|r| {
+        self.try_abs_throttle_position_c()
+            .unwrap_or(Scalar::no_data())
+    }
+
+    pub fn try_abs_throttle_position_c(&mut self) -> Result<Scalar, PidError> {
+        self.try_query(Command::new_pid(b"0148")).map(|r| {
             Scalar::new(
                 (100.0 / 255.0) * r.a_value(),
                 Unit::Percent,
@@ -94,7 +122,11 @@ impl OBD {
 
     // Accelerator pedal position d
     pub fn acc_pedal_position_d(&mut self) -> Scalar {
-        self.query(Command::new_pid(b"0149")).map_no_data(|r| {
+        self.try_acc_pedal_position_d().unwrap_or(Scalar::no_data())
+    }
+
+    pub fn try_acc_pedal_position_d(&mut self) -> Result<Scalar, PidError> {
+        self.try_query(Command::new_pid(b"0149")).map(|r| {
             Scalar::new(
                 (100.0 / 255.0) * r.a_value(),
                 Unit::Percent,
@@ -105,7 +137,11 @@ impl OBD {
 
     // Accelerator pedal position e
     pub fn acc_pedal_position_e(&mut self) -> Scalar {
-        self.query(Command::new_pid(b"014A")).map_no_data(|r| {
+        self.try_acc_pedal_position_e().unwrap_or(Scalar::no_data())
+    }
+
+    pub fn try_acc_pedal_position_e(&mut self) -> Result<Scalar, PidError> {
+        self.try_query(Command::new_pid(b"014A")).map(|r| {
             Scalar::new(
                 (100.0 / 255.0) * r.a_value(),
                 Unit::Percent,
@@ -116,7 +152,11 @@ impl OBD {
 
     // Accelerator pedal position f
     pub fn acc_pedal_position_f(&mut self) -> Scalar {
-        self.query(Command::new_pid(b"014B")).map_no_data(|r| {
+        self.try_acc_pedal_position_f().unwrap_or(Scalar::no_data())
+    }
+
+    pub fn try_acc_pedal_position_f(&mut self) -> Result<Scalar, PidError> {
+        self.try_query(Command::new_pid(b"014B")).map(|r| {
             Scalar::new(
                 (100.0 / 255.0) * r.a_value(),
                 Unit::Percent,
@@ -164,4 +204,33 @@ impl OBD {
 
         sensors_supported
     }
+
+    /// Decode a pid made of a support byte followed by one 2 byte value per sensor
+    /// (e.g 0178: A supported sensors, B-C sensor 1, D-E sensor 2...).
+    ///
+    /// Bit N of the support byte says whether value N is reported.
+    /// Unsupported or missing values are no data.
+    pub(crate) fn decode_sensor_words<const N: usize>(
+        &self,
+        bytes: &[u8],
+        scale: impl Fn(f32) -> (f32, Unit),
+    ) -> [Scalar; N] {
+        let mut values: [Scalar; N] = std::array::from_fn(|_| Scalar::no_data());
+        let Some((&supported, words)) = bytes.split_first() else {
+            return values;
+        };
+
+        for (index, value) in values.iter_mut().enumerate() {
+            let Some(word) = words.get(index * 2..index * 2 + 2) else {
+                break;
+            };
+
+            if supported & (1 << index) != 0 {
+                let (scaled, unit) = scale(((word[0] as u32) << 8 | word[1] as u32) as f32);
+                *value = Scalar::new(scaled, unit, Some(self.unit_preferences));
+            }
+        }
+
+        values
+    }
 }
@@ -0,0 +1,213 @@
+// What was learned about a vehicle, kept between connections.
+//
+// Discovering what a vehicle supports means walking the support bitmaps of
+// every service, which takes a while and pauses polling. The result is saved
+// in the app data dir, one file per VIN. On reconnect the profile is reused
+// once the protocol and the calibration ids read from the vehicle still match
+// it, and rebuilt otherwise (e.g after the ECU was reflashed).
+//
+// Mode 22 pids known for the model are tried afterwards, one at a time in the
+// background, since there can be hundreds of them.
+
+use std::{collections::HashMap, fs, path::PathBuf};
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{
+    capabilities::Capabilities, engine::EngineType, oxygen::OxygenSensorLocation,
+    vin::APP_DATA_DIR, Command, Mode22Pid, OBD,
+};
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("Vehicle did not report a VIN.")]
+    NoVin,
+    #[error("App data directory is not set.")]
+    NoAppDataDir,
+    #[error("When reading or writing vehicle profile: {0}.")]
+    Io(#[from] std::io::Error),
+    #[error("Vehicle profile is malformed: {0}.")]
+    Json(#[from] serde_json::Error),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VehicleProfile {
+    pub vin: String,
+
+    /// Protocol the adapter detected (e.g "ISO 15765-4 (CAN 11/500)")
+    pub protocol: String,
+
+    /// Addresses of the ECUs that responded
+    pub ecus: Vec<String>,
+
+    /// Calibration ids (0904) by ECU
+    pub calibration_ids: HashMap<String, Vec<String>>,
+    pub capabilities: Capabilities,
+    pub engine_type: EngineType,
+    pub oxygen_sensors: Vec<OxygenSensorLocation>,
+
+    /// Mode 22 pids from the model database the vehicle answered.
+    /// `None` until they've been tried.
+    #[serde(default)]
+    pub mode22_pids: Option<Vec<String>>,
+
+    /// RFC 3339 timestamp
+    pub updated_at: String,
+}
+
+impl VehicleProfile {
+    fn path(vin: &str) -> Result<PathBuf, Error> {
+        let dir = APP_DATA_DIR.get().ok_or(Error::NoAppDataDir)?;
+        Ok(dir.join("vehicle-profiles").join(format!("{vin}.json")))
+    }
+
+    /// The saved profile for `vin`, `None` if the vehicle hasn't been seen before.
+    pub fn load(vin: &str) -> Result<Option<Self>, Error> {
+        let path = Self::path(vin)?;
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        Ok(Some(serde_json::from_str(&fs::read_to_string(path)?)?))
+    }
+
+    pub fn save(&self) -> Result<(), Error> {
+        let path = Self::path(&self.vin)?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Whether the profile still describes the connected vehicle.
+    pub fn matches(&self, protocol: &str, calibration_ids: &HashMap<String, Vec<String>>) -> bool {
+        self.protocol == protocol && &self.calibration_ids == calibration_ids
+    }
+}
+
+impl OBD {
+    /// Profile of the connected vehicle.
+    ///
+    /// Reuses the saved profile if the protocol and calibration ids still match,
+    /// otherwise runs discovery and saves the result.
+    /// Once loaded, its capabilities are used to skip unsupported requests and
+    /// its engine type and oxygen sensors are used instead of asking the vehicle.
+    pub fn load_vehicle_profile(&mut self) -> Result<VehicleProfile, Error> {
+        self.profile = None;
+        let vin = self.get_vin().ok_or(Error::NoVin)?;
+        let protocol = self.get_detected_protocol();
+        let calibration_ids = self.get_calibration_ids();
+
+        match VehicleProfile::load(vin.get_vin()) {
+            Ok(Some(profile)) if profile.matches(&protocol, &calibration_ids) => {
+                self.capabilities = profile.capabilities.clone();
+                self.profile = Some(profile.clone());
+                return Ok(profile);
+            }
+            Ok(Some(_)) => println!("vehicle profile for {} is outdated", vin.get_vin()),
+            Ok(None) => {}
+            Err(err) => println!("when loading vehicle profile: {err}"),
+        }
+
+        let capabilities = self.discover_capabilities().clone();
+        let profile = VehicleProfile {
+            vin: vin.get_vin().to_string(),
+            protocol,
+            ecus: capabilities.ecus(),
+            calibration_ids,
+            capabilities,
+            engine_type: self.get_engine_type(),
+            oxygen_sensors: self.get_oxygen_sensor_map(),
+            mode22_pids: None,
+            updated_at: Utc::now().to_rfc3339(),
+        };
+
+        // a recording isn't the vehicle, don't remember it
+        if !self.replay_requests {
+            profile.save()?;
+        }
+
+        self.profile = Some(profile.clone());
+        Ok(profile)
+    }
+
+    /// Profile loaded with `load_vehicle_profile`.
+    pub fn vehicle_profile(&self) -> Option<&VehicleProfile> {
+        self.profile.as_ref()
+    }
+
+    /// Mode 22 pids the loaded profile hasn't tried yet, with the
+    /// VIN they're for. `None` if there's nothing to try.
+    ///
+    /// Meant to be read one at a time with `read_mode_22_pid`, then
+    /// saved with `save_mode_22_pids`.
+    pub fn untested_mode_22_pids(&mut self) -> Option<(String, Vec<Mode22Pid>)> {
+        // This does not work when replaying requests
+        if self.replay_requests {
+            return None;
+        }
+
+        let profile = self.profile.as_ref()?;
+        if profile.mode22_pids.is_some() {
+            return None;
+        }
+
+        let vin = self.get_vin()?;
+        Some((vin.get_vin().to_string(), OBD::get_mode_22_pids(&vin)))
+    }
+
+    /// Save the Mode 22 pids that worked for `vin` in its profile.
+    /// Ignored if another vehicle has been connected since.
+    pub fn save_mode_22_pids(&mut self, vin: &str, pids: Vec<String>) -> Result<(), Error> {
+        let Some(profile) = self.profile.as_mut().filter(|profile| profile.vin == vin) else {
+            return Ok(());
+        };
+
+        profile.mode22_pids = Some(pids);
+        profile.updated_at = Utc::now().to_rfc3339();
+        profile.save()
+    }
+
+    /// Calibration ids (0904) by ECU. An ECU can report several, 16 characters each.
+    pub fn get_calibration_ids(&mut self) -> HashMap<String, Vec<String>> {
+        let mut data: HashMap<String, Vec<u8>> = HashMap::new();
+
+        // 49 04 <count or sequence number> ...
+        for message in self.query_messages(Command::new_pid(b"0904")) {
+            if message.data.len() < 3 || message.data[0] != 0x49 || message.data[1] != 0x04 {
+                continue;
+            }
+
+            data.entry(message.ecu)
+                .or_default()
+                .extend(&message.data[3..]);
+        }
+
+        data.into_iter()
+            .map(|(ecu, bytes)| {
+                let ids = bytes
+                    .chunks(16)
+                    .map(|chunk| {
+                        String::from_utf8_lossy(chunk)
+                            .trim_end_matches('\0')
+                            .trim()
+                            .to_string()
+                    })
+                    .filter(|id| !id.is_empty())
+                    .collect();
+                (ecu, ids)
+            })
+            .collect()
+    }
+
+    // Protocol name without the "AUTO, " prefix, empty if the adapter didn't say
+    pub(crate) fn get_detected_protocol(&mut self) -> String {
+        self.get_protocol_name()
+            .map(|name| name.trim_start_matches("AUTO, ").trim().to_string())
+            .unwrap_or_default()
+    }
+}
@@ -1,33 +1,277 @@
-use std::fs;
+// Recording requests and replaying them.
+//
+// A recording is JSON Lines that is only ever appended to: a session header
+// each time recording starts (adapter version, protocol and VIN), then a line
+// for every request with the raw response, when it was made and how long the
+// adapter took to answer. Paths ending in ".xz" are compressed with xz.
+//
+// Recordings made before this format are a single JSON array. They can still
+// be replayed, or converted with `convert_legacy_recording`.
+
+use std::{
+    collections::HashMap,
+    fs,
+    io::{BufRead, BufReader, Read, Write},
+    path::{Path, PathBuf},
+    time::Instant,
+};
+
+use chrono::Utc;
+use rand::seq::IndexedRandom;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use xz2::{read::XzDecoder, write::XzEncoder};
 
 use crate::{
     cmd::{Command, CommandType},
     obd::OBD,
     response::Response,
 };
-use rand::seq::IndexedRandom;
-use serde_json::{json, Value};
+
+/// Version written in the session header
+pub const FORMAT_VERSION: u32 = 1;
+
+// Compressed recordings are flushed every this many lines, flushing every line
+// would end the compressed block each time and barely compress at all
+const XZ_FLUSH_LINES: usize = 100;
+
+// Every xz file starts with these bytes
+const XZ_MAGIC: [u8; 6] = [0xFD, b'7', b'z', b'X', b'Z', 0x00];
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("When reading or writing recording: {0}.")]
+    Io(#[from] std::io::Error),
+    #[error("Recording is malformed: {0}.")]
+    Json(#[from] serde_json::Error),
+    #[error("{0} is a recording in the old format, convert it before recording to it.")]
+    LegacyRecording(PathBuf),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RecordLine {
+    Session(SessionHeader),
+    Request(RecordedRequest),
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SessionHeader {
+    pub format: u32,
+
+    /// RFC 3339 timestamp
+    pub started_at: Option<String>,
+
+    /// ELM327 version string (e.g "ELM327 v1.5")
+    pub adapter: Option<String>,
+    pub protocol: Option<String>,
+    pub vin: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedRequest {
+    pub request: String,
+    pub(crate) request_type: CommandType,
+
+    /// Raw response from the adapter
+    pub response: String,
+
+    /// RFC 3339 timestamp. Missing in converted recordings.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub at: Option<String>,
+
+    /// Seconds since the session started, unaffected by clock changes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub elapsed: Option<f64>,
+
+    /// Milliseconds from sending the request to reading the whole response
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub latency_ms: Option<f64>,
+}
+
+// Entry of a recording in the old format
+#[derive(Deserialize)]
+struct LegacyRequest {
+    request: String,
+    request_type: CommandType,
+    response: String,
+}
+
+/// Appends to a recording.
+pub(crate) struct Recorder {
+    writer: Box<dyn Write + Send>,
+    compressed: bool,
+    unflushed: usize,
+    started: Instant,
+}
+
+impl Recorder {
+    /// Start a session in the recording at `path`, creating it if needed.
+    ///
+    /// Recordings in the old format are refused, lines appended
+    /// after the JSON array would make the whole file unreadable.
+    pub(crate) fn create(path: &Path, header: SessionHeader) -> Result<Self, Error> {
+        if is_legacy_recording(path)? {
+            return Err(Error::LegacyRecording(path.to_path_buf()));
+        }
+
+        let file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)?;
+
+        let compressed = path.extension().is_some_and(|ext| ext == "xz");
+        let writer: Box<dyn Write + Send> = if compressed {
+            // each session is its own xz stream
+            Box::new(XzEncoder::new(file, 6))
+        } else {
+            Box::new(file)
+        };
+
+        let mut recorder = Self {
+            writer,
+            compressed,
+            unflushed: 0,
+            started: Instant::now(),
+        };
+
+        recorder.write(&RecordLine::Session(header))?;
+        Ok(recorder)
+    }
+
+    pub(crate) fn write(&mut self, line: &RecordLine) -> Result<(), Error> {
+        let mut line = serde_json::to_string(line)?;
+        line.push('\n');
+
+        // a crash loses at most the lines not flushed yet,
+        // the xz stream is finished when the recorder is dropped
+        self.writer.write_all(line.as_bytes())?;
+        self.unflushed += 1;
+        if !self.compressed || self.unflushed >= XZ_FLUSH_LINES {
+            self.writer.flush()?;
+            self.unflushed = 0;
+        }
+        Ok(())
+    }
+}
+
+// Whether `path` holds a recording in the old format, a JSON array.
+// Compressed recordings are always JSON Lines.
+fn is_legacy_recording(path: &Path) -> Result<bool, Error> {
+    let file = match fs::File::open(path) {
+        Ok(file) => file,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(false),
+        Err(err) => return Err(err.into()),
+    };
+
+    let mut reader = BufReader::new(file);
+    loop {
+        let buffer = reader.fill_buf()?;
+        let Some(&byte) = buffer.first() else {
+            return Ok(false);
+        };
+
+        if !byte.is_ascii_whitespace() {
+            return Ok(byte == b'[');
+        }
+        reader.consume(1);
+    }
+}
+
+/// Every line of the recording at `path`, in either format.
+///
+/// Lines that can't be parsed (e.g the last one, if the app
+/// closed while writing it) are skipped.
+pub fn read_recording(path: &Path) -> Result<Vec<RecordLine>, Error> {
+    let bytes = fs::read(path)?;
+    let contents = if bytes.starts_with(&XZ_MAGIC) {
+        let mut data = Vec::new();
+        if let Err(err) = XzDecoder::new_multi_decoder(bytes.as_slice()).read_to_end(&mut data) {
+            println!("recording {} is truncated: {err}", path.display());
+        }
+        String::from_utf8_lossy(&data).into_owned()
+    } else {
+        String::from_utf8_lossy(&bytes).into_owned()
+    };
+
+    if contents.trim_start().starts_with('[') {
+        let requests: Vec<LegacyRequest> = serde_json::from_str(&contents)?;
+        return Ok(requests
+            .into_iter()
+            .map(|legacy| {
+                RecordLine::Request(RecordedRequest {
+                    request: legacy.request,
+                    request_type: legacy.request_type,
+                    response: legacy.response,
+                    at: None,
+                    elapsed: None,
+                    latency_ms: None,
+                })
+            })
+            .collect());
+    }
+
+    Ok(contents
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(|line| match serde_json::from_str(line) {
+            Ok(line) => Some(line),
+            Err(err) => {
+                println!("skipping malformed line in recording: {err}");
+                None
+            }
+        })
+        .collect())
+}
+
+/// Convert a recording in the old format (e.g requests.json) to JSON Lines.
+/// Appended to `to` as a new session. Returns how many requests were converted.
+pub fn convert_legacy_recording(from: &Path, to: &Path) -> Result<usize, Error> {
+    let lines = read_recording(from)?;
+    let header = SessionHeader {
+        format: FORMAT_VERSION,
+        ..Default::default()
+    };
+
+    let mut recorder = Recorder::create(to, header)?;
+    let mut converted = 0;
+    for line in lines.iter() {
+        if let RecordLine::Request(_) = line {
+            recorder.write(line)?;
+            converted += 1;
+        }
+    }
+
+    Ok(converted)
+}
 
 impl OBD {
     pub fn record_requests(&mut self, state: bool, path: String) {
+        // ends the current session, if any
+        self.record_requests = false;
+        self.recorder = None;
+
         if state {
             self.requests_path = path;
+            self.recorded = None;
+
+            let header = SessionHeader {
+                format: FORMAT_VERSION,
+                started_at: Some(Utc::now().to_rfc3339()),
+                adapter: self.elm_version.clone(),
+                protocol: Some(self.get_detected_protocol()).filter(|name| !name.is_empty()),
+                vin: self.get_vin().map(|vin| vin.get_vin().to_string()),
+            };
 
-            match fs::OpenOptions::new()
-                .create(true)
-                .append(true)
-                .open(&self.requests_path)
-            {
-                Ok(_) => {
+            match Recorder::create(Path::new(&self.requests_path), header) {
+                Ok(recorder) => {
+                    self.recorder = Some(recorder);
                     self.record_requests = true;
                 }
                 Err(e) => {
                     println!("File creation to save requests failed: {e}. Not recording requests.");
-                    self.record_requests = false;
                 }
             }
-        } else {
-            self.record_requests = false;
         }
 
         println!("Recording requests: {state:?}");
@@ -36,99 +280,81 @@ impl OBD {
     pub fn replay_requests(&mut self, state: bool) {
         // stop recording when replaying
         if state {
-            self.reset_played_flags();
             self.record_requests = false;
+            self.recorder = None;
         }
 
+        // read again in case it was recorded to since
+        self.recorded = None;
         self.replay_requests = state;
         println!("Replaying requests: {state:?}");
     }
 
-    pub(crate) fn save_request(&mut self, request: &Command, response: &Response) {
-        // get the current file contents
-        let mut contents_json = {
-            let contents = fs::read_to_string(&self.requests_path).expect("file read error");
-            if contents.trim().is_empty() {
-                vec![]
-            } else {
-                serde_json::from_str(&contents).unwrap_or_else(|_| vec![])
-            }
-        };
-
-        let obj = json!({
-            "request": request.as_string(),
-            "request_type": request.command_type(),
-            "response": response.raw_response.as_deref().unwrap_or("")
-        });
-
-        contents_json.push(obj);
-
-        let pretty =
-            serde_json::to_string_pretty(&contents_json).expect("failed pretty'ing string");
-        fs::write(&self.requests_path, pretty).expect("failed to write requests file");
+    /// Whether responses come from a recording instead of the vehicle.
+    pub fn is_replaying(&self) -> bool {
+        self.replay_requests
     }
 
-    pub(crate) fn get_recorded_response(&self, request: &Command) -> Response {
-        let contents_json: Vec<Value> = {
-            let contents = match fs::read_to_string(&self.requests_path) {
-                Ok(contents) => contents,
-                Err(_) => return Response::no_data(),
-            };
+    pub(crate) fn save_request(&mut self, request: &Command, response: &Response) {
+        let latency_ms = self
+            .sent_at
+            .map(|sent_at| sent_at.elapsed().as_secs_f64() * 1000.0);
 
-            if contents.trim().is_empty() {
-                vec![]
-            } else {
-                serde_json::from_str(&contents).unwrap_or_else(|_| vec![])
-            }
+        let Some(recorder) = &mut self.recorder else {
+            return;
         };
 
-        if contents_json.is_empty() {
-            return Response::no_data();
-        }
+        let line = RecordLine::Request(RecordedRequest {
+            request: request.as_string(),
+            request_type: *request.command_type(),
+            response: response.raw_response.clone().unwrap_or_default(),
+            at: Some(Utc::now().to_rfc3339()),
+            elapsed: Some(recorder.started.elapsed().as_secs_f64()),
+            latency_ms,
+        });
 
-        let mut related_requests = Vec::new();
-        for value in contents_json.iter() {
-            if value["request"] == request.as_string() {
-                related_requests.push(value);
-            }
+        if let Err(err) = recorder.write(&line) {
+            println!("{err} Not recording requests.");
+            self.record_requests = false;
+            self.recorder = None;
         }
+    }
 
-        if related_requests.is_empty() {
-            return Response::no_data();
-        }
+    pub(crate) fn get_recorded_response(&mut self, request: &Command) -> Response {
+        // read once, then kept until replaying is toggled
+        let recorded = self.recorded.get_or_insert_with(|| {
+            let lines = read_recording(Path::new(&self.requests_path)).unwrap_or_else(|err| {
+                println!("when reading recording {}: {err}", self.requests_path);
+                Vec::new()
+            });
 
-        // Randomly select response to use from related_requests
-        if let Some(value) = related_requests.choose(&mut rand::rng()) {
-            let escaped_response = value["response"].as_str().unwrap_or_default();
-            if value["request_type"] == json!(CommandType::PIDCommand) {
-                return self
-                    .parse_pid_response(escaped_response)
-                    .unwrap_or(Response::no_data());
+            let mut recorded: HashMap<String, Vec<RecordedRequest>> = HashMap::new();
+            for line in lines {
+                if let RecordLine::Request(entry) = line {
+                    recorded
+                        .entry(entry.request.clone())
+                        .or_default()
+                        .push(entry);
+                }
             }
-            return Response::new(escaped_response.to_string(), escaped_response.to_string());
-        }
-        Response::no_data()
-    }
+            recorded
+        });
 
-    fn reset_played_flags(&self) {
-        let mut contents_json: Vec<Value> = {
-            let contents = fs::read_to_string(&self.requests_path).expect("file read error");
-            serde_json::from_str(&contents).unwrap_or_else(|_| vec![])
+        // Randomly select response to use from the related requests
+        let Some((request_type, response)) = recorded
+            .get(&request.as_string())
+            .and_then(|related_requests| related_requests.choose(&mut rand::rng()))
+            .map(|entry| (entry.request_type, entry.response.clone()))
+        else {
+            return Response::no_data();
         };
 
-        if contents_json.is_empty() {
-            return;
+        if request_type == CommandType::PIDCommand || request_type == CommandType::Arbitrary {
+            return self
+                .parse_pid_response(&response)
+                .unwrap_or(Response::no_data());
         }
 
-        for value in contents_json.iter_mut() {
-            if value["played"] == true {
-                value["played"] = serde_json::Value::Bool(false);
-            }
-        }
-
-        let pretty =
-            serde_json::to_string_pretty(&contents_json).expect("failed pretty'ing string");
-
-        fs::write(&self.requests_path, pretty).expect("failed to write requests file");
+        Response::new(response.clone(), response)
     }
 }
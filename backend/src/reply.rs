@@ -0,0 +1,64 @@
+// Replies to KWP2000 and UDS requests.
+//
+// Both protocols reject a request the same way: 0x7F, the service id and a
+// negative response code. An ECU that needs more time answers with 'response
+// pending' (0x78) and sends the real reply later, without a new request.
+
+use thiserror::Error;
+
+use crate::{response::NegativeResponseCode, Command, EcuMessage, OBD};
+
+/// Byte sent by the ECU instead of `service + 0x40` when rejecting a request
+pub const NEGATIVE_RESPONSE: u8 = 0x7F;
+
+/// How many times to wait on an ECU that replied with 'response pending' (0x78)
+pub const MAX_RESPONSE_PENDING: usize = 10;
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("Negative response to service {service:#04X}: {code}.")]
+    NegativeResponse {
+        service: u8,
+        code: NegativeResponseCode,
+    },
+    #[error("No response to service {0:#04X}.")]
+    NoResponse(u8),
+}
+
+/// The negative response code in `message`, if it rejects `service`.
+pub fn negative_response(service: u8, message: &EcuMessage) -> Option<NegativeResponseCode> {
+    match message.data.as_slice() {
+        [NEGATIVE_RESPONSE, sid, code, ..] if *sid == service => {
+            Some(NegativeResponseCode::from_u8(*code))
+        }
+        _ => None,
+    }
+}
+
+impl OBD {
+    /// Send a request for `service` and return every reply, by ECU.
+    ///
+    /// Waits on ECUs that reply with 'response pending' (0x78),
+    /// those replies are left out.
+    pub(crate) fn query_service(&mut self, service: u8, request: Command) -> Vec<EcuMessage> {
+        let mut messages = self.query_messages(request);
+        let mut replies = Vec::new();
+        let mut waits = 0;
+
+        loop {
+            let (pending, answered): (Vec<_>, Vec<_>) = messages.into_iter().partition(|message| {
+                negative_response(service, message) == Some(NegativeResponseCode::ResponsePending)
+            });
+            replies.extend(answered);
+
+            // An ECU asked for more time, wait for its reply
+            if pending.is_empty() || waits == MAX_RESPONSE_PENDING {
+                break;
+            }
+            waits += 1;
+            messages = self.read_messages();
+        }
+
+        replies
+    }
+}
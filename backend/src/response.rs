@@ -1,6 +1,80 @@
+use std::fmt;
+
 use crate::obd::OBD;
 use crate::scalar::Scalar;
 
+/// Negative response codes (NRC) sent by an ECU in a `7F <service> <code>` reply.
+///
+/// The codes are shared between SAE J1979, KWP2000 (ISO 14230) and UDS (ISO 14229).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NegativeResponseCode {
+    GeneralReject,
+    ServiceNotSupported,
+    SubFunctionNotSupported,
+    BusyRepeatRequest,
+    ConditionsNotCorrect,
+    RoutineNotComplete,
+    RequestOutOfRange,
+    SecurityAccessDenied,
+    InvalidKey,
+    ExceededNumberOfAttempts,
+    RequiredTimeDelayNotExpired,
+    ResponsePending,
+    ServiceNotSupportedInActiveSession,
+    Other(u8),
+}
+
+impl NegativeResponseCode {
+    pub fn from_u8(num: u8) -> Self {
+        match num {
+            0x10 => Self::GeneralReject,
+            0x11 => Self::ServiceNotSupported,
+            0x12 => Self::SubFunctionNotSupported,
+            0x21 => Self::BusyRepeatRequest,
+            0x22 => Self::ConditionsNotCorrect,
+            0x23 => Self::RoutineNotComplete,
+            0x31 => Self::RequestOutOfRange,
+            0x33 => Self::SecurityAccessDenied,
+            0x35 => Self::InvalidKey,
+            0x36 => Self::ExceededNumberOfAttempts,
+            0x37 => Self::RequiredTimeDelayNotExpired,
+            0x78 => Self::ResponsePending,
+            0x7F | 0x80 => Self::ServiceNotSupportedInActiveSession,
+            other => Self::Other(other),
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::GeneralReject => "general reject",
+            Self::ServiceNotSupported => "service not supported",
+            Self::SubFunctionNotSupported => "sub-function not supported or invalid format",
+            Self::BusyRepeatRequest => "busy, repeat request",
+            Self::ConditionsNotCorrect => "conditions not correct or request sequence error",
+            Self::RoutineNotComplete => "routine not complete",
+            Self::RequestOutOfRange => "request out of range",
+            Self::SecurityAccessDenied => "security access denied",
+            Self::InvalidKey => "invalid key",
+            Self::ExceededNumberOfAttempts => "exceeded number of attempts",
+            Self::RequiredTimeDelayNotExpired => "required time delay not expired",
+            Self::ResponsePending => "request correctly received, response pending",
+            Self::ServiceNotSupportedInActiveSession => {
+                "service not supported in active diagnostic session"
+            }
+            Self::Other(_) => "manufacturer specific or reserved code",
+        }
+    }
+}
+
+impl fmt::Display for NegativeResponseCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Other(code) => write!(f, "{} ({:#04X})", self.as_str(), code),
+            _ => write!(f, "{}", self.as_str()),
+        }
+    }
+}
+
 #[derive(Debug, Copy, Clone)]
 enum PayloadComponent {
     A,
@@ -157,11 +231,14 @@ impl Response {
                 // 41 0C is the reply, 1A F8 are the payload bytes
                 // which starts 6 characters into the string.
                 //
+                // A service 02 response has the frame number after the pid
+                // (e.g 42 0C 00 1A F8) and, like mode 22, starts 9 characters in.
+                //
                 // Due to the difference in structures, to get the payload bytes
                 // for mode 22 responses, we have to start 8 characters in the string
                 // instead of 6 for a 01 service response
-                if resp.starts_with("62") {
-                    // Mode 22 response
+                if resp.starts_with("62") || resp.starts_with("42") {
+                    // Mode 22 or service 02 response
                     if resp.len() < 9 {
                         println!("invalid response payload: '{resp}'");
                         return Vec::new();
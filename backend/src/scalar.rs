@@ -11,9 +11,12 @@ pub enum Unit {
     Ratio,
     Celsius,
     Fahrenheit,
+    Kelvin,
     Degrees,
     KiloPascal,
     Pascal,
+    Bar,
+    InchesOfMercury,
     RPM,
     KilometersPerHour,
     MilesPerHour,
@@ -27,54 +30,130 @@ pub enum Unit {
     Miles,
     Feet,
     Milliampere,
+    Amperes,
+    Watts,
+    Kilowatts,
+    Horsepower,
+    KilowattHours,
+    Megajoules,
+    KilowattHoursPer100Kilometers,
     LitresPerHour,
     GallonsPerHour,
+    LitresPer100Kilometers,
+    KilometersPerLitre,
+    MilesPerGallon,
+    MilesPerGallonUk,
     NewtonMeters,
     FootPounds,
     KilogramsPerSecond,
+    KilogramsPerHour,
+    PoundsPerMinute,
     PartsPerMillion,
     MiligramsPerStroke,
+    MilligramsPerCubicMeter,
     PSI,
     Unknown,
     NoData,
 }
 
+/// What a unit measures. Units of the same dimension convert to one another.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum Dimension {
+    Percentage,
+    Ratio,
+    Angle,
+    Temperature,
+    Pressure,
+    AngularSpeed,
+    Speed,
+    Distance,
+    Time,
+    Voltage,
+    Current,
+    Power,
+    Energy,
+    EnergyConsumption,
+    MassFlow,
+    VolumeFlow,
+    FuelEconomy,
+    Torque,
+    Concentration,
+    MassPerStroke,
+    MassConcentration,
+}
+
+// How a unit relates to the base unit of its dimension.
+#[derive(Clone, Copy)]
+enum Scale {
+    /// base = value * factor + offset
+    Linear(f32, f32),
+
+    /// base = k / value, e.g mpg to L/100km
+    Inverse(f32),
+}
+
 #[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Debug)]
 pub struct ParseUnitError;
 
 impl FromStr for Unit {
     type Err = ParseUnitError;
 
+    /// Case and whitespace are ignored, so "KPA", "kPa" and "k Pa" are all kilopascals.
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s {
-            "%" => Ok(Unit::Percent),
-            "ratio" => Ok(Unit::Ratio),
-            "°C" => Ok(Unit::Celsius),
-            "°F" => Ok(Unit::Fahrenheit),
-            "°" => Ok(Unit::Degrees),
-            "kPa" => Ok(Unit::KiloPascal),
-            "Pa" => Ok(Unit::Pascal),
-            "RPM" => Ok(Unit::RPM),
-            "km/h" => Ok(Unit::KilometersPerHour),
-            "g/s" => Ok(Unit::GramsPerSecond),
-            "V" => Ok(Unit::Volts),
-            "s" => Ok(Unit::Seconds),
-            "h" => Ok(Unit::Hours),
-            "mins" => Ok(Unit::Minutes),
-            "km" => Ok(Unit::Kilometers),
-            "mA" => Ok(Unit::Milliampere),
-            "L/h" => Ok(Unit::LitresPerHour),
-            "Nm" => Ok(Unit::NewtonMeters),
-            "Kg/s" => Ok(Unit::KilogramsPerSecond),
+        let unit: String = s
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .flat_map(char::to_lowercase)
+            .collect();
+
+        match unit.as_str() {
+            "%" | "percent" | "pct" => Ok(Unit::Percent),
+            "ratio" | "lambda" | "λ" => Ok(Unit::Ratio),
+            "°c" | "ºc" | "℃" | "c" | "degc" | "celsius" => Ok(Unit::Celsius),
+            "°f" | "ºf" | "℉" | "f" | "degf" | "fahrenheit" => Ok(Unit::Fahrenheit),
+            "k" | "kelvin" => Ok(Unit::Kelvin),
+            "°" | "º" | "deg" | "degree" | "degrees" => Ok(Unit::Degrees),
+            "kpa" | "kilopascal" | "kilopascals" => Ok(Unit::KiloPascal),
+            "pa" | "pascal" | "pascals" => Ok(Unit::Pascal),
+            "bar" => Ok(Unit::Bar),
+            "inhg" | "in.hg" | "\"hg" => Ok(Unit::InchesOfMercury),
+            "psi" | "lb/in²" | "lb/in2" => Ok(Unit::PSI),
+            "rpm" | "r/min" | "1/min" => Ok(Unit::RPM),
+            "km/h" | "km/hr" | "kmh" | "kph" => Ok(Unit::KilometersPerHour),
+            "mph" | "mi/h" => Ok(Unit::MilesPerHour),
+            "g/s" | "g/sec" | "gps" => Ok(Unit::GramsPerSecond),
+            "kg/s" => Ok(Unit::KilogramsPerSecond),
+            "kg/h" | "kg/hr" => Ok(Unit::KilogramsPerHour),
+            "lb/min" | "lbs/min" => Ok(Unit::PoundsPerMinute),
+            "v" | "volt" | "volts" => Ok(Unit::Volts),
+            "s" | "sec" | "secs" | "second" | "seconds" => Ok(Unit::Seconds),
+            "h" | "hr" | "hrs" | "hour" | "hours" => Ok(Unit::Hours),
+            "min" | "mins" | "minute" | "minutes" => Ok(Unit::Minutes),
+            "km" | "kilometers" | "kilometres" => Ok(Unit::Kilometers),
+            "m" | "meters" | "metres" => Ok(Unit::Meters),
+            "mi" | "mile" | "miles" => Ok(Unit::Miles),
+            "ft" | "feet" => Ok(Unit::Feet),
+            "ma" | "milliamps" => Ok(Unit::Milliampere),
+            "a" | "amps" | "amperes" => Ok(Unit::Amperes),
+            "w" | "watts" => Ok(Unit::Watts),
+            "kw" => Ok(Unit::Kilowatts),
+            "hp" | "bhp" => Ok(Unit::Horsepower),
+            "kwh" => Ok(Unit::KilowattHours),
+            "mj" => Ok(Unit::Megajoules),
+            "kwh/100km" => Ok(Unit::KilowattHoursPer100Kilometers),
+            "l/h" | "l/hr" | "lph" => Ok(Unit::LitresPerHour),
+            "gal/h" | "gal/hr" | "gph" => Ok(Unit::GallonsPerHour),
+            "l/100km" => Ok(Unit::LitresPer100Kilometers),
+            "km/l" => Ok(Unit::KilometersPerLitre),
+            "mpg" | "mpg(us)" | "mpgus" => Ok(Unit::MilesPerGallon),
+            "mpg(uk)" | "mpguk" | "mpg(imp)" => Ok(Unit::MilesPerGallonUk),
+            "nm" | "n·m" | "n.m" | "n-m" => Ok(Unit::NewtonMeters),
+            "ft-lb" | "ft-lbs" | "ft·lb" | "ftlb" | "lb-ft" | "lb·ft" | "lbft" => {
+                Ok(Unit::FootPounds)
+            }
             "ppm" => Ok(Unit::PartsPerMillion),
-            "mg/stroke" => Ok(Unit::MiligramsPerStroke),
-            "PSI" => Ok(Unit::PSI),
-            "mph" => Ok(Unit::MilesPerHour),
-            "m" => Ok(Unit::Meters),
-            "mi" => Ok(Unit::Miles),
-            "ft" => Ok(Unit::Feet),
-            "gal/h" => Ok(Unit::GallonsPerHour),
-            "ft-lb" => Ok(Unit::FootPounds),
+            "mg/stroke" | "mg/str" | "mg/stk" => Ok(Unit::MiligramsPerStroke),
+            "mg/m³" | "mg/m3" => Ok(Unit::MilligramsPerCubicMeter),
             _ => Err(ParseUnitError),
         }
     }
@@ -93,9 +172,12 @@ impl Unit {
             Unit::Ratio => "",
             Unit::Celsius => "°C",
             Unit::Fahrenheit => "°F",
+            Unit::Kelvin => "K",
             Unit::Degrees => "°",
             Unit::KiloPascal => "kPa",
             Unit::Pascal => "Pa",
+            Unit::Bar => "bar",
+            Unit::InchesOfMercury => "inHg",
             Unit::RPM => "RPM",
             Unit::KilometersPerHour => "km/h",
             Unit::GramsPerSecond => "g/s",
@@ -105,11 +187,25 @@ impl Unit {
             Unit::Minutes => "mins",
             Unit::Kilometers => "km",
             Unit::Milliampere => "mA",
+            Unit::Amperes => "A",
+            Unit::Watts => "W",
+            Unit::Kilowatts => "kW",
+            Unit::Horsepower => "hp",
+            Unit::KilowattHours => "kWh",
+            Unit::Megajoules => "MJ",
+            Unit::KilowattHoursPer100Kilometers => "kWh/100km",
             Unit::LitresPerHour => "L/h",
+            Unit::LitresPer100Kilometers => "L/100km",
+            Unit::KilometersPerLitre => "km/L",
+            Unit::MilesPerGallon => "mpg",
+            Unit::MilesPerGallonUk => "mpg (UK)",
             Unit::NewtonMeters => "Nm",
             Unit::KilogramsPerSecond => "Kg/s",
+            Unit::KilogramsPerHour => "kg/h",
+            Unit::PoundsPerMinute => "lb/min",
             Unit::PartsPerMillion => "ppm",
             Unit::MiligramsPerStroke => "mg/stroke",
+            Unit::MilligramsPerCubicMeter => "mg/m³",
             Unit::PSI => "PSI",
             Unit::MilesPerHour => "mph",
             Unit::Meters => "m",
@@ -121,9 +217,104 @@ impl Unit {
             Unit::Unknown => "",
         }
     }
+
+    /// `None` for `Unknown` and `NoData`.
+    pub fn dimension(&self) -> Option<Dimension> {
+        use Unit::*;
+
+        let dimension = match self {
+            Percent => Dimension::Percentage,
+            Ratio => Dimension::Ratio,
+            Degrees => Dimension::Angle,
+            Celsius | Fahrenheit | Kelvin => Dimension::Temperature,
+            KiloPascal | Pascal | Bar | InchesOfMercury | PSI => Dimension::Pressure,
+            RPM => Dimension::AngularSpeed,
+            KilometersPerHour | MilesPerHour => Dimension::Speed,
+            Kilometers | Meters | Miles | Feet => Dimension::Distance,
+            Seconds | Minutes | Hours => Dimension::Time,
+            Volts => Dimension::Voltage,
+            Milliampere | Amperes => Dimension::Current,
+            Watts | Kilowatts | Horsepower => Dimension::Power,
+            KilowattHours | Megajoules => Dimension::Energy,
+            KilowattHoursPer100Kilometers => Dimension::EnergyConsumption,
+            GramsPerSecond | KilogramsPerSecond | KilogramsPerHour | PoundsPerMinute => {
+                Dimension::MassFlow
+            }
+            LitresPerHour | GallonsPerHour => Dimension::VolumeFlow,
+            LitresPer100Kilometers | KilometersPerLitre | MilesPerGallon | MilesPerGallonUk => {
+                Dimension::FuelEconomy
+            }
+            NewtonMeters | FootPounds => Dimension::Torque,
+            PartsPerMillion => Dimension::Concentration,
+            MiligramsPerStroke => Dimension::MassPerStroke,
+            MilligramsPerCubicMeter => Dimension::MassConcentration,
+            Unknown | NoData => return None,
+        };
+
+        Some(dimension)
+    }
+
+    // Relative to the base unit of the dimension:
+    // °C, kPa, km/h, km, s, A, kW, kWh, g/s, L/h, L/100km and Nm.
+    fn scale(&self) -> Scale {
+        use Unit::*;
+
+        match self {
+            Fahrenheit => Scale::Linear(5.0 / 9.0, -160.0 / 9.0),
+            Kelvin => Scale::Linear(1.0, -273.15),
+
+            Pascal => Scale::Linear(0.001, 0.0),
+            Bar => Scale::Linear(100.0, 0.0),
+            InchesOfMercury => Scale::Linear(3.386_389, 0.0),
+            PSI => Scale::Linear(6.894_757, 0.0),
+
+            MilesPerHour | Miles => Scale::Linear(1.609_344, 0.0),
+            Meters => Scale::Linear(0.001, 0.0),
+            Feet => Scale::Linear(0.000_304_8, 0.0),
+
+            Minutes => Scale::Linear(60.0, 0.0),
+            Hours => Scale::Linear(3600.0, 0.0),
+
+            Milliampere => Scale::Linear(0.001, 0.0),
+
+            Watts => Scale::Linear(0.001, 0.0),
+            Horsepower => Scale::Linear(0.745_699_9, 0.0),
+            Megajoules => Scale::Linear(1.0 / 3.6, 0.0),
+
+            KilogramsPerSecond => Scale::Linear(1000.0, 0.0),
+            KilogramsPerHour => Scale::Linear(1000.0 / 3600.0, 0.0),
+            PoundsPerMinute => Scale::Linear(453.592_37 / 60.0, 0.0),
+
+            // US gallons
+            GallonsPerHour => Scale::Linear(3.785_412, 0.0),
+
+            KilometersPerLitre => Scale::Inverse(100.0),
+            MilesPerGallon => Scale::Inverse(235.214_6),
+            MilesPerGallonUk => Scale::Inverse(282.480_9),
+
+            FootPounds => Scale::Linear(1.355_818, 0.0),
+
+            _ => Scale::Linear(1.0, 0.0),
+        }
+    }
+
+    fn to_base(self, value: f32) -> f32 {
+        match self.scale() {
+            Scale::Linear(factor, offset) => value * factor + offset,
+            Scale::Inverse(k) => k / value,
+        }
+    }
+
+    // Value of the base unit in this unit
+    fn to_unit(self, base: f32) -> f32 {
+        match self.scale() {
+            Scale::Linear(factor, offset) => (base - offset) / factor,
+            Scale::Inverse(k) => k / base,
+        }
+    }
 }
 
-#[derive(Clone, Default, Debug)]
+#[derive(Clone, Default, Debug, Serialize, Deserialize)]
 pub struct Scalar {
     pub value: f32,
     pub unit: Unit,
@@ -139,91 +330,78 @@ impl fmt::Display for Scalar {
     }
 }
 
-// Doesn't matter what unit you are using.
+// `other` is converted to the unit of `self`.
+// No data if either is no data or they measure different things.
 impl Sub for Scalar {
     type Output = Scalar;
 
     fn sub(self, other: Self) -> Self::Output {
-        Scalar::new(self.value - other.value, self.unit, None)
+        self.checked_sub(&other).unwrap_or(Scalar::no_data())
     }
 }
 
-// Doesn't matter what unit you are using.
+// `other` is converted to the unit of `self`.
+// No data if either is no data or they measure different things.
 impl Add for Scalar {
     type Output = Scalar;
 
     fn add(self, other: Self) -> Self::Output {
-        Scalar::new(self.value + other.value, self.unit, None)
+        self.checked_add(&other).unwrap_or(Scalar::no_data())
     }
 }
 
 impl Scalar {
-    pub fn convert(&self, target_unit: Unit) -> Option<Self> {
-        use Unit::*;
-
-        match (&self.unit, target_unit) {
-            // Distance and speed
-            (Kilometers, Meters) => Some(Scalar::new(self.value * 1000.0, Meters, None)),
-            (Meters, Kilometers) => Some(Scalar::new(self.value / 1000.0, Kilometers, None)),
-
-            (KilometersPerHour, MilesPerHour) | (Kilometers, Miles) => {
-                Some(Scalar::new(self.value / 1.609, target_unit, None))
-            }
-
-            (MilesPerHour, KilometersPerHour) | (Miles, Kilometers) => {
-                Some(Scalar::new(self.value * 1.609, target_unit, None))
-            }
-
-            (Kilometers, Feet) => Some(Scalar::new(self.value * 83281.0, Feet, None)),
-            (Feet, Kilometers) => Some(Scalar::new(self.value / 83281.0, Kilometers, None)),
-
-            // Temperature
-            (Celsius, Fahrenheit) => Some(Scalar::new((self.value * 1.8) + 32.0, Fahrenheit, None)),
-            (Fahrenheit, Celsius) => Some(Scalar::new((self.value - 32.0) * 1.8, Celsius, None)),
+    /// Value in `unit`, converting from the user's preferred unit if needed.
+    /// `None` if the value can't be expressed in `unit`.
+    pub fn value_in(&self, unit: Unit) -> Option<f32> {
+        self.convert(unit).map(|converted| converted.value)
+    }
 
-            // Time
-            (Seconds, Minutes) => Some(Scalar::new(self.value / 60.0, Minutes, None)),
-            (Seconds, Hours) => Some(Scalar::new(self.value / 3600.0, Hours, None)),
-            (Minutes, Seconds) => Some(Scalar::new(self.value * 60.0, Seconds, None)),
-            (Minutes, Hours) => Some(Scalar::new(self.value / 60.0, Hours, None)),
-            (Hours, Seconds) => Some(Scalar::new(self.value * 3600.0, Seconds, None)),
-            (Hours, Minutes) => Some(Scalar::new(self.value * 60.0, Minutes, None)),
+    /// Convert to any unit of the same dimension.
+    /// `None` if the dimensions differ, or the value has no equivalent (e.g 0 km/L in L/100km).
+    pub fn convert(&self, target_unit: Unit) -> Option<Self> {
+        if self.unit == target_unit {
+            return Some(self.clone());
+        }
 
-            // Volume
-            (LitresPerHour, GallonsPerHour) => {
-                Some(Scalar::new(self.value * 0.264172, GallonsPerHour, None))
-            }
-            (GallonsPerHour, LitresPerHour) => {
-                Some(Scalar::new(self.value / 0.264172, LitresPerHour, None))
+        match (self.unit.dimension(), target_unit.dimension()) {
+            (Some(from), Some(to)) if from == to => {
+                let value = target_unit.to_unit(self.unit.to_base(self.value));
+                value
+                    .is_finite()
+                    .then(|| Scalar::new(value, target_unit, None))
             }
+            _ => None,
+        }
+    }
 
-            // Energy
-            (NewtonMeters, FootPounds) => Some(Scalar::new(self.value * 0.73756, FootPounds, None)),
-            (FootPounds, NewtonMeters) => {
-                Some(Scalar::new(self.value / 0.73756, NewtonMeters, None))
-            }
+    pub fn checked_add(&self, other: &Scalar) -> Option<Self> {
+        if self.unit == Unit::NoData {
+            return None;
+        }
 
-            // Pressure
-            (KiloPascal, PSI) => Some(Scalar::new(self.value / 6.895, Unit::PSI, None)),
-            (PSI, KiloPascal) => Some(Scalar::new(self.value * 6.895, Unit::KiloPascal, None)),
-            (KiloPascal, Pascal) => Some(Scalar::new(self.value * 1000.0, Unit::Pascal, None)),
-            (Pascal, KiloPascal) => Some(Scalar::new(self.value / 1000.0, Unit::KiloPascal, None)),
+        let other = other.value_in(self.unit)?;
+        Some(Scalar::new(self.value + other, self.unit, None))
+    }
 
-            (_, _) => None,
+    pub fn checked_sub(&self, other: &Scalar) -> Option<Self> {
+        if self.unit == Unit::NoData {
+            return None;
         }
+
+        let other = other.value_in(self.unit)?;
+        Some(Scalar::new(self.value - other, self.unit, None))
     }
 
     pub fn new(value: f32, unit: Unit, preferences: Option<UnitPreferences>) -> Self {
         if let Some(preferences) = preferences {
-            let target_unit = *match &unit {
-                Unit::KilometersPerHour | Unit::MilesPerHour => preferences.speed(),
-                Unit::Kilometers | Unit::Miles | Unit::Meters | Unit::Feet => {
-                    preferences.distance()
-                }
-                Unit::Celsius | Unit::Fahrenheit | Unit::Degrees => preferences.temp(),
-                Unit::NewtonMeters | Unit::FootPounds => preferences.torque(),
-                Unit::KiloPascal | Unit::Pascal | Unit::PSI => preferences.pressure(),
-                Unit::LitresPerHour | Unit::GallonsPerHour => preferences.flow_rate(),
+            let target_unit = *match unit.dimension() {
+                Some(Dimension::Speed) => preferences.speed(),
+                Some(Dimension::Distance) => preferences.distance(),
+                Some(Dimension::Temperature) => preferences.temp(),
+                Some(Dimension::Torque) => preferences.torque(),
+                Some(Dimension::Pressure) => preferences.pressure(),
+                Some(Dimension::VolumeFlow) => preferences.flow_rate(),
                 _ => &unit,
             };
 
@@ -1,4 +1,7 @@
-use obdium::{scalar::Scalar, BankNumber};
+use obdium::oxygen::OxygenSensorScaling;
+use obdium::registry::{self, PidDef, PidError, Poll};
+use obdium::sample::Sample;
+use obdium::scalar::{Scalar, Unit};
 use obdium::{Command, SensorNumber, Service, OBD, PAUSE_OBD_COUNT};
 use serde::{Deserialize, Serialize};
 use std::sync::atomic::Ordering;
@@ -10,41 +13,156 @@ use tauri::async_runtime::spawn;
 use tauri::{Emitter, WebviewWindow};
 use tokio::time;
 
-use crate::bridge::CUSTOM_PIDS_TRACKED;
-
-macro_rules! update {
-    ($win:expr, $($name:expr => $val:expr),* $(,)?) => {
-        $(
-            update_card($win, $name, $val);
-        )*
-    };
-}
+use crate::bridge::{events::do_record_dtc_history, Dtc, CUSTOM_PIDS_TRACKED, DRIVE_CYCLE};
 
 #[derive(Serialize, Deserialize, Clone)]
 struct Card {
     name: String,
     unit: String,
-    value: f32,
+
+    /// `None` when the value couldn't be read, `status` says why
+    value: Option<f32>,
+
+    /// "ok", or the kind of error (e.g "no data", "unsupported")
+    status: String,
+    error: Option<String>,
+}
+
+type CardValue = Result<Scalar, PidError>;
+
+impl Card {
+    fn new<T: Into<String>>(name: T, value: CardValue) -> Self {
+        match value {
+            Ok(scalar) => Card {
+                name: name.into(),
+                unit: scalar.unit.as_str().to_string().to_uppercase(),
+                value: Some((scalar.value * 100.0).round() / 100.0),
+                status: "ok".to_string(),
+                error: None,
+            },
+            Err(err) => Card {
+                name: name.into(),
+                unit: String::new(),
+                value: None,
+                status: err.as_str().to_string(),
+                error: Some(err.to_string()),
+            },
+        }
+    }
+
+    // Values outside the range of the pid are still shown, but flagged
+    fn from_sample(sample: Sample) -> Self {
+        let mut card = Card::new(sample.name, fitted(Ok(sample.value)));
+        if card.value.is_some() && !sample.valid {
+            card.status = "out of range".to_string();
+            card.error = Some("Value is outside the range SAE J1979 defines.".to_string());
+        }
+        card
+    }
 }
 
-fn update_card<T>(window: &WebviewWindow, name: T, scalar: Scalar)
+fn emit_card(window: &WebviewWindow, card: Card) {
+    window.emit("update-card", card).unwrap();
+}
+
+fn update_card<T>(window: &WebviewWindow, name: T, value: CardValue)
 where
     T: Into<String> + std::fmt::Debug,
 {
-    let card = Card {
-        name: name.into(),
-        unit: scalar.unit.as_str().to_string().to_uppercase(),
-        value: scalar.value.round(),
-    };
+    emit_card(window, Card::new(name, value));
+}
 
-    window.emit("update-card", card).unwrap();
+// A value from a reply that holds several (e.g one per sensor).
+// The reply came back, so a value that's missing isn't reported by the vehicle.
+fn fitted(value: CardValue) -> CardValue {
+    value.and_then(|scalar| {
+        if scalar.unit == Unit::NoData {
+            Err(PidError::Unsupported)
+        } else {
+            Ok(scalar)
+        }
+    })
 }
 
-pub fn critical_frequency_calls(window: &Arc<WebviewWindow>, obd: &Arc<Mutex<OBD>>) {
+type CardGetter = fn(&mut OBD) -> CardValue;
+
+/// Cards that aren't a service 01 pid, so aren't in the registry.
+/// Manufacturer (mode 22) values and values derived from several pids.
+const EXTRA_CARDS: &[(&str, Poll, CardGetter)] = &[
+    (
+        "Engine Oil Pressure",
+        Poll::High,
+        OBD::try_engine_oil_pressure,
+    ),
+    (
+        "Boost Gauge Pressure",
+        Poll::High,
+        OBD::try_boost_guage_pressure,
+    ),
+    ("Engine Oil Temp. (Mode 22)", Poll::LessFrequent, |obd| {
+        obd.try_engine_oil_temp(Service::Mode22)
+    }),
+    ("Engine Oil Temp. (Sensors: A)", Poll::LessFrequent, |obd| {
+        fitted(obd.try_engine_oil_temp_sensors().map(|temps| temps.0))
+    }),
+    ("Engine Oil Temp. (Sensors: B)", Poll::LessFrequent, |obd| {
+        fitted(obd.try_engine_oil_temp_sensors().map(|temps| temps.1))
+    }),
+    ("Engine Oil Life", Poll::Once, OBD::try_engine_oil_life),
+];
+
+/// Something to read for a card, or several cards.
+#[derive(Clone, Copy)]
+enum CardSource {
+    Pid(&'static PidDef),
+    Extra(&'static str, CardGetter),
+}
+
+impl CardSource {
+    fn read(&self, obd: &mut OBD) -> Vec<Card> {
+        match self {
+            CardSource::Pid(def) => match def.sample(obd) {
+                Ok(samples) => samples.into_iter().map(Card::from_sample).collect(),
+                Err(err) => def
+                    .cards
+                    .iter()
+                    .map(|name| Card::new(*name, Err(err.clone())))
+                    .collect(),
+            },
+            CardSource::Extra(name, getter) => vec![Card::new(*name, getter(obd))],
+        }
+    }
+}
+
+fn card_sources(poll: Poll) -> Vec<CardSource> {
+    let mut sources: Vec<CardSource> = registry::polled(poll).map(CardSource::Pid).collect();
+    sources.extend(
+        EXTRA_CARDS
+            .iter()
+            .filter(|(_, rate, _)| *rate == poll)
+            .map(|(name, _, getter)| CardSource::Extra(name, *getter)),
+    );
+    sources
+}
+
+/// Poll the cards of `poll` every `period`, `per_tick` sources at a time.
+fn poll_cards(
+    window: &Arc<WebviewWindow>,
+    obd: &Arc<Mutex<OBD>>,
+    poll: Poll,
+    period: Duration,
+    per_tick: usize,
+) {
     let window = Arc::clone(window);
     let obd = Arc::clone(obd);
     spawn(async move {
-        let mut interval = time::interval(Duration::from_millis(500));
+        let sources = card_sources(poll);
+        if sources.is_empty() {
+            return;
+        }
+
+        let mut interval = time::interval(period);
+        let mut chunks = sources.chunks(per_tick).cycle();
         loop {
             interval.tick().await;
             if PAUSE_OBD_COUNT.load(Ordering::Relaxed) > 0 {
@@ -56,547 +174,289 @@ pub fn critical_frequency_calls(window: &Arc<WebviewWindow>, obd: &Arc<Mutex<OBD
                 break;
             }
 
-            let rpm = obd.rpm();
-            let turbocharger_rpm = obd.turbocharger_rpm();
-            let vehicle_speed = obd.vehicle_speed();
+            let cards: Vec<Card> = chunks
+                .next()
+                .unwrap_or_default()
+                .iter()
+                .flat_map(|source| source.read(&mut obd))
+                .collect();
 
             drop(obd);
 
-            update!(
-                &window,
-                "Vehicle Speed" => vehicle_speed,
-                "Engine Speed" => rpm,
-                "Turbocharger RPM" => turbocharger_rpm,
-            );
+            for card in cards {
+                emit_card(&window, card);
+            }
         }
     });
 }
 
+pub fn critical_frequency_calls(window: &Arc<WebviewWindow>, obd: &Arc<Mutex<OBD>>) {
+    poll_cards(window, obd, Poll::Critical, Duration::from_millis(500), 3);
+}
+
 pub fn high_frequency_calls(window: &Arc<WebviewWindow>, obd: &Arc<Mutex<OBD>>) {
+    poll_cards(window, obd, Poll::High, Duration::from_secs(1), 5);
+}
+
+pub fn frequent_calls(window: &Arc<WebviewWindow>, obd: &Arc<Mutex<OBD>>) {
+    poll_cards(window, obd, Poll::Frequent, Duration::from_secs(4), 2);
+}
+
+pub fn less_frequent_calls(window: &Arc<WebviewWindow>, obd: &Arc<Mutex<OBD>>) {
+    poll_cards(window, obd, Poll::LessFrequent, Duration::from_secs(3), 6);
+}
+
+pub fn oxygen_sensors(window: &Arc<WebviewWindow>, obd: &Arc<Mutex<OBD>>) {
     let window = Arc::clone(window);
     let obd = Arc::clone(obd);
-
     spawn(async move {
-        let mut interval = time::interval(Duration::from_secs(1));
-        let mut cycle = 0;
+        let mut interval = time::interval(Duration::from_secs(6));
+        let mut cycles = 0;
+
+        // Sensors present and the pids they're read through,
+        // only known once connected
+        let mut sensors: Option<OxygenSensors> = None;
         loop {
             interval.tick().await;
             if PAUSE_OBD_COUNT.load(Ordering::Relaxed) > 0 {
                 continue;
             }
 
-            cycle = (cycle + 1) % 5;
-
-            match cycle {
-                0 => {
-                    // Cycle 1
-                    let (
-                        maf_air_flow_rate,
-                        engine_fuel_rate,
-                        engine_oil_pressure,
-                        drivers_demand_engine_torque,
-                        actual_engine_torque,
-                    ) = {
-                        let mut obd = obd.lock().unwrap();
-                        if !obd.is_connected() {
-                            break;
-                        }
+            cycles = (cycles + 1) % 3;
 
-                        (
-                            obd.maf_air_flow_rate(),
-                            obd.engine_fuel_rate(),
-                            obd.engine_oil_pressure(),
-                            obd.drivers_demand_engine_torque(),
-                            obd.actual_engine_torque(),
-                        )
-                    };
+            let mut obd = obd.lock().unwrap();
+            if !obd.is_connected() {
+                break;
+            }
 
-                    update!(
-                        &window,
-                        "MAF Airflow Rate" => maf_air_flow_rate,
-                        "Engine Fuel Rate" => engine_fuel_rate,
-                        "Engine Oil Pressure" => engine_oil_pressure,
-                        "Drivers Demand Engine Torque" => drivers_demand_engine_torque,
-                        "Actual Engine Torque" => actual_engine_torque,
-                    );
+            // the ECU may not have answered yet, so the layout
+            // is asked for again until it's known
+            if sensors.as_ref().is_none_or(|sensors| !sensors.discovered) {
+                sensors = Some(OxygenSensors::new(&mut obd));
+            }
+            let Some(sensors) = &sensors else {
+                continue;
+            };
+
+            let mut cards = Vec::new();
+            for (bit, sensor) in &sensors.sensors {
+                match cycles {
+                    0 if sensors.narrowband => {
+                        let read = obd.try_read_oxygen_sensor(sensor);
+                        cards.extend(oxygen_cards(
+                            0x14 + bit,
+                            [
+                                read.clone().map(|(voltage, _)| voltage),
+                                fitted(read.map(|(_, trim)| trim)),
+                            ],
+                        ));
+                    }
+                    1 if sensors.wideband_voltage => {
+                        let read = obd.try_read_oxygen_sensor_abcd(sensor, &sensors.scaling);
+                        cards.extend(oxygen_cards(
+                            0x24 + bit,
+                            [
+                                read.clone().map(|(ratio, _)| ratio),
+                                read.map(|(_, voltage)| voltage),
+                            ],
+                        ));
+                    }
+                    2 if sensors.wideband_current => {
+                        let read = obd.try_read_oxygen_sensor_current(sensor, &sensors.scaling);
+                        cards.extend(oxygen_cards(
+                            0x34 + bit,
+                            [
+                                read.clone().map(|(ratio, _)| ratio),
+                                read.map(|(_, current)| current),
+                            ],
+                        ));
+                    }
+                    _ => {}
                 }
-                1 => {
-                    // Cycle 2
-                    let (
-                        engine_load,
-                        ref_engine_torque,
-                        fuel_pressure,
-                        fuel_rail_pressure,
-                        fuel_rail_gauge_pressure,
-                    ) = {
-                        let mut obd = obd.lock().unwrap();
-                        (
-                            obd.engine_load(),
-                            obd.reference_engine_torque(),
-                            obd.fuel_pressure(),
-                            obd.fuel_rail_pressure(),
-                            obd.fuel_rail_guage_pressure(),
-                        )
-                    };
+            }
+            drop(obd);
 
-                    update!(
-                        &window,
-                        "Engine Load" => engine_load,
-                        "Reference Engine Torque" => ref_engine_torque,
-                        "Fuel Pressure" => fuel_pressure,
-                        "Fuel Rail Pressure" => fuel_rail_pressure,
-                        "Fuel Rail Gauge Pressure" => fuel_rail_gauge_pressure,
-                    );
-                }
-                2 => {
-                    // Cycle 3
-                    let (
-                        cylinder_fuel_rate,
-                        max_air_flow_rate_maf,
-                        timing_advance,
-                        boost_guage_pressure,
-                    ) = {
-                        let mut obd = obd.lock().unwrap();
-                        (
-                            obd.cylinder_fuel_rate(),
-                            obd.max_air_flow_rate_from_maf(),
-                            obd.timing_advance(),
-                            obd.boost_guage_pressure(),
-                        )
-                    };
+            for (name, value) in cards {
+                update_card(&window, name, value);
+            }
+        }
+    });
+}
 
-                    update!(
-                        &window,
-                        "Cylinder Fuel Rate" => cylinder_fuel_rate,
-                        "MAF Maximum Airflow Rate" => max_air_flow_rate_maf,
-                        "Timing Advance" => timing_advance,
-                        "Boost Gauge Pressure" => boost_guage_pressure,
-                    );
-                }
-                3 => {
-                    // Cycle 4
-                    let (
-                        throttle_pos,
-                        rel_throttle_pos,
-                        abs_throttle_b,
-                        abs_throttle_c,
-                        accel_pedal_d,
-                    ) = {
-                        let mut obd = obd.lock().unwrap();
-                        (
-                            obd.throttle_position(),
-                            obd.relative_throttle_pos(),
-                            obd.abs_throttle_position_b(),
-                            obd.abs_throttle_position_c(),
-                            obd.acc_pedal_position_d(),
-                        )
-                    };
+// Values of the oxygen sensor pid `pid` paired with its cards in the registry,
+// the graphs follow cards by those names
+fn oxygen_cards(pid: u8, values: [CardValue; 2]) -> Vec<(&'static str, CardValue)> {
+    registry::find(0x01, pid)
+        .map(|def| def.cards.iter().copied().zip(values).collect())
+        .unwrap_or_default()
+}
 
-                    update!(
-                        &window,
-                        "Throttle Pos." => throttle_pos,
-                        "Relative Throttle Pos." => rel_throttle_pos,
-                        "Abs. Throttle Pos. (D)" => abs_throttle_b,
-                        "Abs. Throttle Pos. (C)" => abs_throttle_c,
-                        "Accelerator Pedal Pos. (D)" => accel_pedal_d,
-                    );
-                }
-                4 => {
-                    // Cycle 5
-                    let (acc_pedal_pos_e, acc_pedal_pos_f, engine_torque_data) = {
-                        let mut obd = obd.lock().unwrap();
-                        (
-                            obd.acc_pedal_position_e(),
-                            obd.acc_pedal_position_f(),
-                            obd.engine_percent_torque_data(),
-                        )
-                    };
+struct OxygenSensors {
+    /// Each sensor with its bit in the layout,
+    /// it's read through pid 0114, 0124 and 0134 plus that bit
+    sensors: Vec<(u8, SensorNumber)>,
+    scaling: OxygenSensorScaling,
+
+    // Whether the vehicle reported its layout (0113 or 011D).
+    // Until it does every sensor is polled.
+    discovered: bool,
+
+    // Which kind of pid the vehicle reports for its sensors.
+    // 0114-011B narrowband, 0124-012B wideband voltage, 0134-013B wideband current.
+    narrowband: bool,
+    wideband_voltage: bool,
+    wideband_current: bool,
+}
 
-                    update!(
-                        &window,
-                        "Idle Engine Torque" => engine_torque_data.0,
-                        "Engine Point 1 Torque" => engine_torque_data.1,
-                        "Engine Point 2 Torque" => engine_torque_data.2,
-                        "Engine Point 3 Torque" => engine_torque_data.3,
-                        "Engine Point 4 Torque" => engine_torque_data.4,
-                        "Accelerator Pedal Pos. (E)" => acc_pedal_pos_e,
-                        "Accelerator Pedal Pos. (F)" => acc_pedal_pos_f,
-                    );
-                }
-                _ => unreachable!(),
-            }
+impl OxygenSensors {
+    fn new(obd: &mut OBD) -> Self {
+        // known already if a vehicle profile was loaded
+        if !obd.capabilities().is_known(0x01) {
+            obd.get_service_supported_pids("01");
         }
-    });
+
+        let supported = obd.capabilities().supported(0x01);
+        let supports_any = |first: u8| (first..first + 8).any(|pid| supported.contains(&pid));
+
+        // no list of supported pids, poll everything present
+        let unknown = supported.is_empty();
+        let scaling = obd.oxygen_sensor_scaling();
+        let locations = obd.get_oxygen_sensor_map();
+        let sensors = SensorNumber::ALL
+            .into_iter()
+            .zip(0..)
+            .filter(|(sensor, _)| {
+                locations.is_empty() || locations.iter().any(|location| location.sensor == *sensor)
+            })
+            .map(|(sensor, bit)| (bit, sensor))
+            .collect();
+
+        Self {
+            sensors,
+            scaling,
+            discovered: !locations.is_empty(),
+            narrowband: unknown || supports_any(0x14),
+            wideband_voltage: unknown || supports_any(0x24),
+            wideband_current: unknown || supports_any(0x34),
+        }
+    }
 }
 
-pub fn frequent_calls(window: &Arc<WebviewWindow>, obd: &Arc<Mutex<OBD>>) {
+pub fn custom_pid_calls(window: &Arc<WebviewWindow>, obd: &Arc<Mutex<OBD>>) {
     let window = Arc::clone(window);
     let obd = Arc::clone(obd);
     spawn(async move {
-        let mut interval = time::interval(Duration::from_secs(4));
-        let mut cycles = 0;
+        let mut interval = time::interval(Duration::from_secs(1));
         loop {
             interval.tick().await;
             if PAUSE_OBD_COUNT.load(Ordering::Relaxed) > 0 {
                 continue;
             }
 
-            cycles = (cycles + 1) % 2;
-            match cycles {
-                0 => {
-                    let (stft_bank_1, intake_air_temp, intake_manifold_abs_pressure) = {
-                        let mut obd = obd.lock().unwrap();
-                        if !obd.is_connected() {
-                            break;
+            {
+                let mut obd = obd.lock().unwrap();
+                let pids = CUSTOM_PIDS_TRACKED.lock().unwrap();
+                for (_, pid) in pids.iter() {
+                    let value = match obd.try_query(Command::new_arb(&pid.command)) {
+                        Ok(response) => {
+                            match obd.calculate_dynamic_equation(
+                                &pid.equation,
+                                &pid.unit,
+                                &response,
+                            ) {
+                                Ok(scalar) => Ok(scalar),
+
+                                // equation can't be evaluated, leave the card as it is
+                                Err(_) => continue,
+                            }
                         }
-                        (
-                            obd.short_term_fuel_trim(&BankNumber::Bank1),
-                            obd.intake_air_temp(),
-                            obd.intake_manifold_abs_pressure(),
-                        )
+                        Err(err) => Err(err),
                     };
-
-                    update!(&window,
-                        "Short Term Fuel Trim (Bank 1)" => stft_bank_1,
-                        "Intake Air Temp." => intake_air_temp,
-                        "Intake Manifold Abs. Pressure" => intake_manifold_abs_pressure
-                    );
+                    update_card(&window, &pid.name, value);
                 }
-                1 => {
-                    let stft_bank_2 = {
-                        let mut obd = obd.lock().unwrap();
-                        obd.short_term_fuel_trim(&BankNumber::Bank2)
-                    };
-
-                    update!(
-                        &window,
-                        "Short Term Fuel Trim (Bank 2)" => stft_bank_2,
-                    );
-                }
-                _ => unreachable!(),
             }
         }
     });
 }
 
-pub fn less_frequent_calls(window: &Arc<WebviewWindow>, obd: &Arc<Mutex<OBD>>) {
+pub fn once_calls(window: &Arc<WebviewWindow>, obd: &Arc<Mutex<OBD>>) {
     let window = Arc::clone(window);
     let obd = Arc::clone(obd);
     spawn(async move {
-        let mut interval = time::interval(Duration::from_secs(3));
-        let mut cycles = 0;
-        loop {
-            interval.tick().await;
-            if PAUSE_OBD_COUNT.load(Ordering::Relaxed) > 0 {
-                continue;
-            }
-
-            cycles = (cycles + 1) % 4;
-
-            match cycles {
-                0 => {
-                    let (
-                        ltft_bank_1,
-                        ltft_bank_2,
-                        coolant_temp,
-                        coolant_temp_sensors,
-                        engine_oil_temp,
-                        engine_oil_temp_ext,
-                        engine_oil_temp_sensors,
-                    ) = {
-                        let mut obd = obd.lock().unwrap();
-                        if !obd.is_connected() {
-                            break;
-                        }
-                        (
-                            obd.long_term_fuel_trim(&BankNumber::Bank1),
-                            obd.long_term_fuel_trim(&BankNumber::Bank2),
-                            obd.coolant_temp(),
-                            obd.coolant_temp_sensors(),
-                            obd.engine_oil_temp(Service::Mode01),
-                            obd.engine_oil_temp(Service::Mode22),
-                            obd.engine_oil_temp_sensors(),
-                        )
-                    };
-
-                    update!(
-                        &window,
-                        "Long Term Fuel Trim (Bank 1)" => ltft_bank_1,
-                        "Long Term Fuel Trim (Bank 2)" => ltft_bank_2,
-                        "Coolant Temp." => coolant_temp,
-                        "Engine Oil Temp. (Mode 22)" => engine_oil_temp_ext,
-                        "Engine Oil Temp. (Mode 01)" => engine_oil_temp,
-                        "Coolant Temp. (Sensors: A)" => coolant_temp_sensors.0,
-                        "Coolant Temp. (Sensors: B)" => coolant_temp_sensors.1,
-                        "Engine Oil Temp. (Sensors: A)" => engine_oil_temp_sensors.0,
-                        "Engine Oil Temp. (Sensors: B)" => engine_oil_temp_sensors.1,
-                    );
-                }
-                1 => {
-                    let (
-                        commanded_egr,
-                        egr_error,
-                        catalyst_temp_b1_s1,
-                        catalyst_temp_b1_s2,
-                        catalyst_temp_b2_s1,
-                        catalyst_temp_b2_s2,
-                    ) = {
-                        let mut obd = obd.lock().unwrap();
-                        (
-                            obd.commanded_egr(),
-                            obd.egr_error(),
-                            obd.catalyst_temp(BankNumber::Bank1, SensorNumber::Sensor1),
-                            obd.catalyst_temp(BankNumber::Bank1, SensorNumber::Sensor2),
-                            obd.catalyst_temp(BankNumber::Bank2, SensorNumber::Sensor1),
-                            obd.catalyst_temp(BankNumber::Bank2, SensorNumber::Sensor2),
-                        )
-                    };
-
-                    update!(
-                        &window,
-                        "Catalyst Temp. (Bank 1: Sensor 1)" => catalyst_temp_b1_s1,
-                        "Catalyst Temp. (Bank 1: Sensor 2)" => catalyst_temp_b1_s2,
-                        "Catalyst Temp. (Bank 2: Sensor 1)" => catalyst_temp_b2_s1,
-                        "Catalyst Temp. (Bank 2: Sensor 2)" => catalyst_temp_b2_s2,
-                        "Commanded EGR" => commanded_egr,
-                        "EGR Error" => egr_error,
-                    );
-                }
-                2 => {
-                    let (
-                        barometric_pressure,
-                        ambient_air_temp,
-                        max_values_for,
-                        fuel_injection_timing,
-                    ) = {
-                        let mut obd = obd.lock().unwrap();
-                        (
-                            obd.abs_barometric_pressure(),
-                            obd.ambient_air_temp(),
-                            obd.max_values_for(),
-                            obd.fuel_injection_timing(),
-                        )
-                    };
-
-                    update!(
-                        &window,
-                        "Absolute Barometric Pressure" => barometric_pressure,
-                        "Ambient Air Temp." => ambient_air_temp,
-                        "Fuel Injection Timing" => fuel_injection_timing,
-                        "Maximum AFR Value" => max_values_for.0,
-                        "Maximum O2 Sensor Voltage" => max_values_for.1,
-                        "Maximum O2 Sensor Current" => max_values_for.2,
-                        "Maximum Intake Abs. Pressure" => max_values_for.3,
-                    );
-                }
-                3 => {
-                    let (
-                        commanded_evap_purge,
-                        evap_sys_vapor_pressure,
-                        control_mod_voltage,
-                        engine_runtime,
-                    ) = {
-                        let mut obd = obd.lock().unwrap();
-                        (
-                            obd.commanded_evap_purge(),
-                            obd.evap_system_vapor_pressure(),
-                            obd.control_module_voltage(),
-                            obd.engine_runtime(),
-                        )
-                    };
-
-                    update!(
-                        &window,
-                        "Commanded EVAP Purge" => commanded_evap_purge,
-                        "EVAP System Vapor Pressure" =>evap_sys_vapor_pressure,
-                        "Control Module Voltage" => control_mod_voltage,
-                        "Engine Runtime (Session)" => engine_runtime,
-                    );
-                }
-                _ => unreachable!(),
-            }
+        let cards: Vec<Card> = {
+            let mut obd = obd.lock().unwrap();
+            card_sources(Poll::Once)
+                .iter()
+                .flat_map(|source| source.read(&mut obd))
+                .collect()
+        };
+
+        for card in cards {
+            emit_card(&window, card);
         }
     });
 }
 
-pub fn oxygen_sensors(window: &Arc<WebviewWindow>, obd: &Arc<Mutex<OBD>>) {
+/// Watch for new trouble codes while driving.
+/// Emits 'new-dtcs' when a code appears that wasn't reported before.
+pub fn dtc_watcher(window: &Arc<WebviewWindow>, obd: &Arc<Mutex<OBD>>) {
     let window = Arc::clone(window);
     let obd = Arc::clone(obd);
     spawn(async move {
-        let mut interval = time::interval(Duration::from_secs(6));
-        let mut cycles = 0;
+        let mut interval = time::interval(Duration::from_secs(30));
         loop {
             interval.tick().await;
             if PAUSE_OBD_COUNT.load(Ordering::Relaxed) > 0 {
                 continue;
             }
 
-            cycles = (cycles + 1) % 4;
-
-            match cycles {
-                0 => {
-                    let (o2_sensor_1, o2_sensor_2, o2_sensor_3, o2_sensor_4) = {
-                        let mut obd = obd.lock().unwrap();
-                        if !obd.is_connected() {
-                            break;
-                        }
-                        (
-                            obd.read_oxygen_sensor(&SensorNumber::Sensor1),
-                            obd.read_oxygen_sensor(&SensorNumber::Sensor2),
-                            obd.read_oxygen_sensor(&SensorNumber::Sensor3),
-                            obd.read_oxygen_sensor(&SensorNumber::Sensor4),
-                        )
-                    };
-
-                    update!(
-                        &window,
-                        "O2 Sensor (1) Voltage (1)" => o2_sensor_1.0,
-                        "O2 Sensor (1) STFT" => o2_sensor_1.1,
-                        "O2 Sensor (2) Voltage (1)" => o2_sensor_2.0,
-                        "O2 Sensor (2) STFT" => o2_sensor_2.1,
-                        "O2 Sensor (3) Voltage (1)" => o2_sensor_3.0,
-                        "O2 Sensor (3) STFT" => o2_sensor_3.1,
-                        "O2 Sensor (4) Voltage (1)" => o2_sensor_4.0,
-                        "O2 Sensor (4) STFT" => o2_sensor_4.1,
-                    );
-                }
-                1 => {
-                    let (
-                        o2_sensor_af_ratio_1,
-                        o2_sensor_af_ratio_2,
-                        o2_sensor_af_ratio_3,
-                        o2_sensor_af_ratio_4,
-                    ) = {
-                        let mut obd = obd.lock().unwrap();
-                        (
-                            obd.read_oxygen_sensor_abcd(&SensorNumber::Sensor1),
-                            obd.read_oxygen_sensor_abcd(&SensorNumber::Sensor2),
-                            obd.read_oxygen_sensor_abcd(&SensorNumber::Sensor3),
-                            obd.read_oxygen_sensor_abcd(&SensorNumber::Sensor4),
-                        )
-                    };
+            let mut obd = obd.lock().unwrap();
+            if !obd.is_connected() {
+                break;
+            }
 
-                    update!(
-                        &window,
-                        "O2 Sensor (1) AFR" => o2_sensor_af_ratio_1.0,
-                        "O2 Sensor (1) Voltage (2)" => o2_sensor_af_ratio_1.1,
-                        "O2 Sensor (2) AFR" => o2_sensor_af_ratio_2.0,
-                        "O2 Sensor (2) Voltage (2)" => o2_sensor_af_ratio_2.1,
-                        "O2 Sensor (3) AFR" => o2_sensor_af_ratio_3.0,
-                        "O2 Sensor (3) Voltage (2)" => o2_sensor_af_ratio_3.1,
-                        "O2 Sensor (4) AFR" => o2_sensor_af_ratio_4.0,
-                        "O2 Sensor (4) Voltage (2)" => o2_sensor_af_ratio_4.1,
-                    );
-                }
-                2 => {
-                    let (o2_sensor_5, o2_sensor_6, o2_sensor_7, o2_sensor_8) = {
-                        let mut obd = obd.lock().unwrap();
-                        (
-                            obd.read_oxygen_sensor(&SensorNumber::Sensor5),
-                            obd.read_oxygen_sensor(&SensorNumber::Sensor6),
-                            obd.read_oxygen_sensor(&SensorNumber::Sensor7),
-                            obd.read_oxygen_sensor(&SensorNumber::Sensor8),
-                        )
-                    };
+            // a failed read is ignored, it would mark every code cleared
+            let read = obd.read_all_trouble_codes();
+            let appeared = do_record_dtc_history(&mut obd, read.as_deref());
 
-                    update!(
-                        &window,
-                        "O2 Sensor (5) Voltage (1)" => o2_sensor_5.0,
-                        "O2 Sensor (5) STFT" => o2_sensor_5.1,
-                        "O2 Sensor (6) Voltage (1)" => o2_sensor_6.0,
-                        "O2 Sensor (6) STFT" => o2_sensor_6.1,
-                        "O2 Sensor (7) Voltage (1)" => o2_sensor_7.0,
-                        "O2 Sensor (7) STFT" => o2_sensor_7.1,
-                        "O2 Sensor (8) Voltage (1)" => o2_sensor_8.0,
-                        "O2 Sensor (8) STFT" => o2_sensor_8.1,
-                    );
-                }
-                3 => {
-                    let (
-                        o2_sensor_af_ratio_5,
-                        o2_sensor_af_ratio_6,
-                        o2_sensor_af_ratio_7,
-                        o2_sensor_af_ratio_8,
-                    ) = {
-                        let mut obd = obd.lock().unwrap();
-                        (
-                            obd.read_oxygen_sensor_abcd(&SensorNumber::Sensor5),
-                            obd.read_oxygen_sensor_abcd(&SensorNumber::Sensor6),
-                            obd.read_oxygen_sensor_abcd(&SensorNumber::Sensor7),
-                            obd.read_oxygen_sensor_abcd(&SensorNumber::Sensor8),
-                        )
-                    };
+            drop(obd);
 
-                    update!(
-                        &window,
-                            "O2 Sensor (5) AFR" => o2_sensor_af_ratio_5.0,
-                            "O2 Sensor (5) Voltage (2)" => o2_sensor_af_ratio_5.1,
-                            "O2 Sensor (6) AFR" => o2_sensor_af_ratio_6.0,
-                            "O2 Sensor (6) Voltage (2)" => o2_sensor_af_ratio_6.1,
-                            "O2 Sensor (7) AFR" => o2_sensor_af_ratio_7.0,
-                            "O2 Sensor (7) Voltage (2)" => o2_sensor_af_ratio_7.1,
-                            "O2 Sensor (8) AFR" => o2_sensor_af_ratio_8.0,
-                            "O2 Sensor (8) Voltage (2)" => o2_sensor_af_ratio_8.1,
-                    );
-                }
-                _ => unreachable!(),
+            if !appeared.is_empty() {
+                let appeared: Vec<Dtc> = appeared.into_iter().map(Dtc::from).collect();
+                window.emit("new-dtcs", appeared).unwrap();
             }
         }
     });
 }
 
-pub fn custom_pid_calls(window: &Arc<WebviewWindow>, obd: &Arc<Mutex<OBD>>) {
+/// Follow the current drive cycle monitors (0141).
+/// Emits 'monitors-completed' when monitors complete during the drive.
+pub fn drive_cycle_watcher(window: &Arc<WebviewWindow>, obd: &Arc<Mutex<OBD>>) {
     let window = Arc::clone(window);
     let obd = Arc::clone(obd);
     spawn(async move {
-        let mut interval = time::interval(Duration::from_secs(1));
+        let mut interval = time::interval(Duration::from_secs(15));
         loop {
             interval.tick().await;
             if PAUSE_OBD_COUNT.load(Ordering::Relaxed) > 0 {
                 continue;
             }
 
-            {
-                let mut obd = obd.lock().unwrap();
-                let pids = CUSTOM_PIDS_TRACKED.lock().unwrap();
-                for (_, pid) in pids.iter() {
-                    let response = obd.query(Command::new_arb(&pid.command));
-                    if let Ok(scalar) =
-                        obd.calculate_dynamic_equation(&pid.equation, &pid.unit, &response)
-                    {
-                        update!(&window, &pid.name => scalar);
-                    }
-                }
+            let mut obd = obd.lock().unwrap();
+            if !obd.is_connected() {
+                break;
             }
-        }
-    });
-}
 
-pub fn once_calls(window: &Arc<WebviewWindow>, obd: &Arc<Mutex<OBD>>) {
-    // Once calls
-    let window = Arc::clone(window);
-    let obd = Arc::clone(obd);
-    spawn(async move {
-        let mut obd = obd.lock().unwrap();
-        let warm_ups = obd.warm_ups_since_codes_cleared();
-        let dist_since = obd.distance_traveled_since_codes_cleared();
-        let dist_with = obd.distance_traveled_with_mil();
-        let time_since = obd.time_since_codes_cleared();
-        let time_with = obd.time_run_with_mil();
-        let odometer = obd.odometer();
-        let ethanol_fuel_percent = obd.ethanol_fuel_percentage();
-        let engine_oil_life = obd.engine_oil_life();
-
-        update!(
-            &window,
-                "Warm-Ups Since Codes Cleared" => warm_ups,
-                "Dist. Since Codes Cleared" => dist_since,
-                "Dist. With Check Engine Light" => dist_with,
-                "Time Since Codes Cleared" => time_since,
-                "Time With Check Engine Light" => time_with,
-                "Odometer" => odometer,
-                "Ethanol Fuel Percentage" => ethanol_fuel_percent,
-                "Engine Oil Life" => engine_oil_life,
-        );
+            let completed = {
+                let mut drive_cycle = DRIVE_CYCLE.lock().unwrap();
+                obd.update_drive_cycle(&mut drive_cycle)
+            };
+
+            drop(obd);
+
+            if !completed.is_empty() {
+                window.emit("monitors-completed", completed).unwrap();
+            }
+        }
     });
 }
@@ -0,0 +1,385 @@
+// UDS (ISO 14229) DTC reading.
+//
+// UDS and J1979-2 (OBD on UDS) report DTCs as 3 bytes. The first two are the
+// same as a SAE J1979 DTC, the third is a failure type byte describing how
+// the component failed (e.g P0420-13, circuit open). Each code comes with a
+// status byte and, when requested, a severity byte and functional unit.
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    diagnostics::{TroubleCode, TroubleCodeStatus},
+    reply::negative_response,
+    Command, OBD,
+};
+
+pub use crate::reply::Error;
+
+/// UDS service identifiers used by obdium
+pub mod service {
+    pub const READ_DTC_INFORMATION: u8 = 0x19;
+}
+
+/// ReadDTCInformation (0x19) sub-functions
+pub mod report {
+    pub const DTC_BY_STATUS_MASK: u8 = 0x02;
+    pub const DTC_BY_SEVERITY_MASK: u8 = 0x08;
+}
+
+/// Failure type byte (ISO 15031-6 / SAE J2012-DA) of a 3-byte DTC.
+///
+/// The high nibble is the failure category, the low nibble the sub type.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FailureType(pub u8);
+
+impl FailureType {
+    pub fn category(&self) -> &'static str {
+        match self.0 >> 4 {
+            0x0 => "General failure information",
+            0x1 => "General electrical failures",
+            0x2 => "General signal failures",
+            0x3 => "FM (frequency modulated) / PWM (pulse width modulated) failures",
+            0x4 => "System internal failures",
+            0x5 => "System programming failures",
+            0x6 => "Algorithm based failures",
+            0x7 => "Mechanical failures",
+            0x8 => "Bus signal / message failures",
+            0x9 => "Component failures",
+            0xF => "Manufacturer specific",
+            _ => "ISO/SAE reserved",
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self.0 {
+            0x00 => "No sub type information",
+            0x01 => "General electrical failure",
+            0x02 => "General signal failure",
+            0x03 => "FM (frequency modulated) / PWM (pulse width modulated) failure",
+            0x04 => "System internal failure",
+            0x05 => "System programming failure",
+            0x06 => "Algorithm based failure",
+            0x07 => "Mechanical failure",
+            0x08 => "Bus signal / message failure",
+            0x09 => "Component failure",
+            0x11 => "Circuit short to ground",
+            0x12 => "Circuit short to battery",
+            0x13 => "Circuit open",
+            0x14 => "Circuit short to ground or open",
+            0x15 => "Circuit short to battery or open",
+            0x16 => "Circuit voltage below threshold",
+            0x17 => "Circuit voltage above threshold",
+            0x18 => "Circuit current below threshold",
+            0x19 => "Circuit current above threshold",
+            0x1A => "Circuit resistance below threshold",
+            0x1B => "Circuit resistance above threshold",
+            0x1C => "Circuit voltage out of range",
+            0x1D => "Circuit current out of range",
+            0x1E => "Circuit resistance out of range",
+            0x1F => "Circuit intermittent",
+            0x21 => "Signal amplitude below minimum",
+            0x22 => "Signal amplitude above maximum",
+            0x23 => "Signal stuck low",
+            0x24 => "Signal stuck high",
+            0x25 => "Signal shape / waveform failure",
+            0x26 => "Signal rate of change below threshold",
+            0x27 => "Signal rate of change above threshold",
+            0x28 => "Signal bias level out of range / zero adjustment failure",
+            0x29 => "Signal invalid",
+            0x2A => "Signal stuck in range",
+            0x2B => "Signal cross coupled",
+            0x2F => "Signal erratic",
+            0x31 => "No signal",
+            0x32 => "Signal low time below minimum",
+            0x33 => "Signal low time above maximum",
+            0x34 => "Signal high time below minimum",
+            0x35 => "Signal high time above maximum",
+            0x36 => "Signal frequency too low",
+            0x37 => "Signal frequency too high",
+            0x38 => "Signal frequency incorrect",
+            0x39 => "Signal has too few pulses",
+            0x3A => "Signal has too many pulses",
+            0x41 => "General checksum failure",
+            0x42 => "General memory failure",
+            0x43 => "Special memory failure",
+            0x44 => "Data memory failure",
+            0x45 => "Program memory failure",
+            0x46 => "Calibration / parameter memory failure",
+            0x47 => "Watchdog / safety µC failure",
+            0x48 => "Supervision software failure",
+            0x49 => "Internal electronic failure",
+            0x4A => "Incorrect component installed",
+            0x4B => "Over temperature",
+            0x51 => "Not programmed",
+            0x52 => "Not activated",
+            0x53 => "Deactivated",
+            0x54 => "Missing calibration",
+            0x55 => "Not configured",
+            0x61 => "Signal calculation failure",
+            0x62 => "Signal compare failure",
+            0x63 => "Circuit / component protection time-out",
+            0x64 => "Signal plausibility failure",
+            0x65 => "Signal has too few transitions / events",
+            0x66 => "Signal has too many transitions / events",
+            0x67 => "Signal incorrect after event",
+            0x68 => "Event information",
+            0x71 => "Actuator stuck",
+            0x72 => "Actuator stuck open",
+            0x73 => "Actuator stuck closed",
+            0x74 => "Actuator slipping",
+            0x75 => "Emergency position not reachable",
+            0x76 => "Wrong mounting position",
+            0x77 => "Commanded position not reachable",
+            0x78 => "Alignment or adjustment incorrect",
+            0x79 => "Mechanical linkage failure",
+            0x7A => "Fluid leak or seal failure",
+            0x7B => "Low fluid level",
+            0x81 => "Invalid serial data received",
+            0x82 => "Alive / sequence counter incorrect / not updated",
+            0x83 => "Value of signal protection calculation incorrect",
+            0x84 => "Signal below allowable range",
+            0x85 => "Signal above allowable range",
+            0x86 => "Signal invalid",
+            0x87 => "Missing message",
+            0x88 => "Bus off",
+            0x8F => "Erratic",
+            0x91 => "Parametric",
+            0x92 => "Performance or incorrect operation",
+            0x93 => "No operation",
+            0x94 => "Unexpected operation",
+            0x95 => "Incorrect assembly",
+            0x96 => "Component internal failure",
+            0x97 => "Component or system operation obstructed or blocked",
+            0x98 => "Component or system over temperature",
+            _ => self.category(),
+        }
+    }
+}
+
+impl fmt::Display for FailureType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:02X} ({})", self.0, self.as_str())
+    }
+}
+
+/// DTC severity from the UDS severity byte (bits 5 to 7).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum DtcSeverity {
+    #[default]
+    NoSeverity,
+    MaintenanceOnly,
+    CheckAtNextHalt,
+    CheckImmediately,
+}
+
+impl DtcSeverity {
+    /// The most severe class set in `severity`
+    pub fn from_u8(severity: u8) -> Self {
+        if severity & 0x80 != 0 {
+            Self::CheckImmediately
+        } else if severity & 0x40 != 0 {
+            Self::CheckAtNextHalt
+        } else if severity & 0x20 != 0 {
+            Self::MaintenanceOnly
+        } else {
+            Self::NoSeverity
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::NoSeverity => "No severity information",
+            Self::MaintenanceOnly => "Maintenance only",
+            Self::CheckAtNextHalt => "Check at next halt",
+            Self::CheckImmediately => "Check immediately",
+        }
+    }
+}
+
+impl fmt::Display for DtcSeverity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// UDS DTC status byte (ISO 14229-1 DTCStatusMask)
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UdsDtcStatus(pub u8);
+
+impl UdsDtcStatus {
+    const BITS: [(u8, &'static str); 8] = [
+        (0x01, "test failed"),
+        (0x02, "test failed this operation cycle"),
+        (0x04, "pending"),
+        (0x08, "confirmed"),
+        (0x10, "test not completed since last clear"),
+        (0x20, "test failed since last clear"),
+        (0x40, "test not completed this operation cycle"),
+        (0x80, "warning indicator requested"),
+    ];
+
+    pub fn test_failed(&self) -> bool {
+        self.0 & 0x01 != 0
+    }
+
+    pub fn test_failed_this_operation_cycle(&self) -> bool {
+        self.0 & 0x02 != 0
+    }
+
+    pub fn pending(&self) -> bool {
+        self.0 & 0x04 != 0
+    }
+
+    pub fn confirmed(&self) -> bool {
+        self.0 & 0x08 != 0
+    }
+
+    pub fn test_not_completed_since_last_clear(&self) -> bool {
+        self.0 & 0x10 != 0
+    }
+
+    pub fn test_failed_since_last_clear(&self) -> bool {
+        self.0 & 0x20 != 0
+    }
+
+    pub fn test_not_completed_this_operation_cycle(&self) -> bool {
+        self.0 & 0x40 != 0
+    }
+
+    pub fn warning_indicator_requested(&self) -> bool {
+        self.0 & 0x80 != 0
+    }
+
+    /// Names of every bit that is set
+    pub fn flags(&self) -> Vec<&'static str> {
+        Self::BITS
+            .iter()
+            .filter(|(bit, _)| self.0 & bit != 0)
+            .map(|(_, name)| *name)
+            .collect()
+    }
+}
+
+impl fmt::Display for UdsDtcStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:#04X} [{}]", self.0, self.flags().join(", "))
+    }
+}
+
+impl OBD {
+    /// ReadDTCInformation, reportDTCByStatusMask (19 02).
+    ///
+    /// Returns every code, from every ECU, with a status matching `status_mask`
+    /// (0xFF for all codes, 0x08 for confirmed codes).
+    pub fn uds_read_dtcs(&mut self, status_mask: u8) -> Result<Vec<TroubleCode>, Error> {
+        let replies = self.uds_read_dtc_information(report::DTC_BY_STATUS_MASK, &[status_mask])?;
+
+        let mut codes = Vec::new();
+        for (ecu, data) in replies {
+            // <status availability mask> [<DTC high> <DTC middle> <DTC low> <status>]...
+            for record in data.get(1..).unwrap_or_default().chunks_exact(4) {
+                let mut code = TroubleCode::from_uds_bytes(
+                    [record[0], record[1], record[2]],
+                    UdsDtcStatus(record[3]),
+                );
+                code.ecu = Some(ecu.clone());
+                codes.push(code);
+            }
+        }
+
+        Ok(codes)
+    }
+
+    /// ReadDTCInformation, reportDTCBySeverityMaskRecord (19 08).
+    ///
+    /// Like `uds_read_dtcs` but also returns each code's severity and functional unit.
+    pub fn uds_read_dtcs_with_severity(
+        &mut self,
+        severity_mask: u8,
+        status_mask: u8,
+    ) -> Result<Vec<TroubleCode>, Error> {
+        let replies = self.uds_read_dtc_information(
+            report::DTC_BY_SEVERITY_MASK,
+            &[severity_mask, status_mask],
+        )?;
+
+        let mut codes = Vec::new();
+        for (ecu, data) in replies {
+            // <status availability mask>
+            // [<severity> <functional unit> <DTC high> <DTC middle> <DTC low> <status>]...
+            for record in data.get(1..).unwrap_or_default().chunks_exact(6) {
+                let mut code = TroubleCode::from_uds_bytes(
+                    [record[2], record[3], record[4]],
+                    UdsDtcStatus(record[5]),
+                );
+                code.severity = Some(DtcSeverity::from_u8(record[0]));
+                code.functional_unit = Some(record[1]);
+                code.ecu = Some(ecu.clone());
+                codes.push(code);
+            }
+        }
+
+        Ok(codes)
+    }
+
+    // Send a ReadDTCInformation request and return the positive replies by ECU,
+    // excluding the response service and sub-function bytes.
+    // Fails only if no ECU gave a positive reply.
+    fn uds_read_dtc_information(
+        &mut self,
+        sub_function: u8,
+        data: &[u8],
+    ) -> Result<Vec<(String, Vec<u8>)>, Error> {
+        let mut request = format!("{:02X}{:02X}", service::READ_DTC_INFORMATION, sub_function);
+        for byte in data {
+            request.push_str(&format!("{:02X}", byte));
+        }
+
+        let messages =
+            self.query_service(service::READ_DTC_INFORMATION, Command::new_arb(&request));
+        let mut replies = Vec::new();
+        let mut rejection = None;
+        for message in messages {
+            match message.data.as_slice() {
+                [response, echo, rest @ ..]
+                    if *response == service::READ_DTC_INFORMATION + 0x40
+                        && *echo == sub_function =>
+                {
+                    replies.push((message.ecu, rest.to_vec()));
+                }
+                _ => {
+                    rejection =
+                        rejection.or(negative_response(service::READ_DTC_INFORMATION, &message));
+                }
+            }
+        }
+
+        if replies.is_empty() {
+            return match rejection {
+                Some(code) => Err(Error::NegativeResponse {
+                    service: service::READ_DTC_INFORMATION,
+                    code,
+                }),
+                None => Err(Error::NoResponse(service::READ_DTC_INFORMATION)),
+            };
+        }
+
+        Ok(replies)
+    }
+}
+
+impl TroubleCodeStatus {
+    /// Build a status from a UDS DTC status byte.
+    ///
+    /// UDS has no permanant bit, permanant codes are only reported by service 0A.
+    pub fn from_uds(status: UdsDtcStatus) -> Self {
+        Self {
+            confirmed: status.confirmed(),
+            pending: status.pending(),
+            permanant: false,
+            uds: Some(status),
+        }
+    }
+}
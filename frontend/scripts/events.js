@@ -19,6 +19,16 @@ const connectButton = document.getElementById("btn-connect");
 const disconnectButton = document.getElementById("btn-disconnect");
 const demoStatus = document.getElementById("demo-status");
 
+// the value of a card, or why it couldn't be read
+// (e.g 'NO DATA', 'UNSUPPORTED')
+function cardValueText(payload) {
+  if (payload.value === null) {
+    return payload.status.toUpperCase();
+  }
+
+  return payload.value.toString();
+}
+
 listen("update-card", (event) => {
   const cards = document.querySelectorAll(".card");
   const exists = Array.from(cards).some((card) => {
@@ -40,19 +50,14 @@ listen("update-card", (event) => {
     const unitSpan = document.createElement("span");
 
     card.className = "card";
+    if (event.payload.error) card.title = event.payload.error;
     valueDiv.className = "value";
     unitSpan.className = "unit";
 
     h3.textContent = event.payload.name;
-    unitSpan.textContent =
-      event.payload.unit.toLowerCase() === "no data" ? "" : event.payload.unit;
+    unitSpan.textContent = event.payload.value === null ? "" : event.payload.unit;
 
-    // if the unit is no data meaning that scalar has 'no data'
-    // (see backend docs), then display 'N/A' for value
-    const valueText =
-      event.payload.unit.toLowerCase() === "no data"
-        ? "N/A"
-        : event.payload.value.toString();
+    const valueText = cardValueText(event.payload);
 
     // add value and unit to one div to align horizontally
     valueDiv.appendChild(document.createTextNode(valueText + " "));
@@ -86,22 +91,24 @@ listen("update-card", (event) => {
         );
 
         if (textNode) {
-          textNode.textContent =
-            event.payload.unit.toLowerCase() === "no data"
-              ? "N/A "
-              : event.payload.value.toString() + " ";
+          textNode.textContent = cardValueText(event.payload) + " ";
 
           const unitElem = valueElem.querySelector(".unit");
           unitElem.textContent =
-            event.payload.unit.toLowerCase() === "no data"
-              ? ""
-              : event.payload.unit;
+            event.payload.value === null ? "" : event.payload.unit;
+
+          if (event.payload.error) {
+            card.title = event.payload.error;
+          } else {
+            card.removeAttribute("title");
+          }
         }
       }
     }
   });
 
   // update any graphs that might be using this
+  if (event.payload.value === null) return;
   emit("update-graphs", {
     name: event.payload.name,
     value: event.payload.value,
@@ -127,6 +134,39 @@ listen("vehicle-details", (event) => {
   ).toUpperCase();
 });
 
+// vehicle did not report its vin, suggest the closest known vehicles
+listen("vehicle-matches", (event) => {
+  const vin = document.querySelector(".vin");
+  const makeModel = document.querySelector(".car-model");
+  const matches = event.payload;
+
+  vin.textContent = "VIN: UNAVAILABLE";
+  if (matches.length === 0) {
+    addNotification(
+      "VEHICLE VIN",
+      "The vehicle did not report its VIN. Enter it on the VIN screen."
+    );
+    return;
+  }
+
+  const best = matches[0];
+  const name = [best.model_year, best.make, best.model]
+    .filter((part) => part)
+    .join(" ")
+    .toUpperCase();
+
+  makeModel.textContent = "LIKELY " + name;
+  addNotification(
+    "VEHICLE VIN",
+    "The vehicle did not report its VIN. It looks like a " +
+      name +
+      (best.engine ? " (" + best.engine + ")" : "") +
+      ", " +
+      Math.round(best.score * 100) +
+      "% match. Enter the VIN on the VIN screen to confirm."
+  );
+});
+
 listen("connection-status", async (event) => {
   const protocolDropdown = document.getElementById("protocol-menu");
   const protocolSelected = document.getElementById("protocol-selected");
@@ -245,6 +285,10 @@ listen("update-pids", (event) => {
                 <div class="pid-value">${pidInfo.formula == "" ? "??" : pidInfo.formula}</div>
                 </div>
                 <div class="pid-column">
+                <div class="pid-label">BYTES</div>
+                <div class="pid-value">${pidInfo.bytes == 0 ? "??" : pidInfo.bytes}</div>
+                </div>
+                <div class="pid-column">
                 <div class="pid-label">UNIT</div>
                 <div class="pid-value">${pidInfo.unit == "" ? "??" : pidInfo.unit.toUpperCase()}</div>
                 </div>
@@ -258,12 +302,10 @@ listen("update-pids", (event) => {
 
     listenExpandPID(pidGroup);
 
-    addGraphDropdownOption(
-      pidInfo.pid,
-      pidInfo.pidName,
-      pidInfo.unit,
-      pidInfo.formula,
-    );
+    // graphs follow cards by name, one option per card
+    for (const card of pidInfo.cards) {
+      addGraphDropdownOption(card, card, pidInfo.unit, pidInfo.formula);
+    }
   }
 
   // Increment results counter
@@ -291,9 +333,27 @@ listen("update-dtcs", (event) => {
   dtcList.innerHTML = ``;
 
   for (const troubleCode of dtcs) {
-    const description = troubleCode.permanant
-      ? troubleCode.description + " [PERMANANT CODE]"
-      : troubleCode.description + " [PENDING CODE]";
+    let states = [];
+    if (troubleCode.confirmed) states.push("CONFIRMED");
+    if (troubleCode.pending) states.push("PENDING");
+    if (troubleCode.permanant) states.push("PERMANANT");
+
+    let description = troubleCode.description;
+    if (troubleCode.failureType) {
+      description += " - " + troubleCode.failureType;
+    }
+    description += " [" + states.join(", ") + " CODE]";
+    if (troubleCode.severity) {
+      description += " [" + troubleCode.severity.toUpperCase() + "]";
+    }
+    if (troubleCode.descriptionSource === "generic fallback") {
+      description += " (generic description, may differ for this make)";
+    } else if (troubleCode.descriptionSource !== "SAE" && troubleCode.descriptionSource !== "none") {
+      description += " (" + troubleCode.descriptionSource + " specific)";
+    }
+    if (troubleCode.ecu) {
+      description += " (ECU " + troubleCode.ecu + ")";
+    }
 
     let dtcRow = document.createElement("div");
     dtcRow.className = "info-row";
@@ -392,6 +452,28 @@ listen("update-readiness-tests", (event) => {
   }
 });
 
+listen("update-inspection-report", (event) => {
+  const report = event.payload;
+  const verdict = {
+    Pass: "PASS",
+    Exempt: "EXEMPT",
+    NotReady: "NOT READY",
+    Fail: "FAIL",
+  }[report.verdict];
+
+  const reasons = report.reasons.length > 0 ? report.reasons.join(" ") : "Ready for inspection.";
+
+  const verdictRow = document.createElement("div");
+  verdictRow.className = "info-row";
+  verdictRow.style = "justify-content: space-between;";
+  verdictRow.innerHTML = `
+<div class="name" id="inspection-verdict" style="flex: 2; font-weight: 700; color: #f7f3ff;">INSPECTION (${report.rules}): ${verdict}</div>
+    <div class="name" id="inspection-reasons" style="flex: 2; font-size: 0.97rem; font-weight: 500; color: #d4dadf;">${reasons}</div>
+    `;
+
+  readinessTests.prepend(verdictRow);
+});
+
 listen("update-command-output", (event) => {
   appendTerminalOutput(event.payload);
 });
@@ -446,4 +528,105 @@ listen("display-notification", (event) => {
   console.log(title, desc);
   
   addNotification(title, desc);
-});
\ No newline at end of file
+});
+
+listen("dtcs-cleared", (event) => {
+  const result = event.payload;
+
+  let message = "Cleared diagnostic trouble codes on " + result.clearedEcus.length + " ECU(s).";
+  if (result.snapshotPath) {
+    message += " Snapshot saved to " + result.snapshotPath + ".";
+  }
+  addNotification("TROUBLE CODES", message);
+
+  for (const [ecu, reason] of result.rejectedEcus) {
+    addNotification("TROUBLE CODES", "ECU " + ecu + " refused to clear codes: " + reason);
+  }
+
+  if (!result.verified) {
+    addNotification(
+      "TROUBLE CODES",
+      "Could not read the codes again, so it is not known whether they were cleared.",
+    );
+  }
+
+  for (const troubleCode of result.returned) {
+    addNotification("CODE RETURNED " + troubleCode.name, troubleCode.description);
+  }
+
+  // get permanant codes and codes that came back
+  // (codes that remain even after being cleared)
+  emit("get-dtcs");
+});
+
+listen("dtc-clear-failed", (event) => {
+  addNotification("TROUBLE CODES", event.payload);
+});
+
+listen("aftertreatment", (event) => {
+  const banks = event.payload;
+  if (banks.every((bank) => bank.health.findings.length === 0)) {
+    addNotification("AFTERTREATMENT", "No issues found with the DPF, DEF or SCR system.");
+    return;
+  }
+
+  // DEF, regeneration and inducement data is shared by both banks
+  const shown = new Set();
+  for (const bank of banks) {
+    const title = banks.length > 1 ? "AFTERTREATMENT BANK " + bank.bank : "AFTERTREATMENT";
+    for (const [level, finding] of bank.health.findings) {
+      if (shown.has(finding)) {
+        continue;
+      }
+
+      shown.add(finding);
+      addNotification(title + " (" + level.toUpperCase() + ")", finding);
+    }
+  }
+});
+
+listen("evap-test-started", (event) => {
+  addNotification(
+    "EVAP TEST",
+    "Leak test accepted by ECU " + event.payload.join(", ") + ". Waiting for results.",
+  );
+});
+
+listen("evap-test-results", (event) => {
+  const results = event.payload;
+  const failed = results.filter(
+    (test) => test.value < test.min || test.value > test.max,
+  ).length;
+
+  addNotification(
+    "EVAP TEST",
+    failed == 0
+      ? "Leak test passed (" + results.length + " results)."
+      : failed + " of " + results.length + " leak test results failed.",
+  );
+});
+
+listen("evap-test-failed", (event) => {
+  addNotification("EVAP TEST", event.payload);
+});
+
+listen("monitors-completed", (event) => {
+  for (const completion of event.payload) {
+    const speed = completion.conditions.vehicle_speed;
+    const coolant = completion.conditions.coolant_temp;
+    addNotification(
+      "MONITOR COMPLETE",
+      completion.monitor + " completed at " + speed.value.toFixed(0) + " " + speed.unit +
+        ", coolant " + coolant.value.toFixed(0) + " " + coolant.unit + ".",
+    );
+  }
+});
+
+listen("new-dtcs", (event) => {
+  const dtcs = event.payload;
+  if (!dtcs || dtcs.length === 0) return;
+
+  for (const troubleCode of dtcs) {
+    addNotification("NEW TROUBLE CODE " + troubleCode.name, troubleCode.description);
+  }
+});
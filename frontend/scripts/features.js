@@ -13,23 +13,13 @@ const obdGrid = document.getElementById("dashboard-cards");
 const terminalOutput = document.getElementById("terminal-output");
 
 export function clearDtcs() {
-  addNotification(
-    "TROUBLE CODES",
-    "Cleared all pending diagnostic trouble codes.",
-  );
-
   if (dtcList.innerHTML.trim() == "") {
     return;
   }
 
   // clear codes
+  // the result comes back in "dtcs-cleared" or "dtc-clear-failed"
   emit("clear-dtcs");
-  dtcHeader.textContent = "DIAGNOSTIC TROUBLE CODES (0)";
-  dtcList.innerHTML = ``;
-
-  // get permanant codes
-  // (codes that remain even after being cleared)
-  emit("get-dtcs");
 }
 
 export async function exportDtcs(autoSave) {
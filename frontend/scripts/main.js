@@ -147,14 +147,17 @@ dtcLogButton.addEventListener("click", () => exportDtcs(false));
 const logFileButton = document.getElementById("log-file-button");
 logFileButton.addEventListener("click", async () => {
   window.logFilePath = await save({
-    title: "Save as JSON",
-    defaultPath: "requests.json",
-    filters: [{ name: "JSON", extensions: ["json"] }],
+    title: "Save as JSON Lines",
+    defaultPath: "requests.jsonl",
+    filters: [
+      { name: "JSON Lines", extensions: ["jsonl"] },
+      { name: "Compressed JSON Lines", extensions: ["xz"] },
+    ],
   });
 
   // set default path
   if (!window.logFilePath) {
-    window.logFilePath = "./requests.json";
+    window.logFilePath = "./requests.jsonl";
     return;
   }
 
@@ -169,6 +172,16 @@ imTestRefreshButton.addEventListener("click", () => {
   emit("get-readiness-tests");
 });
 
+const imTestEvapButton = document.getElementById("readiness-test-evap");
+imTestEvapButton.addEventListener("click", () => {
+  emit("run-evap-test");
+});
+
+const imTestAftertreatmentButton = document.getElementById("readiness-test-aftertreatment");
+imTestAftertreatmentButton.addEventListener("click", () => {
+  emit("get-aftertreatment");
+});
+
 imTestExportButton.addEventListener("click", async () => {
   let totalJSON = [];
   imTestList.childNodes.forEach((testRow) => {